@@ -0,0 +1,227 @@
+/// Ocean-floor bathymetry.
+///
+/// Reshapes every below-sea-level tile using its distance to the nearest coast
+/// instead of leaving the continent FBM as the seafloor:
+///
+///  - **Continental shelf** — a gently sloping platform out to the shelf break.
+///    Shelves are wide on passive margins and narrow where the nearest coast
+///    sits on a volcanic chain (an active margin).
+///  - **Continental slope** — a smooth drop from the shelf break to the abyss.
+///  - **Abyssal plain** — a flat deep floor keeping a little of the original relief.
+///  - **Mid-ocean ridges** — ridged noise raised far from any coast.
+///  - **Trenches** — narrow, very deep troughs just offshore of active margins.
+use crate::grid::{Grid, unit_vector};
+use crate::noise::{fbm, ridged};
+use noise::Perlin;
+
+/// Depth below sea level at the coast (elevation units).
+const COAST_DEPTH: f32 = 0.02;
+/// Depth below sea level at the shelf break.
+const SHELF_BREAK: f32 = 0.10;
+/// Depth of the abyssal plain below sea level.
+const ABYSS_DEPTH: f32 = 0.62;
+/// Fraction of the original seafloor relief kept on the abyssal plain.
+const ABYSS_RELIEF: f32 = 0.3;
+/// Shelf width on a passive margin (km).
+const SHELF_KM: f32 = 160.0;
+/// Fraction of the shelf lost on a fully active margin.
+const ACTIVE_SHELF_LOSS: f32 = 0.7;
+/// Width of the continental slope (km).
+const SLOPE_KM: f32 = 250.0;
+/// Height of a mid-ocean ridge crest above the abyssal plain.
+const RIDGE_HEIGHT: f32 = 0.32;
+/// Ridged-noise value above which a ridge rises.
+const RIDGE_THRESHOLD: f32 = 0.88;
+/// Minimum volcanic zone at the nearest coast for it to count as an active margin.
+const TRENCH_MIN_ZONE: f32 = 0.25;
+/// Extra depth at the axis of a trench.
+const TRENCH_DEPTH: f32 = 0.38;
+/// Distance of the trench axis beyond the shelf break (km).
+const TRENCH_OFFSET_KM: f32 = 60.0;
+/// Half-width of a trench (km).
+const TRENCH_WIDTH_KM: f32 = 50.0;
+/// Water within this distance of land keeps the shore biomes (km).
+const COAST_KM: f32 = 25.0;
+
+/// Seafloor province of a tile, used by biome selection.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum SeaFloor {
+    /// Above sea level.
+    Land,
+    /// Shallow fringe right against the coast (beaches, marshes).
+    Coast,
+    Shelf,
+    Slope,
+    Abyss,
+    Ridge,
+    Trench,
+}
+
+/// Reshapes the seafloor in `elevation` and returns each tile's province.
+///
+/// - `volcanic_zone` effective volcanic zone, used to find active margins
+/// - `noise_scale`   the planet-size frequency multiplier from `generate_world`
+pub fn shape_seafloor(
+    grid: &Grid,
+    seed: u32,
+    elevation: &mut [f32],
+    volcanic_zone: &[f32],
+    sea_level: f32,
+    circumference_km: f32,
+    noise_scale: f64,
+) -> Vec<SeaFloor> {
+    let ridge_noise = Perlin::new(seed + 400);
+    let shelf_noise = Perlin::new(seed + 401);
+    let km_per_rad = circumference_km / (2.0 * std::f32::consts::PI);
+
+    let (dist, nearest) = grid.distance_from(|i| elevation[i] >= sea_level);
+
+    (0..grid.tile_count())
+        .map(|i| {
+            if elevation[i] >= sea_level {
+                return SeaFloor::Land;
+            }
+            let d_km = dist[i] as f32 * km_per_rad;
+            let active = match nearest[i] {
+                usize::MAX => 0.0,
+                c => volcanic_zone[c],
+            };
+            let active = ((active - TRENCH_MIN_ZONE) / (1.0 - TRENCH_MIN_ZONE)).clamp(0.0, 1.0);
+
+            let (q, r) = grid.coords(i);
+            let (lon, lat) = grid.lon_lat(q, r);
+            let [nx, ny, nz] = unit_vector(lon, lat).map(|c| c * noise_scale);
+
+            // Shelf width: noisy, and narrowed on active margins.
+            let wobble =
+                0.6 + 0.8 * (fbm(&shelf_noise, nx * 2.0, ny * 2.0, nz * 2.0, 3) * 0.5 + 0.5);
+            let shelf_km = SHELF_KM * wobble * (1.0 - ACTIVE_SHELF_LOSS * active);
+
+            let shelf_b = -COAST_DEPTH - (SHELF_BREAK - COAST_DEPTH) * (d_km / shelf_km).min(1.0);
+
+            let original_b = elevation[i] - sea_level;
+            let ridge_raw = ridged(&ridge_noise, nx * 1.6, ny * 1.6, nz * 1.6);
+            let ridge = ((ridge_raw - RIDGE_THRESHOLD) / (1.0 - RIDGE_THRESHOLD)).clamp(0.0, 1.0);
+            let abyss_b = -ABYSS_DEPTH + (original_b + 0.5) * ABYSS_RELIEF + ridge * RIDGE_HEIGHT;
+
+            let slope_t = smoothstep(shelf_km, shelf_km + SLOPE_KM, d_km);
+            // Ridges only rise once the slope has fully reached the abyss.
+            let ridge_visible = slope_t >= 1.0 && ridge > 0.3;
+
+            let trench_axis = shelf_km + TRENCH_OFFSET_KM;
+            let trench =
+                TRENCH_DEPTH * active * (-((d_km - trench_axis) / TRENCH_WIDTH_KM).powi(2)).exp();
+
+            let b = (shelf_b + (abyss_b - shelf_b) * slope_t - trench).min(-0.005);
+            elevation[i] = (sea_level + b).clamp(-1.0, 1.0);
+
+            if d_km <= COAST_KM {
+                SeaFloor::Coast
+            } else if trench > TRENCH_DEPTH * 0.5 {
+                SeaFloor::Trench
+            } else if d_km < shelf_km {
+                SeaFloor::Shelf
+            } else if ridge_visible {
+                SeaFloor::Ridge
+            } else if slope_t < 1.0 {
+                SeaFloor::Slope
+            } else {
+                SeaFloor::Abyss
+            }
+        })
+        .collect()
+}
+
+/// Hermite step from 0 at `a` to 1 at `b`.
+fn smoothstep(a: f32, b: f32, x: f32) -> f32 {
+    let t = ((x - a) / (b - a)).clamp(0.0, 1.0);
+    t * t * (3.0 - 2.0 * t)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Land (`0.2`) over the western half of a 512 × 256 grid, flat sea
+    /// (`-0.3`) over the eastern half, with the coast's volcanic zone set to
+    /// `zone`.  The planet is 10 000 km round, about 20 km a tile, so the
+    /// coastal fringe and trenches span whole tiles.  Returns the grid, the
+    /// reshaped elevation and the provinces.
+    fn half_ocean(zone: f32) -> (Grid, Vec<f32>, Vec<SeaFloor>) {
+        let grid = Grid::new(512, 256);
+        let n = grid.tile_count();
+        let mut elevation: Vec<f32> = (0..n)
+            .map(|i| if grid.coords(i).0 < 256 { 0.2 } else { -0.3 })
+            .collect();
+        let seafloor = shape_seafloor(&grid, 5, &mut elevation, &vec![zone; n], 0.0, 10_000.0, 1.0);
+        (grid, elevation, seafloor)
+    }
+
+    /// Elevation and province going east from the coast along the equator.
+    fn transect(grid: &Grid, elevation: &[f32], seafloor: &[SeaFloor]) -> Vec<(f32, SeaFloor)> {
+        (256..384)
+            .map(|q| grid.index(q, grid.height / 2))
+            .map(|i| (elevation[i], seafloor[i]))
+            .collect()
+    }
+
+    #[test]
+    fn passive_margins_step_from_shelf_to_abyss() {
+        let (grid, elevation, seafloor) = half_ocean(0.0);
+        for i in 0..grid.tile_count() {
+            let land = grid.coords(i).0 < 256;
+            assert_eq!(seafloor[i] == SeaFloor::Land, land);
+            if land {
+                assert_eq!(elevation[i], 0.2);
+            } else {
+                assert!(elevation[i] < 0.0 && seafloor[i] != SeaFloor::Trench);
+            }
+        }
+
+        // Provinces come in order going offshore, and the shelf only ever
+        // gets deeper.
+        let rank = |sf: SeaFloor| match sf {
+            SeaFloor::Land => 0,
+            SeaFloor::Coast => 1,
+            SeaFloor::Shelf => 2,
+            SeaFloor::Slope => 3,
+            _ => 4,
+        };
+        let line = transect(&grid, &elevation, &seafloor);
+        assert_eq!(line[0].1, SeaFloor::Coast);
+        assert!(line.windows(2).all(|w| rank(w[0].1) <= rank(w[1].1)));
+        for w in line.windows(2).filter(|w| rank(w[1].1) <= 2) {
+            assert!(w[1].0 <= w[0].0, "{w:?} rises");
+        }
+        assert!(line[0].0 >= -SHELF_BREAK);
+        let (abyss, _) = line[line.len() - 1];
+        assert!(abyss < -SHELF_BREAK && (abyss + ABYSS_DEPTH).abs() < RIDGE_HEIGHT + 0.2);
+    }
+
+    #[test]
+    fn active_margins_have_narrow_shelves_and_trenches() {
+        let (grid, passive_e, passive_sf) = half_ocean(0.0);
+        let (_, active_e, active_sf) = half_ocean(1.0);
+        let shelf = |sf: &[SeaFloor]| sf.iter().filter(|&&s| s == SeaFloor::Shelf).count();
+        assert!(shelf(&active_sf) < shelf(&passive_sf));
+
+        let line = transect(&grid, &active_e, &active_sf);
+        let passive = transect(&grid, &passive_e, &passive_sf);
+        let trench: Vec<usize> = (0..line.len())
+            .filter(|&k| line[k].1 == SeaFloor::Trench)
+            .collect();
+        assert!(!trench.is_empty());
+        for &k in &trench {
+            assert!(
+                line[k].0 < passive[k].0 - TRENCH_DEPTH * 0.5,
+                "{:?} vs {:?}",
+                line[k],
+                passive[k]
+            );
+        }
+        // The trench floor lies below the slope beyond it.
+        let floor = trench.iter().map(|&k| line[k].0).fold(f32::MAX, f32::min);
+        let beyond = line[trench[trench.len() - 1] + 1];
+        assert!(beyond.1 == SeaFloor::Slope && floor < beyond.0);
+    }
+}
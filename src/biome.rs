@@ -3,19 +3,29 @@
 /// This module owns the [`Biome`] type and all biome-related logic:
 ///  - [`Biome`]             — the enum itself (colour, name, sort order).
 ///  - [`planet_offsets`]    — climate deltas driven by planet archetype.
-///  - [`choose_biome`]      — altitude/temperature/moisture → base biome.
-///  - [`apply_volcanic`]    — optionally overrides biome with volcanic terrain.
+///  - [`choose_biome`]      — glacial/seafloor/altitude/temperature/moisture → base biome.
+///  - [`apply_volcanic`]    — overrides biome with eruption footprints.
 ///  - [`apply_planet_type`] — final remap to planet-exclusive biomes.
+use crate::bathymetry::SeaFloor;
+use crate::glaciation::GlacialFeature;
+use crate::volcanism::VolcanicFeature;
 use crate::world::PlanetType;
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 
 // ── Biome type ────────────────────────────────────────────────────────────────
 
-#[derive(Clone, Copy, Serialize, PartialEq, Eq, Hash)]
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq, Hash)]
 pub enum Biome {
     // ── Standard water ────────────────────────────────────────────────────────
     DeepOcean,
     Ocean,
+    // ── Seafloor provinces ────────────────────────────────────────────────────
+    /// Shallow continental shelf between the coast and the shelf break.
+    Shelf,
+    /// Deep oceanic trench offshore of a volcanic (active) margin.
+    Trench,
+    /// Coral reef on a warm, shallow shelf.
+    Reef,
     // ── Shore ─────────────────────────────────────────────────────────────────
     Beach,
     Wetland,
@@ -34,6 +44,11 @@ pub enum Biome {
     // ── High elevation ────────────────────────────────────────────────────────
     Mountain,
     Snow,
+    // ── Glacial margins ───────────────────────────────────────────────────────
+    /// Debris ridge dumped at the edge of a land-terminating ice sheet.
+    Moraine,
+    /// Glacier-carved coastal valley flooded by the sea.
+    Fjord,
     // ── Volcanic (Terran + Volcanic world) ────────────────────────────────────
     /// Active caldera / summit vent — molten rock at the peak.
     Volcano,
@@ -71,6 +86,10 @@ pub fn biome_color(b: Biome) -> [u8; 3] {
         // Water
         Biome::DeepOcean => [10, 20, 140],
         Biome::Ocean => [30, 70, 200],
+        // Seafloor provinces
+        Biome::Shelf => [60, 120, 215],
+        Biome::Trench => [5, 8, 80],
+        Biome::Reef => [60, 200, 190],
         // Shore
         Biome::Beach => [220, 210, 120],
         Biome::Wetland => [90, 140, 80],
@@ -89,6 +108,9 @@ pub fn biome_color(b: Biome) -> [u8; 3] {
         // High elevation
         Biome::Mountain => [130, 120, 110],
         Biome::Snow => [245, 245, 250],
+        // Glacial margins
+        Biome::Moraine => [150, 140, 125],
+        Biome::Fjord => [40, 95, 150],
         // Volcanic (Terran + Volcanic world)
         Biome::Volcano => [255, 50, 0],
         Biome::LavaField => [200, 80, 10],
@@ -114,6 +136,9 @@ pub fn biome_name(b: Biome) -> &'static str {
     match b {
         Biome::DeepOcean => "Deep Ocean",
         Biome::Ocean => "Ocean",
+        Biome::Shelf => "Shelf",
+        Biome::Trench => "Trench",
+        Biome::Reef => "Reef",
         Biome::Beach => "Beach",
         Biome::Wetland => "Wetland",
         Biome::IceCap => "Ice Cap",
@@ -127,6 +152,8 @@ pub fn biome_name(b: Biome) -> &'static str {
         Biome::Jungle => "Jungle",
         Biome::Mountain => "Mountain",
         Biome::Snow => "Snow",
+        Biome::Moraine => "Moraine",
+        Biome::Fjord => "Fjord",
         Biome::Volcano => "Volcano",
         Biome::LavaField => "Lava Field",
         Biome::AshLand => "Ash Land",
@@ -147,34 +174,75 @@ pub fn biome_order(b: Biome) -> u8 {
     match b {
         Biome::DeepOcean => 0,
         Biome::Ocean => 1,
-        Biome::Beach => 2,
-        Biome::Wetland => 3,
-        Biome::IceCap => 4,
-        Biome::Tundra => 5,
-        Biome::Taiga => 6,
-        Biome::Shrubland => 7,
-        Biome::Plain => 8,
-        Biome::Forest => 9,
-        Biome::Savanna => 10,
-        Biome::Desert => 11,
-        Biome::Jungle => 12,
-        Biome::Mountain => 13,
-        Biome::Snow => 14,
-        Biome::Volcano => 15,
-        Biome::LavaField => 16,
-        Biome::AshLand => 17,
-        Biome::MagmaSea => 18,
-        Biome::ScorchedWaste => 19,
-        Biome::FrozenOcean => 20,
-        Biome::GlacialPlain => 21,
-        Biome::CausticLake => 22,
-        Biome::ToxicSwamp => 23,
-        Biome::AcidFlatland => 24,
-        Biome::RockyWaste => 25,
-        Biome::DustPlain => 26,
+        Biome::Shelf => 2,
+        Biome::Trench => 3,
+        Biome::Reef => 4,
+        Biome::Beach => 5,
+        Biome::Wetland => 6,
+        Biome::IceCap => 7,
+        Biome::Tundra => 8,
+        Biome::Taiga => 9,
+        Biome::Shrubland => 10,
+        Biome::Plain => 11,
+        Biome::Forest => 12,
+        Biome::Savanna => 13,
+        Biome::Desert => 14,
+        Biome::Jungle => 15,
+        Biome::Mountain => 16,
+        Biome::Snow => 17,
+        Biome::Moraine => 18,
+        Biome::Fjord => 19,
+        Biome::Volcano => 20,
+        Biome::LavaField => 21,
+        Biome::AshLand => 22,
+        Biome::MagmaSea => 23,
+        Biome::ScorchedWaste => 24,
+        Biome::FrozenOcean => 25,
+        Biome::GlacialPlain => 26,
+        Biome::CausticLake => 27,
+        Biome::ToxicSwamp => 28,
+        Biome::AcidFlatland => 29,
+        Biome::RockyWaste => 30,
+        Biome::DustPlain => 31,
     }
 }
 
+/// Every biome, in [`biome_order`].
+pub const BIOMES: [Biome; 32] = [
+    Biome::DeepOcean,
+    Biome::Ocean,
+    Biome::Shelf,
+    Biome::Trench,
+    Biome::Reef,
+    Biome::Beach,
+    Biome::Wetland,
+    Biome::IceCap,
+    Biome::Tundra,
+    Biome::Taiga,
+    Biome::Shrubland,
+    Biome::Plain,
+    Biome::Forest,
+    Biome::Savanna,
+    Biome::Desert,
+    Biome::Jungle,
+    Biome::Mountain,
+    Biome::Snow,
+    Biome::Moraine,
+    Biome::Fjord,
+    Biome::Volcano,
+    Biome::LavaField,
+    Biome::AshLand,
+    Biome::MagmaSea,
+    Biome::ScorchedWaste,
+    Biome::FrozenOcean,
+    Biome::GlacialPlain,
+    Biome::CausticLake,
+    Biome::ToxicSwamp,
+    Biome::AcidFlatland,
+    Biome::RockyWaste,
+    Biome::DustPlain,
+];
+
 // ── Planet climate offsets ────────────────────────────────────────────────────
 
 /// Returns `(Δtemperature, Δmoisture, Δvolcanic_zone)` for the given planet
@@ -196,29 +264,50 @@ pub fn planet_offsets(pt: PlanetType) -> (f32, f32, f32) {
 // ── Top-level biome selector ──────────────────────────────────────────────────
 
 /// Selects the final biome for a tile by running the full pipeline:
-/// altitude band → volcanic override → planet remapping.
+/// glacial landform or altitude band → volcanic override → planet remapping.
 ///
 /// - `e`   elevation     in `[-1, 1]`
 /// - `m`   moisture      in `[-1, 1]`  (`> 0` is wet)
 /// - `t`   temperature   in `[ 0, 1]`  (`0` = polar, `1` = equatorial)
-/// - `vz`  volcanic_zone in `[ 0, 1]`  (`0` = inert, `1` = fully volcanic)
+/// - `sf`  seafloor province from the bathymetry pass (water tiles only)
+/// - `gf`  landform left by the ice-sheet simulation (takes precedence over altitude)
+/// - `vf`  footprint left by the lava-flow simulation (takes precedence over both)
 /// - `pt`  planet archetype — governs the final biome remapping pass
-pub fn choose_biome(e: f32, m: f32, t: f32, vz: f32, pt: PlanetType) -> Biome {
-    let base = if e < -0.15 {
-        ocean_biome(e)
-    } else if e < 0.0 {
-        shore_biome(t, m)
-    } else if e > 0.7 {
-        highland_biome(e, t)
-    } else {
-        land_biome(t, m)
+pub fn choose_biome(
+    e: f32,
+    m: f32,
+    t: f32,
+    sf: SeaFloor,
+    gf: GlacialFeature,
+    vf: VolcanicFeature,
+    pt: PlanetType,
+) -> Biome {
+    let base = match gf {
+        GlacialFeature::Sheet => Biome::IceCap,
+        GlacialFeature::Moraine => Biome::Moraine,
+        GlacialFeature::Fjord => Biome::Fjord,
+        GlacialFeature::None if e < 0.0 => water_biome(e, m, t, sf),
+        GlacialFeature::None if e > 0.7 => highland_biome(e, t),
+        GlacialFeature::None => land_biome(t, m),
     };
-    let after_volcano = apply_volcanic(base, e, vz);
+    let after_volcano = apply_volcanic(base, vf);
     apply_planet_type(after_volcano, pt)
 }
 
 // ── Altitude bands ────────────────────────────────────────────────────────────
 
+/// Below sea level: the seafloor province picks the biome.  Only the thin
+/// coastal fringe keeps the shore biomes; reefs need warm, shallow shelves.
+fn water_biome(e: f32, m: f32, t: f32, sf: SeaFloor) -> Biome {
+    match sf {
+        SeaFloor::Coast | SeaFloor::Land => shore_biome(t, m),
+        SeaFloor::Shelf if t > 0.55 && e > -0.05 => Biome::Reef,
+        SeaFloor::Shelf => Biome::Shelf,
+        SeaFloor::Trench => Biome::Trench,
+        SeaFloor::Slope | SeaFloor::Abyss | SeaFloor::Ridge => ocean_biome(e),
+    }
+}
+
 fn ocean_biome(e: f32) -> Biome {
     if e < -0.45 {
         Biome::DeepOcean
@@ -228,8 +317,10 @@ fn ocean_biome(e: f32) -> Biome {
 }
 
 fn shore_biome(t: f32, m: f32) -> Biome {
+    // Ice-covered shores are already `IceCap` via the glaciation pass; an
+    // ice-free polar shore is bare, frozen ground.
     if t < 0.15 {
-        Biome::IceCap // frozen shore / pack ice
+        Biome::Tundra
     } else if m > 0.3 {
         Biome::Wetland // mangroves / marshes
     } else {
@@ -249,7 +340,8 @@ fn highland_biome(e: f32, t: f32) -> Biome {
 
 fn land_biome(t: f32, m: f32) -> Biome {
     if t < 0.15 {
-        return Biome::IceCap; // polar
+        // Too dry to grow an ice sheet: polar desert.
+        return Biome::Tundra;
     }
     if t < 0.30 {
         return boreal_biome(m);
@@ -286,31 +378,19 @@ fn tropical_biome(m: f32) -> Biome {
 
 // ── Volcanic modifier ─────────────────────────────────────────────────────────
 
-/// Optionally overrides a biome when it sits inside an active volcanic zone.
-///
-/// - `vz` volcanic_zone in `[0, 1]`: `0` = inert, higher = stronger activity.
-/// - `e`  biome elevation, distinguishes caldera from flank from foothill.
+/// Overrides a biome with the footprint left by an eruption.
 ///
-/// Override ladder (strongest condition wins):
-/// - **Volcano**   — summit/caldera : `Mountain|Snow`, `e > 0.80`, `vz > 0.55`
-/// - **LavaField** — flanks         : `Mountain|Snow`,             `vz > 0.30`
-/// - **AshLand**   — lower slopes   : `Mountain|Snow|Shrubland|Plain|Tundra`, `e > 0.30`, `vz > 0.15`
-pub fn apply_volcanic(biome: Biome, e: f32, vz: f32) -> Biome {
-    if vz <= 0.0 {
-        return biome;
-    }
-    match biome {
-        // Summit / caldera → active vent
-        Biome::Mountain | Biome::Snow if e > 0.80 && vz > 0.55 => Biome::Volcano,
-        // Volcanic flanks → cooling lava flows
-        Biome::Mountain | Biome::Snow if vz > 0.30 => Biome::LavaField,
-        // Lower slopes and surrounding terrain → ash wasteland
-        Biome::Mountain | Biome::Snow | Biome::Shrubland | Biome::Plain | Biome::Tundra
-            if e > 0.30 && vz > 0.15 =>
-        {
-            Biome::AshLand
-        }
-        other => other,
+/// The footprints come from [`crate::volcanism::erupt`], which only marks
+/// tiles above sea level, so water biomes are never replaced.
+/// - **Volcano**   — active summit vent
+/// - **LavaField** — tiles crossed by a lava flow
+/// - **AshLand**   — ash-fall blanket around an active vent
+pub fn apply_volcanic(biome: Biome, vf: VolcanicFeature) -> Biome {
+    match vf {
+        VolcanicFeature::None => biome,
+        VolcanicFeature::Vent => Biome::Volcano,
+        VolcanicFeature::Lava => Biome::LavaField,
+        VolcanicFeature::Ash => Biome::AshLand,
     }
 }
 
@@ -327,7 +407,13 @@ pub fn apply_planet_type(biome: Biome, pt: PlanetType) -> Biome {
         // ── Volcanic world ────────────────────────────────────────────────────
         // Ocean basins fill with magma; lowlands are scoured to bare rock.
         PlanetType::Volcanic => match biome {
-            Biome::DeepOcean | Biome::Ocean => Biome::MagmaSea,
+            Biome::DeepOcean
+            | Biome::Ocean
+            | Biome::Shelf
+            | Biome::Trench
+            | Biome::Reef
+            | Biome::Fjord => Biome::MagmaSea,
+            Biome::Moraine => Biome::ScorchedWaste,
             Biome::Beach | Biome::Wetland => Biome::AshLand,
             Biome::Plain | Biome::Shrubland | Biome::Savanna | Biome::Desert => {
                 Biome::ScorchedWaste
@@ -342,35 +428,52 @@ pub fn apply_planet_type(biome: Biome, pt: PlanetType) -> Biome {
         // ── Frozen world ──────────────────────────────────────────────────────
         // Oceans are sealed under ice; temperate zones become permafrost plains.
         PlanetType::Frozen => match biome {
-            Biome::DeepOcean | Biome::Ocean | Biome::MagmaSea => Biome::FrozenOcean,
+            Biome::DeepOcean
+            | Biome::Ocean
+            | Biome::Shelf
+            | Biome::Trench
+            | Biome::Reef
+            | Biome::MagmaSea => Biome::FrozenOcean,
             Biome::Beach | Biome::Wetland => Biome::IceCap,
             Biome::Plain | Biome::Shrubland => Biome::GlacialPlain,
             Biome::Forest | Biome::Jungle => Biome::Taiga,
             Biome::Savanna | Biome::Desert => Biome::GlacialPlain,
             Biome::LavaField | Biome::AshLand | Biome::ScorchedWaste => Biome::GlacialPlain,
-            other => other, // Tundra, IceCap, Taiga, Snow, Mountain — keep as-is
+            other => other, // Tundra, IceCap, Taiga, Snow, Mountain, Moraine, Fjord — keep as-is
         },
 
         // ── Caustic world ─────────────────────────────────────────────────────
         // Oceans become acid seas; vegetation zones drown in toxic runoff.
         PlanetType::Caustic => match biome {
-            Biome::DeepOcean | Biome::Ocean => Biome::CausticLake,
+            Biome::DeepOcean
+            | Biome::Ocean
+            | Biome::Shelf
+            | Biome::Trench
+            | Biome::Reef
+            | Biome::Fjord => Biome::CausticLake,
             Biome::Beach | Biome::Wetland | Biome::Forest | Biome::Jungle | Biome::Taiga => {
                 Biome::ToxicSwamp
             }
             Biome::Plain | Biome::Shrubland | Biome::Savanna | Biome::Tundra | Biome::Desert => {
                 Biome::AcidFlatland
             }
-            Biome::IceCap | Biome::Snow | Biome::GlacialPlain => Biome::AcidFlatland,
+            Biome::IceCap | Biome::Snow | Biome::GlacialPlain | Biome::Moraine => {
+                Biome::AcidFlatland
+            }
             other => other, // Mountain, LavaField, AshLand, Volcano — keep as-is
         },
 
         // ── Barren world ──────────────────────────────────────────────────────
         // No liquid water; all life extinct; only rock and dust remain.
         PlanetType::Barren => match biome {
-            Biome::DeepOcean | Biome::Ocean | Biome::CausticLake | Biome::FrozenOcean => {
-                Biome::RockyWaste
-            }
+            Biome::DeepOcean
+            | Biome::Ocean
+            | Biome::Shelf
+            | Biome::Trench
+            | Biome::Reef
+            | Biome::Fjord
+            | Biome::CausticLake
+            | Biome::FrozenOcean => Biome::RockyWaste,
             Biome::Beach | Biome::Wetland | Biome::ToxicSwamp => Biome::RockyWaste,
             Biome::Plain
             | Biome::Shrubland
@@ -384,7 +487,7 @@ pub fn apply_planet_type(biome: Biome, pt: PlanetType) -> Biome {
             Biome::LavaField | Biome::AshLand | Biome::ScorchedWaste | Biome::Snow => {
                 Biome::RockyWaste
             }
-            other => other, // Mountain, Volcano — keep as-is
+            other => other, // Mountain, Volcano, Moraine — keep as-is
         },
     }
 }
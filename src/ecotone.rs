@@ -0,0 +1,128 @@
+/// Biome ecotones — soft transitions between neighbouring biomes.
+///
+/// Biome selection is a set of hard thresholds on temperature and moisture,
+/// which draws ruler-straight borders (e.g. the Taiga/Plain temperature band).
+/// Two things soften them:
+///
+///  - **Dithering** — a low-amplitude noise field nudges the temperature and
+///    moisture fed to the thresholds, so borders wander organically.
+///  - **Transition weights** — each tile probes the classifier a little way
+///    along both climate axes.  The nearest different biome becomes the tile's
+///    secondary biome, weighted by how close the threshold is: `0.5` right on
+///    the border, fading to `0.0` beyond [`TEMPERATURE_WIDTH`] / [`MOISTURE_WIDTH`].
+use crate::noise::{fbm, fbm_detail};
+use crate::world::Biome;
+use noise::Perlin;
+
+/// Peak temperature nudge applied by the dither field.
+const TEMPERATURE_DITHER: f32 = 0.035;
+/// Peak moisture nudge applied by the dither field (moisture spans twice the range).
+const MOISTURE_DITHER: f32 = 0.07;
+/// Half-width of a transition zone along the temperature axis.
+const TEMPERATURE_WIDTH: f32 = 0.06;
+/// Half-width of a transition zone along the moisture axis.
+const MOISTURE_WIDTH: f32 = 0.12;
+/// Probe positions within the transition zone, as fractions of its width.
+const PROBES: [f32; 4] = [0.25, 0.5, 0.75, 1.0];
+
+/// Dither field for one tile: `(Δtemperature, Δmoisture)`.
+///
+/// `nx, ny, nz` are the tile's unit-sphere coordinates, already multiplied by
+/// the planet's noise scale.  Sampled at a high frequency so it only
+/// roughens borders rather than moving whole climate zones.
+pub fn dither(noise: &Perlin, nx: f64, ny: f64, nz: f64) -> (f32, f32) {
+    let dt = fbm(noise, nx * 12.0, ny * 12.0, nz * 12.0, 3);
+    let dm = fbm(
+        noise,
+        nx * 12.0 + 31.7,
+        ny * 12.0 + 7.1,
+        nz * 12.0 + 19.3,
+        3,
+    );
+    (dt * TEMPERATURE_DITHER, dm * MOISTURE_DITHER)
+}
+
+/// Finer octaves of [`dither`] for `extra` octaves beyond the generator's,
+/// added on top of it when rendering at more than the world's resolution.
+pub fn dither_detail(noise: &Perlin, nx: f64, ny: f64, nz: f64, extra: u32) -> (f32, f32) {
+    let dt = fbm_detail(noise, nx * 12.0, ny * 12.0, nz * 12.0, 3, extra);
+    let dm = fbm_detail(
+        noise,
+        nx * 12.0 + 31.7,
+        ny * 12.0 + 7.1,
+        nz * 12.0 + 19.3,
+        3,
+        extra,
+    );
+    (dt * TEMPERATURE_DITHER, dm * MOISTURE_DITHER)
+}
+
+/// Finds the secondary biome of a tile and its blend weight in `[0, 0.5]`.
+///
+/// `classify(t, m)` must return the biome the tile would get at temperature
+/// `t` and moisture `m`, everything else unchanged.  Returns `(primary, 0.0)`
+/// when no other biome lies within reach.
+pub fn transition(
+    primary: Biome,
+    t: f32,
+    m: f32,
+    classify: impl Fn(f32, f32) -> Biome,
+) -> (Biome, f32) {
+    for &f in &PROBES {
+        let dt = TEMPERATURE_WIDTH * f;
+        let dm = MOISTURE_WIDTH * f;
+        let probes = [(t - dt, m), (t + dt, m), (t, m - dm), (t, m + dm)];
+        for (pt, pm) in probes {
+            let other = classify(pt.clamp(0.0, 1.0), pm.clamp(-1.0, 1.0));
+            if other != primary {
+                // Linear falloff from 0.5 at the first probe to 0.0 at the
+                // last, so blends fade out at the edge of the zone.  A
+                // neighbour only the last probe reaches gets no weight, and
+                // the tile stays unblended.
+                let (first, last) = (PROBES[0], PROBES[PROBES.len() - 1]);
+                let blend = 0.5 * (last - f) / (last - first);
+                return if blend > 0.0 {
+                    (other, blend)
+                } else {
+                    (primary, 0.0)
+                };
+            }
+        }
+    }
+    (primary, 0.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Plain below `t = 0.5`, savanna above, whatever the moisture.
+    fn classify(t: f32, _m: f32) -> Biome {
+        if t < 0.5 {
+            Biome::Plain
+        } else {
+            Biome::Savanna
+        }
+    }
+
+    #[test]
+    fn blend_fades_with_distance_from_the_border() {
+        let at = |offset: f32| transition(Biome::Plain, 0.5 - offset, 0.0, classify);
+        // Reached by the first probe: an even split.
+        assert_eq!(at(0.001), (Biome::Savanna, 0.5));
+        // Reached by the second of four probes: two thirds of the way up.
+        let (other, blend) = at(TEMPERATURE_WIDTH * 0.4);
+        assert_eq!(other, Biome::Savanna);
+        assert!((blend - 0.5 * 0.5 / 0.75).abs() < 1e-6);
+        // Reached only by the last probe, or not at all: no secondary biome.
+        assert_eq!(at(TEMPERATURE_WIDTH * 0.9), (Biome::Plain, 0.0));
+        assert_eq!(at(0.3), (Biome::Plain, 0.0));
+
+        let mut previous = 0.5;
+        for k in 1..60 {
+            let (other, blend) = at(k as f32 * 0.001);
+            assert!(blend <= previous && (other == Biome::Savanna) == (blend > 0.0));
+            previous = blend;
+        }
+    }
+}
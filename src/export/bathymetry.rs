@@ -0,0 +1,47 @@
+use crate::world::World;
+use image::{ImageBuffer, Luma};
+
+/// Writes water depth as a 16-bit grayscale PNG.
+///
+/// Each pixel value is the depth below sea level in **metres** (`0` on land),
+/// laid out like `world.png` (column `q`, row `r`).  Depths beyond the 16-bit
+/// range are clamped to `65 535 m`.
+pub fn export_bathymetry(world: &World, path: &str) {
+    let mut img: ImageBuffer<Luma<u16>, Vec<u16>> =
+        ImageBuffer::new(world.width as u32, world.height as u32);
+    for tile in &world.tiles {
+        let depth = (-tile.elevation_m).max(0.0).round().min(u16::MAX as f32);
+        img.put_pixel(tile.q as u32, tile.r as u32, Luma([depth as u16]));
+    }
+    img.save(path).unwrap();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generation::test_world;
+
+    #[test]
+    fn pixels_hold_depth_in_metres() {
+        let world = test_world();
+        let path = std::env::temp_dir().join(format!("bathymetry-{}.png", std::process::id()));
+        export_bathymetry(&world, path.to_str().unwrap());
+        let img = image::open(&path).unwrap().into_luma16();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(img.dimensions(), (world.width as u32, world.height as u32));
+        for tile in &world.tiles {
+            let depth = img.get_pixel(tile.q as u32, tile.r as u32).0[0];
+            if tile.elevation_m >= 0.0 {
+                assert_eq!(depth, 0);
+            } else {
+                assert!(
+                    (depth as f32 + tile.elevation_m).abs() <= 0.5,
+                    "{depth} vs {}",
+                    tile.elevation_m
+                );
+            }
+        }
+        assert!(world.tiles.iter().any(|t| t.elevation_m < -1000.0));
+    }
+}
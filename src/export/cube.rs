@@ -0,0 +1,122 @@
+/// Cube-sphere worlds as images: one PNG per face plus the unfolded cross.
+use super::png::{BACKGROUND, DASH_ON, PERIOD, REFERENCE_PARALLELS};
+use super::render::{RenderOptions, tile_colors};
+use crate::grid::CUBE_FACES;
+use crate::world::*;
+use image::{Rgb, RgbImage};
+
+/// Writes the faces of a [`Layout::CubeSphere`] world:
+///
+/// - `faces/{name}.png` — one `N × N` image per face, `up` at the top
+/// - `world.png`        — the six faces unfolded into a 4 × 3 cross, with the
+///   reference parallels
+///
+/// Pixels are tiles, exactly as generated; nothing is resampled.
+pub fn export_cube(world: &World, options: &RenderOptions, dir: &str) {
+    let n = world.height as u32;
+    let grid = world.grid();
+    let colors = tile_colors(world, options);
+    // Pixel of cell (x, y) within its face image.
+    let pixel = |i: usize| {
+        let (q, r) = grid.coords(i);
+        (q as u32 % n, n - 1 - r as u32)
+    };
+
+    std::fs::create_dir_all(format!("{dir}/faces")).expect("failed to create faces directory");
+    for (f, face) in CUBE_FACES.iter().enumerate() {
+        let mut img = RgbImage::new(n, n);
+        let first = f * (n * n) as usize;
+        for (i, &color) in colors.iter().enumerate().skip(first).take((n * n) as usize) {
+            let (x, y) = pixel(i);
+            img.put_pixel(x, y, Rgb(color));
+        }
+        img.save(format!("{dir}/faces/{}.png", face.name)).unwrap();
+    }
+
+    let mut cross = RgbImage::from_pixel(4 * n, 3 * n, Rgb(BACKGROUND));
+    let lats: Vec<f64> = (0..world.tiles.len())
+        .map(|i| {
+            let (q, r) = grid.coords(i);
+            grid.lon_lat(q, r).1.to_degrees()
+        })
+        .collect();
+    for (i, &color) in colors.iter().enumerate() {
+        let (col, row) = CUBE_FACES[i / (n * n) as usize].cross;
+        let (x, y) = pixel(i);
+        let (x, y) = (col as u32 * n + x, row as u32 * n + y);
+
+        // A tile is on a parallel when it lies north of it and a neighbour
+        // lies south.  Dashes are counted in tiles along the equator.
+        let line = REFERENCE_PARALLELS
+            .iter()
+            .find(|&&(lat, _)| lats[i] >= lat && grid.neighbors4(i).any(|j| lats[j] < lat));
+        let (q, r) = grid.coords(i);
+        let along = (grid.lon_lat(q, r).0.to_degrees() / 90.0 * n as f64) as u32;
+        match line {
+            Some(&(_, line_color)) if along % PERIOD < DASH_ON => {
+                cross.put_pixel(x, y, Rgb(line_color))
+            }
+            _ => cross.put_pixel(x, y, Rgb(color)),
+        }
+    }
+    cross.save(format!("{dir}/world.png")).unwrap();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generation::generate_world;
+    use crate::grid::Grid;
+
+    #[test]
+    fn faces_unfold_into_the_cross() {
+        let n = 16;
+        let world = generate_world(
+            Grid::cube_sphere(n as i32),
+            7,
+            0.0,
+            0.5,
+            PlanetType::Terran,
+            40_000.0,
+            1.0,
+        );
+        let dir = std::env::temp_dir().join(format!("cube-{}", std::process::id()));
+        let dir = dir.to_str().unwrap();
+        export_cube(&world, &RenderOptions::default(), dir);
+        let cross = image::open(format!("{dir}/world.png")).unwrap().into_rgb8();
+        let faces: Vec<RgbImage> = CUBE_FACES
+            .iter()
+            .map(|face| {
+                let path = format!("{dir}/faces/{}.png", face.name);
+                image::open(path).unwrap().into_rgb8()
+            })
+            .collect();
+        std::fs::remove_dir_all(dir).unwrap();
+
+        assert_eq!(cross.dimensions(), (4 * n, 3 * n));
+        // Every face sits in its cell of the cross, where only the dashed
+        // parallels are drawn over it.
+        for (face, img) in CUBE_FACES.iter().zip(&faces) {
+            assert_eq!(img.dimensions(), (n, n));
+            let (col, row) = (face.cross.0 as u32, face.cross.1 as u32);
+            let same = img
+                .enumerate_pixels()
+                .filter(|&(x, y, p)| cross.get_pixel(col * n + x, row * n + y) == p)
+                .count();
+            assert!(same >= (n * n * 3 / 4) as usize, "{}: {same}", face.name);
+        }
+        // Outside the six faces the cross is background.
+        for (col, row) in [(0, 0), (2, 0), (3, 0), (0, 2), (2, 2), (3, 2)] {
+            assert_eq!(cross.get_pixel(col * n + n / 2, row * n + n / 2).0, BACKGROUND);
+        }
+        // The equator crosses the four faces of the middle row.
+        let equator = REFERENCE_PARALLELS[0].1;
+        for col in 0..4 {
+            let on_equator = (col * n..(col + 1) * n)
+                .flat_map(|x| (n..2 * n).map(move |y| (x, y)))
+                .filter(|&(x, y)| cross.get_pixel(x, y).0 == equator)
+                .count();
+            assert!(on_equator > 0, "no equator in column {col}");
+        }
+    }
+}
@@ -0,0 +1,270 @@
+/// Drop-in terrain bundle for game engines (Godot, Unity and the like).
+///
+/// Every texture has one pixel per tile, laid out like `world.png` (column
+/// `q`, row `r`), so they line up with each other and with the heightmap.
+use super::heightmap::{HeightmapOptions, export_heightmap};
+use crate::world::*;
+use image::{ImageBuffer, Luma, Rgba, RgbaImage};
+use serde::Serialize;
+
+/// Terrain material groups, by texture rather than by climate: each group is
+/// one terrain texture in the engine.
+const GROUPS: [&str; 8] = [
+    "water", "sand", "grass", "forest", "rock", "snow", "volcanic", "toxic",
+];
+
+/// Index into [`GROUPS`] of the material a biome is painted with.
+fn biome_group(b: Biome) -> usize {
+    match b {
+        Biome::DeepOcean
+        | Biome::Ocean
+        | Biome::Shelf
+        | Biome::Trench
+        | Biome::Reef
+        | Biome::Fjord => 0,
+        Biome::Beach | Biome::Desert | Biome::DustPlain => 1,
+        Biome::Wetland | Biome::Tundra | Biome::Shrubland | Biome::Plain | Biome::Savanna => 2,
+        Biome::Taiga | Biome::Forest | Biome::Jungle => 3,
+        Biome::Mountain | Biome::Moraine | Biome::ScorchedWaste | Biome::RockyWaste => 4,
+        Biome::IceCap | Biome::Snow | Biome::FrozenOcean | Biome::GlacialPlain => 5,
+        Biome::Volcano | Biome::LavaField | Biome::AshLand | Biome::MagmaSea => 6,
+        Biome::CausticLake | Biome::ToxicSwamp | Biome::AcidFlatland => 7,
+    }
+}
+
+/// Weights of the 3 × 3 neighbourhood a splat weight is gathered from.
+const SPLAT_KERNEL: [[f32; 3]; 3] = [[1.0, 2.0, 1.0], [2.0, 4.0, 2.0], [1.0, 2.0, 1.0]];
+
+/// `manifest.json`: what each file holds and how big the world is.
+#[derive(Serialize)]
+struct Manifest {
+    width: i32,
+    height: i32,
+    seed: u32,
+    planet_type: PlanetType,
+    /// Equatorial circumference of the planet.
+    circumference_km: f32,
+    /// Surface gravity relative to Earth.
+    gravity_modifier: f32,
+    /// Ground distance between two columns at the equator, in metres.
+    metres_per_pixel_x: f32,
+    /// Ground distance between two rows, in metres.
+    metres_per_pixel_y: f32,
+    /// Texture file → what its channels hold.
+    textures: Vec<TextureInfo>,
+    /// Splat group index → material name and the biomes painted with it.
+    groups: Vec<GroupInfo>,
+    /// Value in `biome_index.png` → biome.
+    biomes: Vec<BiomeInfo>,
+}
+
+#[derive(Serialize)]
+struct TextureInfo {
+    file: &'static str,
+    format: &'static str,
+    content: &'static str,
+}
+
+#[derive(Serialize)]
+struct GroupInfo {
+    index: usize,
+    name: &'static str,
+    biomes: Vec<&'static str>,
+}
+
+#[derive(Serialize)]
+struct BiomeInfo {
+    index: u8,
+    name: &'static str,
+    color: [u8; 3],
+    group: usize,
+}
+
+/// Writes the engine bundle into `dir`:
+///
+/// - `heightmap.{png,r16,json}` — as [`export_heightmap`]
+/// - `biome_index.png`          — 8-bit biome index ([`biome_order`]) per pixel
+/// - `splat_weights.png`        — RGBA weights of the pixel's four strongest
+///   material groups, strongest in R, summing to 255
+/// - `splat_groups.png`         — RGBA group index for each channel of
+///   `splat_weights.png`
+/// - `manifest.json`            — texture → biome / group mapping and scale
+///
+/// Splat weights come from the biome classification: each tile in a 3 × 3
+/// neighbourhood adds its biome's group, and its ecotone blend towards its
+/// secondary biome's group, so materials fade across borders.
+pub fn export_engine_bundle(world: &World, heightmap: &HeightmapOptions, dir: &str) {
+    std::fs::create_dir_all(dir).expect("failed to create engine directory");
+    let (w, h) = (world.width, world.height);
+    export_heightmap(world, heightmap, &format!("{dir}/heightmap"));
+
+    let mut index: ImageBuffer<Luma<u8>, Vec<u8>> = ImageBuffer::new(w as u32, h as u32);
+    for tile in &world.tiles {
+        index.put_pixel(
+            tile.q as u32,
+            tile.r as u32,
+            Luma([biome_order(tile.biome)]),
+        );
+    }
+    index
+        .save(format!("{dir}/biome_index.png"))
+        .expect("failed to write biome index");
+
+    let (weights, groups) = splat_maps(world);
+    weights
+        .save(format!("{dir}/splat_weights.png"))
+        .expect("failed to write splat weights");
+    groups
+        .save(format!("{dir}/splat_groups.png"))
+        .expect("failed to write splat groups");
+
+    let circumference_m = world.circumference_km * 1000.0;
+    let manifest = Manifest {
+        width: w,
+        height: h,
+        seed: world.seed,
+        planet_type: world.planet_type,
+        circumference_km: world.circumference_km,
+        gravity_modifier: world.gravity_modifier,
+        metres_per_pixel_x: circumference_m / w as f32,
+        metres_per_pixel_y: circumference_m * 0.5 / h as f32,
+        textures: vec![
+            TextureInfo {
+                file: "heightmap.png",
+                format: "uint16 grayscale",
+                content: "terrain surface height; scale in heightmap.json (also heightmap.r16)",
+            },
+            TextureInfo {
+                file: "biome_index.png",
+                format: "uint8 grayscale",
+                content: "biome index, see biomes",
+            },
+            TextureInfo {
+                file: "splat_weights.png",
+                format: "RGBA8",
+                content: "weights of the four strongest groups, strongest in R, summing to 255",
+            },
+            TextureInfo {
+                file: "splat_groups.png",
+                format: "RGBA8",
+                content: "group index of each splat_weights channel, see groups",
+            },
+        ],
+        groups: GROUPS
+            .iter()
+            .enumerate()
+            .map(|(index, &name)| GroupInfo {
+                index,
+                name,
+                biomes: BIOMES
+                    .iter()
+                    .filter(|&&b| biome_group(b) == index)
+                    .map(|&b| biome_name(b))
+                    .collect(),
+            })
+            .collect(),
+        biomes: BIOMES
+            .iter()
+            .map(|&b| BiomeInfo {
+                index: biome_order(b),
+                name: biome_name(b),
+                color: biome_color(b),
+                group: biome_group(b),
+            })
+            .collect(),
+    };
+    let json = serde_json::to_string_pretty(&manifest).unwrap();
+    std::fs::write(format!("{dir}/manifest.json"), json).expect("failed to write manifest");
+}
+
+/// `splat_weights.png` and `splat_groups.png` of [`export_engine_bundle`].
+fn splat_maps(world: &World) -> (RgbaImage, RgbaImage) {
+    let (w, h) = (world.width, world.height);
+    let mut weights = RgbaImage::new(w as u32, h as u32);
+    let mut groups = RgbaImage::new(w as u32, h as u32);
+    for tile in &world.tiles {
+        let mut sum = [0f32; GROUPS.len()];
+        for (dr, row) in SPLAT_KERNEL.iter().enumerate() {
+            for (dq, k) in row.iter().enumerate() {
+                let q = (tile.q + dq as i32 - 1).rem_euclid(w);
+                let r = (tile.r + dr as i32 - 1).clamp(0, h - 1);
+                let n = &world.tiles[(q * h + r) as usize];
+                sum[biome_group(n.biome)] += k * (1.0 - n.blend);
+                sum[biome_group(n.secondary_biome)] += k * n.blend;
+            }
+        }
+
+        let mut ranked: Vec<usize> = (0..GROUPS.len()).collect();
+        ranked.sort_by(|&a, &b| sum[b].total_cmp(&sum[a]));
+        let top = [ranked[0], ranked[1], ranked[2], ranked[3]];
+        let total: f32 = top.iter().map(|&g| sum[g]).sum();
+        let mut bytes = top.map(|g| (sum[g] / total * 255.0).round() as u8);
+        // Rounding can miss 255 by one or two; the strongest takes the slack.
+        let rest: u32 = bytes[1..].iter().map(|&b| b as u32).sum();
+        bytes[0] = (255 - rest) as u8;
+        weights.put_pixel(tile.q as u32, tile.r as u32, Rgba(bytes));
+        groups.put_pixel(tile.q as u32, tile.r as u32, Rgba(top.map(|g| g as u8)));
+    }
+    (weights, groups)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generation::test_world;
+
+    #[test]
+    fn splat_weights_sum_to_255() {
+        let world = test_world();
+        let (weights, groups) = splat_maps(&world);
+        for (x, y, Rgba(bytes)) in weights.enumerate_pixels() {
+            let total: u32 = bytes.iter().map(|&b| b as u32).sum();
+            assert_eq!(total, 255, "pixel {x},{y}: {bytes:?}");
+            // Strongest first; R may be one short after taking the slack.
+            assert!(
+                bytes[0] as u32 + 1 >= bytes[1] as u32
+                    && bytes[1] >= bytes[2]
+                    && bytes[2] >= bytes[3]
+            );
+
+            let Rgba(indices) = *groups.get_pixel(x, y);
+            assert!(indices.iter().all(|&g| (g as usize) < GROUPS.len()));
+            let mut distinct = indices.to_vec();
+            distinct.sort();
+            distinct.dedup();
+            assert_eq!(distinct.len(), 4, "pixel {x},{y}: groups {indices:?}");
+        }
+    }
+
+    #[test]
+    fn uniform_and_even_blends() {
+        let mut world = test_world();
+        for tile in &mut world.tiles {
+            tile.biome = Biome::Forest;
+            tile.secondary_biome = Biome::Forest;
+            tile.blend = 0.0;
+        }
+        let (weights, groups) = splat_maps(&world);
+        assert_eq!(weights.get_pixel(5, 5).0, [255, 0, 0, 0]);
+        assert_eq!(
+            groups.get_pixel(5, 5).0[0] as usize,
+            biome_group(Biome::Forest)
+        );
+
+        // Half forest, half desert everywhere: an even split that still sums to 255.
+        for tile in &mut world.tiles {
+            tile.secondary_biome = Biome::Desert;
+            tile.blend = 0.5;
+        }
+        let (weights, groups) = splat_maps(&world);
+        let Rgba([a, b, c, d]) = *weights.get_pixel(5, 5);
+        assert_eq!((a as u32 + b as u32, c, d), (255, 0, 0));
+        assert!(a.abs_diff(b) <= 1);
+        let mut pair = [groups.get_pixel(5, 5).0[0], groups.get_pixel(5, 5).0[1]];
+        pair.sort();
+        assert_eq!(
+            pair.map(|g| g as usize),
+            [biome_group(Biome::Desert), biome_group(Biome::Forest)]
+        );
+    }
+}
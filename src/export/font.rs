@@ -0,0 +1,691 @@
+/// Antialiased text for the PNG exports, in the bundled DejaVu Sans.
+///
+/// The font file is compiled into the binary and read with a small TrueType
+/// reader: `cmap` maps characters to glyphs, `hmtx` and `kern` space them, and
+/// `glyf` gives their outlines, composite glyphs (most accented letters)
+/// included.  Outlines are flattened to line segments and filled by summing
+/// each edge's signed area into the pixels it crosses, which gives exact
+/// coverage for antialiasing at any size.  Characters the font lacks draw as
+/// its missing-glyph box, so they show instead of vanishing.
+///
+/// The SVG exports name the same face in [`FAMILY`], so text measured here
+/// lines up in both.
+use image::RgbImage;
+use std::collections::HashMap;
+use std::sync::OnceLock;
+
+/// DejaVu Sans; see `assets/fonts/LICENSE`.
+static FONT_DATA: &[u8] = include_bytes!("../../assets/fonts/DejaVuSans.ttf");
+
+/// `font-family` of SVG text: the bundled face first, then look-alikes.
+pub const FAMILY: &str = "'DejaVu Sans', Verdana, sans-serif";
+
+/// Curves are cut into segments about this long, in pixels.
+const FLATNESS: f64 = 0.3;
+/// Deepest nesting of composite glyphs followed.
+const MAX_DEPTH: u32 = 8;
+
+/// Width of `text` in pixels at `size` pixels per em, kerning included.
+pub fn text_width(text: &str, size: f64) -> f64 {
+    let font = font();
+    let scale = size / font.units_per_em;
+    let mut width = 0.0;
+    let mut previous = None;
+    for c in text.chars() {
+        let glyph = font.glyph(c);
+        if let Some(left) = previous {
+            width += font.kerning(left, glyph) * scale;
+        }
+        width += font.advance(glyph) * scale;
+        previous = Some(glyph);
+    }
+    width
+}
+
+/// Height of a line of text at `size` pixels per em, from the font's
+/// ascender to its descender.
+pub fn line_height(size: f64) -> f64 {
+    let font = font();
+    (font.ascender - font.descender) * size / font.units_per_em
+}
+
+/// Distance from the top of a line at `size` pixels per em to its baseline.
+pub fn ascent(size: f64) -> f64 {
+    let font = font();
+    font.ascender * size / font.units_per_em
+}
+
+/// Draws `text` at `size` pixels per em with the top-left corner of its line
+/// at `(x, y)`, blending `color` over the image by coverage.
+pub fn draw_text(img: &mut RgbImage, text: &str, x: f64, y: f64, size: f64, color: [u8; 3]) {
+    let font = font();
+    let scale = size / font.units_per_em;
+    let baseline = y + font.ascender * scale;
+    let mut pen = x;
+    let mut previous = None;
+    for c in text.chars() {
+        let glyph = font.glyph(c);
+        if let Some(left) = previous {
+            pen += font.kerning(left, glyph) * scale;
+        }
+        let contours = font.outline(glyph);
+        if !contours.is_empty() {
+            // Font units, y up, to image pixels, y down.
+            let to_px = |(gx, gy): (f64, f64)| (pen + gx * scale, baseline - gy * scale);
+            draw_glyph(img, &flatten(&contours, to_px), color);
+        }
+        pen += font.advance(glyph) * scale;
+        previous = Some(glyph);
+    }
+}
+
+fn font() -> &'static Font {
+    static FONT: OnceLock<Font> = OnceLock::new();
+    FONT.get_or_init(|| Font::parse(FONT_DATA).expect("bundled font is not a usable TrueType font"))
+}
+
+/// A point of a glyph outline: position in font units and whether it lies on
+/// the curve (`false` for a quadratic control point).
+type Point = (f64, f64, bool);
+
+/// The parts of a TrueType font needed to lay out and draw text.
+struct Font {
+    data: &'static [u8],
+    units_per_em: f64,
+    ascender: f64,
+    descender: f64,
+    num_h_metrics: usize,
+    hmtx: usize,
+    loca: usize,
+    long_loca: bool,
+    glyf: usize,
+    cmap: HashMap<u32, u16>,
+    kern: HashMap<(u16, u16), i16>,
+}
+
+impl Font {
+    fn parse(data: &'static [u8]) -> Option<Font> {
+        let tables = (0..u16_at(data, 4)? as usize)
+            .map(|k| {
+                let record = 12 + 16 * k;
+                let tag = data.get(record..record + 4)?;
+                Some((tag, u32_at(data, record + 8)? as usize))
+            })
+            .collect::<Option<HashMap<_, _>>>()?;
+        let table = |tag: &[u8]| tables.get(tag).copied();
+        let (head, hhea) = (table(b"head")?, table(b"hhea")?);
+        Some(Font {
+            data,
+            units_per_em: u16_at(data, head + 18)? as f64,
+            ascender: i16_at(data, hhea + 4)? as f64,
+            descender: i16_at(data, hhea + 6)? as f64,
+            num_h_metrics: u16_at(data, hhea + 34)? as usize,
+            hmtx: table(b"hmtx")?,
+            loca: table(b"loca")?,
+            long_loca: i16_at(data, head + 50)? == 1,
+            glyf: table(b"glyf")?,
+            cmap: parse_cmap(data, table(b"cmap")?)?,
+            kern: table(b"kern")
+                .and_then(|kern| parse_kern(data, kern))
+                .unwrap_or_default(),
+        })
+    }
+
+    /// Glyph of `c`, or the missing-glyph box (glyph 0).
+    fn glyph(&self, c: char) -> u16 {
+        self.cmap.get(&(c as u32)).copied().unwrap_or(0)
+    }
+
+    /// Advance width of `glyph` in font units.
+    fn advance(&self, glyph: u16) -> f64 {
+        let metric = (glyph as usize).min(self.num_h_metrics - 1);
+        u16_at(self.data, self.hmtx + 4 * metric).unwrap_or(0) as f64
+    }
+
+    /// Kerning between `left` and `right` in font units.
+    fn kerning(&self, left: u16, right: u16) -> f64 {
+        self.kern.get(&(left, right)).copied().unwrap_or(0) as f64
+    }
+
+    /// Contours of `glyph` in font units; empty for blank glyphs.
+    fn outline(&self, glyph: u16) -> Vec<Vec<Point>> {
+        let mut contours = Vec::new();
+        self.add_outline(glyph, [1.0, 0.0, 0.0, 1.0, 0.0, 0.0], 0, &mut contours);
+        contours
+    }
+
+    /// Appends the contours of `glyph`, mapped by the affine `transform`
+    /// `[a, b, c, d, e, f]` (`x' = a·x + c·y + e`, `y' = b·x + d·y + f`).
+    fn add_outline(
+        &self,
+        glyph: u16,
+        transform: [f64; 6],
+        depth: u32,
+        contours: &mut Vec<Vec<Point>>,
+    ) -> Option<()> {
+        let data = self.data;
+        let (start, end) = if self.long_loca {
+            let at = self.loca + 4 * glyph as usize;
+            (u32_at(data, at)? as usize, u32_at(data, at + 4)? as usize)
+        } else {
+            let at = self.loca + 2 * glyph as usize;
+            (
+                2 * u16_at(data, at)? as usize,
+                2 * u16_at(data, at + 2)? as usize,
+            )
+        };
+        if end <= start {
+            return Some(());
+        }
+        let at = self.glyf + start;
+        let count = i16_at(data, at)?;
+        if count < 0 {
+            return self.add_composite(at + 10, transform, depth, contours);
+        }
+
+        let count = count as usize;
+        let ends = (0..count)
+            .map(|k| u16_at(data, at + 10 + 2 * k).map(|e| e as usize))
+            .collect::<Option<Vec<_>>>()?;
+        let points = ends.last().map_or(0, |&e| e + 1);
+        let instructions = at + 10 + 2 * count;
+        let mut cursor = instructions + 2 + u16_at(data, instructions)? as usize;
+
+        let mut flags = Vec::with_capacity(points);
+        while flags.len() < points {
+            let flag = *data.get(cursor)?;
+            cursor += 1;
+            flags.push(flag);
+            if flag & 8 != 0 {
+                let repeat = *data.get(cursor)?;
+                cursor += 1;
+                flags.extend(std::iter::repeat_n(flag, repeat as usize));
+            }
+        }
+        flags.truncate(points);
+        let xs = read_coordinates(data, &mut cursor, &flags, 2, 16)?;
+        let ys = read_coordinates(data, &mut cursor, &flags, 4, 32)?;
+
+        let [a, b, c, d, e, f] = transform;
+        let mut first = 0;
+        for end in ends {
+            let contour = (first..=end)
+                .map(|k| {
+                    let (x, y) = (xs[k] as f64, ys[k] as f64);
+                    (a * x + c * y + e, b * x + d * y + f, flags[k] & 1 != 0)
+                })
+                .collect::<Vec<_>>();
+            if contour.len() > 1 {
+                contours.push(contour);
+            }
+            first = end + 1;
+        }
+        Some(())
+    }
+
+    /// Appends the components of the composite glyph whose component records
+    /// start at `at`.
+    fn add_composite(
+        &self,
+        mut at: usize,
+        transform: [f64; 6],
+        depth: u32,
+        contours: &mut Vec<Vec<Point>>,
+    ) -> Option<()> {
+        const WORDS: u16 = 0x1;
+        const XY_VALUES: u16 = 0x2;
+        const SCALE: u16 = 0x8;
+        const MORE: u16 = 0x20;
+        const XY_SCALE: u16 = 0x40;
+        const TWO_BY_TWO: u16 = 0x80;
+        if depth >= MAX_DEPTH {
+            return None;
+        }
+        let data = self.data;
+        let f2dot14 = |at: usize| i16_at(data, at).map(|v| v as f64 / 16384.0);
+        loop {
+            let flags = u16_at(data, at)?;
+            let glyph = u16_at(data, at + 2)?;
+            at += 4;
+            let (dx, dy) = if flags & WORDS != 0 {
+                at += 4;
+                (i16_at(data, at - 4)? as f64, i16_at(data, at - 2)? as f64)
+            } else {
+                at += 2;
+                let byte = |at: usize| data.get(at).map(|&v| v as i8 as f64);
+                (byte(at - 2)?, byte(at - 1)?)
+            };
+            // Components placed by matching points are rare; they go
+            // unshifted.
+            let (dx, dy) = if flags & XY_VALUES != 0 {
+                (dx, dy)
+            } else {
+                (0.0, 0.0)
+            };
+            let [a, b, c, d] = if flags & SCALE != 0 {
+                at += 2;
+                let s = f2dot14(at - 2)?;
+                [s, 0.0, 0.0, s]
+            } else if flags & XY_SCALE != 0 {
+                at += 4;
+                [f2dot14(at - 4)?, 0.0, 0.0, f2dot14(at - 2)?]
+            } else if flags & TWO_BY_TWO != 0 {
+                at += 8;
+                [
+                    f2dot14(at - 8)?,
+                    f2dot14(at - 6)?,
+                    f2dot14(at - 4)?,
+                    f2dot14(at - 2)?,
+                ]
+            } else {
+                [1.0, 0.0, 0.0, 1.0]
+            };
+            // The component's own mapping, then the parent's.
+            let [pa, pb, pc, pd, pe, pf] = transform;
+            let component = [
+                pa * a + pc * b,
+                pb * a + pd * b,
+                pa * c + pc * d,
+                pb * c + pd * d,
+                pa * dx + pc * dy + pe,
+                pb * dx + pd * dy + pf,
+            ];
+            self.add_outline(glyph, component, depth + 1, contours)?;
+            if flags & MORE == 0 {
+                return Some(());
+            }
+        }
+    }
+}
+
+/// Reads one axis of a simple glyph's points: `short` marks a one-byte delta
+/// whose sign is `same`; without `short`, `same` repeats the last value.
+fn read_coordinates(
+    data: &[u8],
+    cursor: &mut usize,
+    flags: &[u8],
+    short: u8,
+    same: u8,
+) -> Option<Vec<i32>> {
+    let mut value = 0i32;
+    let mut values = Vec::with_capacity(flags.len());
+    for &flag in flags {
+        if flag & short != 0 {
+            let delta = *data.get(*cursor)? as i32;
+            *cursor += 1;
+            value += if flag & same != 0 { delta } else { -delta };
+        } else if flag & same == 0 {
+            value += i16_at(data, *cursor)? as i32;
+            *cursor += 2;
+        }
+        values.push(value);
+    }
+    Some(values)
+}
+
+/// Character to glyph map from the Unicode subtable of `cmap`: format 12
+/// (all of Unicode) if the font has one, else format 4 (the BMP).
+fn parse_cmap(data: &[u8], cmap: usize) -> Option<HashMap<u32, u16>> {
+    let subtables = (0..u16_at(data, cmap + 2)? as usize)
+        .filter_map(|k| {
+            let record = cmap + 4 + 8 * k;
+            let platform = u16_at(data, record)?;
+            let encoding = u16_at(data, record + 2)?;
+            let at = cmap + u32_at(data, record + 4)? as usize;
+            Some((platform, encoding, u16_at(data, at)?, at))
+        })
+        .collect::<Vec<_>>();
+    let unicode =
+        |platform, encoding| platform == 0 || (platform == 3 && matches!(encoding, 1 | 10));
+    let mut map = HashMap::new();
+    if let Some(&(.., at)) = subtables
+        .iter()
+        .find(|&&(p, e, format, _)| unicode(p, e) && format == 12)
+    {
+        for k in 0..u32_at(data, at + 12)? as usize {
+            let group = at + 16 + 12 * k;
+            let (first, last) = (u32_at(data, group)?, u32_at(data, group + 4)?);
+            let glyph = u32_at(data, group + 8)?;
+            for c in first..=last {
+                map.insert(c, (glyph + c - first) as u16);
+            }
+        }
+        return Some(map);
+    }
+    let &(.., at) = subtables
+        .iter()
+        .find(|&&(p, e, format, _)| unicode(p, e) && format == 4)?;
+    let segments = u16_at(data, at + 6)? as usize / 2;
+    let ends = at + 14;
+    let starts = ends + 2 * segments + 2;
+    let deltas = starts + 2 * segments;
+    let offsets = deltas + 2 * segments;
+    for k in 0..segments {
+        let (first, last) = (u16_at(data, starts + 2 * k)?, u16_at(data, ends + 2 * k)?);
+        let delta = u16_at(data, deltas + 2 * k)?;
+        let offset = u16_at(data, offsets + 2 * k)? as usize;
+        for c in first..=last.min(0xFFFE) {
+            let glyph = if offset == 0 {
+                c.wrapping_add(delta)
+            } else {
+                let at = offsets + 2 * k + offset + 2 * (c - first) as usize;
+                match u16_at(data, at)? {
+                    0 => 0,
+                    g => g.wrapping_add(delta),
+                }
+            };
+            if glyph != 0 {
+                map.insert(c as u32, glyph);
+            }
+        }
+    }
+    Some(map)
+}
+
+/// Pair kerning from the format 0 subtables of a version 0 `kern` table.
+fn parse_kern(data: &[u8], kern: usize) -> Option<HashMap<(u16, u16), i16>> {
+    let mut pairs = HashMap::new();
+    if u16_at(data, kern)? != 0 {
+        return None;
+    }
+    let mut at = kern + 4;
+    for _ in 0..u16_at(data, kern + 2)? {
+        let length = u16_at(data, at + 2)? as usize;
+        let coverage = u16_at(data, at + 4)?;
+        // Horizontal kerning values (format 0, not minimum or cross-stream).
+        if coverage >> 8 == 0 && coverage & 0x7 == 0x1 {
+            for k in 0..u16_at(data, at + 6)? as usize {
+                let pair = at + 14 + 6 * k;
+                let (left, right) = (u16_at(data, pair)?, u16_at(data, pair + 2)?);
+                pairs.insert((left, right), i16_at(data, pair + 4)?);
+            }
+        }
+        at += length;
+    }
+    Some(pairs)
+}
+
+fn u16_at(data: &[u8], at: usize) -> Option<u16> {
+    Some(u16::from_be_bytes(data.get(at..at + 2)?.try_into().ok()?))
+}
+
+fn i16_at(data: &[u8], at: usize) -> Option<i16> {
+    u16_at(data, at).map(|v| v as i16)
+}
+
+fn u32_at(data: &[u8], at: usize) -> Option<u32> {
+    Some(u32::from_be_bytes(data.get(at..at + 4)?.try_into().ok()?))
+}
+
+/// Contours mapped to pixels by `to_px` and their quadratic curves cut into
+/// lines, as closed polygons.
+fn flatten(
+    contours: &[Vec<Point>],
+    to_px: impl Fn((f64, f64)) -> (f64, f64),
+) -> Vec<Vec<(f64, f64)>> {
+    contours
+        .iter()
+        .map(|contour| {
+            let n = contour.len();
+            let at = |k: usize| {
+                let (x, y, on) = contour[k % n];
+                let (x, y) = to_px((x, y));
+                (x, y, on)
+            };
+            // Start on the curve: at an on-curve point, or between two
+            // control points, where the curve passes implicitly.
+            let first = (0..n).find(|&k| contour[k].2);
+            let (start, from) = match first {
+                Some(k) => {
+                    let (x, y, _) = at(k);
+                    ((x, y), k + 1)
+                }
+                None => {
+                    let ((ax, ay, _), (bx, by, _)) = (at(0), at(1));
+                    (((ax + bx) / 2.0, (ay + by) / 2.0), 1)
+                }
+            };
+            let mut polygon = vec![start];
+            let mut control: Option<(f64, f64)> = None;
+            for k in from..from + n {
+                let (x, y, on) = at(k);
+                match (on, control) {
+                    (true, None) => polygon.push((x, y)),
+                    (true, Some(c)) => {
+                        curve(&mut polygon, c, (x, y));
+                        control = None;
+                    }
+                    (false, None) => control = Some((x, y)),
+                    (false, Some(c)) => {
+                        let mid = ((c.0 + x) / 2.0, (c.1 + y) / 2.0);
+                        curve(&mut polygon, c, mid);
+                        control = Some((x, y));
+                    }
+                }
+            }
+            if let Some(c) = control {
+                curve(&mut polygon, c, start);
+            }
+            polygon
+        })
+        .collect()
+}
+
+/// Appends the quadratic curve from the polygon's last point through
+/// `control` to `end`, as line segments.
+fn curve(polygon: &mut Vec<(f64, f64)>, control: (f64, f64), end: (f64, f64)) {
+    let start = *polygon.last().expect("curves follow a start point");
+    let length = (control.0 - start.0).hypot(control.1 - start.1)
+        + (end.0 - control.0).hypot(end.1 - control.1);
+    let steps = ((length / FLATNESS).sqrt().ceil() as usize).clamp(1, 32);
+    for k in 1..=steps {
+        let t = k as f64 / steps as f64;
+        let (u, v, w) = ((1.0 - t) * (1.0 - t), 2.0 * t * (1.0 - t), t * t);
+        polygon.push((
+            u * start.0 + v * control.0 + w * end.0,
+            u * start.1 + v * control.1 + w * end.1,
+        ));
+    }
+}
+
+/// Fills `polygons` (non-zero rule) with `color`, blended by coverage.
+///
+/// Every edge adds its signed area to the cells it crosses; summing a row
+/// from the left then gives the share of each pixel inside the outline.
+fn draw_glyph(img: &mut RgbImage, polygons: &[Vec<(f64, f64)>], color: [u8; 3]) {
+    let points = polygons.iter().flatten();
+    let (x0, y0) = points
+        .clone()
+        .fold((f64::MAX, f64::MAX), |(x, y), p| (x.min(p.0), y.min(p.1)));
+    let (x1, y1) = points.fold((f64::MIN, f64::MIN), |(x, y), p| (x.max(p.0), y.max(p.1)));
+    let (left, top) = (x0.floor(), y0.floor());
+    // Two spare columns take the spill of edges at the right.
+    let w = (x1.ceil() - left) as usize + 2;
+    let h = (y1.ceil() - top) as usize;
+    let mut area = vec![0f64; w * h];
+
+    for polygon in polygons {
+        for (k, &(ax, ay)) in polygon.iter().enumerate() {
+            let (bx, by) = polygon[(k + 1) % polygon.len()];
+            let a = (ax - left, ay - top);
+            let b = (bx - left, by - top);
+            accumulate(&mut area, w, h, a, b);
+        }
+    }
+
+    for row in 0..h {
+        let py = top as i64 + row as i64;
+        let mut sum = 0.0;
+        for col in 0..w {
+            sum += area[row * w + col];
+            let coverage = sum.abs().min(1.0);
+            let px = left as i64 + col as i64;
+            if coverage < 1.0 / 512.0
+                || px < 0
+                || py < 0
+                || px >= img.width() as i64
+                || py >= img.height() as i64
+            {
+                continue;
+            }
+            let pixel = img.get_pixel_mut(px as u32, py as u32);
+            for (channel, &ink) in pixel.0.iter_mut().zip(&color) {
+                *channel =
+                    (*channel as f64 + (ink as f64 - *channel as f64) * coverage).round() as u8;
+            }
+        }
+    }
+}
+
+/// Adds the signed area the edge `a`–`b` sweeps, pixel by pixel, to `area`
+/// (`w` × `h` cells, `a` and `b` inside it).
+fn accumulate(area: &mut [f64], w: usize, h: usize, a: (f64, f64), b: (f64, f64)) {
+    if a.1 == b.1 {
+        return;
+    }
+    let (dir, a, b) = if a.1 < b.1 { (1.0, a, b) } else { (-1.0, b, a) };
+    let dxdy = (b.0 - a.0) / (b.1 - a.1);
+    let mut x = a.0;
+    for row in a.1.floor() as usize..(b.1.ceil() as usize).min(h) {
+        let dy = ((row + 1) as f64).min(b.1) - (row as f64).max(a.1);
+        let next = x + dxdy * dy;
+        let d = dy * dir;
+        let line = row * w;
+        let (xl, xr) = if x < next { (x, next) } else { (next, x) };
+        let (fl, cr) = (xl.floor(), xr.ceil());
+        let (il, ir) = (fl as usize, cr as usize);
+        if ir <= il + 1 {
+            // Within one pixel: split by the edge's mean position.
+            let share = 0.5 * (x + next) - fl;
+            area[line + il] += d * (1.0 - share);
+            area[line + il + 1] += d * share;
+        } else {
+            // Across several: the covered area grows linearly in between.
+            let s = 1.0 / (xr - xl);
+            let fx = xl - fl;
+            let first = 0.5 * s * (1.0 - fx) * (1.0 - fx);
+            let fx_end = xr - cr + 1.0;
+            let last = 0.5 * s * fx_end * fx_end;
+            area[line + il] += d * first;
+            if ir == il + 2 {
+                area[line + il + 1] += d * (1.0 - first - last);
+            } else {
+                let second = s * (1.5 - fx);
+                area[line + il + 1] += d * (second - first);
+                for col in il + 2..ir - 1 {
+                    area[line + col] += d * s;
+                }
+                let before_last = second + (ir - il - 3) as f64 * s;
+                area[line + ir - 1] += d * (1.0 - before_last - last);
+            }
+            area[line + ir] += d * last;
+        }
+        x = next;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Ink laid on white by `draw_text`, in whole pixels of full coverage.
+    fn ink(text: &str, size: f64) -> (RgbImage, f64) {
+        let mut img = RgbImage::from_pixel(200, 60, image::Rgb([255, 255, 255]));
+        draw_text(&mut img, text, 4.0, 4.0, size, [0, 0, 0]);
+        let total = img.pixels().map(|p| (255 - p.0[0]) as f64 / 255.0).sum();
+        (img, total)
+    }
+
+    #[test]
+    fn glyphs_for_ascii_accented_and_astral_characters() {
+        let font = font();
+        for c in ['A', 'g', 'é', 'Å', 'ß', '😀', '𝔸'] {
+            let glyph = font.glyph(c);
+            assert_ne!(glyph, 0, "{c:?} has no glyph");
+            assert!(!font.outline(glyph).is_empty(), "{c:?} has no outline");
+            assert!(font.advance(glyph) > 0.0);
+        }
+        // Accented letters are composites of the base letter and the accent.
+        assert!(font.outline(font.glyph('é')).len() > font.outline(font.glyph('e')).len());
+        // Blank glyphs advance without drawing.
+        assert!(font.outline(font.glyph(' ')).is_empty());
+        assert!(font.advance(font.glyph(' ')) > 0.0);
+    }
+
+    #[test]
+    fn missing_characters_draw_the_missing_glyph_box() {
+        let font = font();
+        for c in ['中', '\u{10000}', '\u{E000}'] {
+            assert_eq!(font.glyph(c), 0, "{c:?}");
+        }
+        assert!(!font.outline(0).is_empty());
+        assert_eq!(text_width("中", 20.0), text_width("\u{E000}", 20.0));
+        assert!(ink("中", 20.0).1 > 0.0);
+    }
+
+    #[test]
+    fn kerning_tightens_text_width() {
+        let single = |text: &str| {
+            text.chars()
+                .map(|c| text_width(&c.to_string(), 100.0))
+                .sum::<f64>()
+        };
+        for pair in ["AV", "To", "LT"] {
+            assert!(
+                text_width(pair, 100.0) < single(pair) - 5.0,
+                "{pair} is not kerned"
+            );
+        }
+        // Pairs without kerning are the sum of their advances.
+        assert!((text_width("ll", 100.0) - single("ll")).abs() < 1e-9);
+        // Widths scale with the size.
+        assert!((text_width("Legend", 24.0) - 2.0 * text_width("Legend", 12.0)).abs() < 1e-9);
+        assert_eq!(text_width("", 12.0), 0.0);
+    }
+
+    #[test]
+    fn coverage_is_exact_for_partial_pixels() {
+        // A 3.5 × 2 rectangle starting a quarter and a half into its pixels.
+        let mut img = RgbImage::from_pixel(8, 8, image::Rgb([255, 255, 255]));
+        let rect = vec![(1.25, 1.5), (4.75, 1.5), (4.75, 3.5), (1.25, 3.5)];
+        draw_glyph(&mut img, &[rect], [0, 0, 0]);
+        let coverage = |x, y| (255 - img.get_pixel(x, y).0[0]) as f64 / 255.0;
+        let total: f64 = (0..8)
+            .flat_map(|y| (0..8).map(move |x| (x, y)))
+            .map(|(x, y)| coverage(x, y))
+            .sum();
+        assert!((total - 7.0).abs() < 0.05, "total coverage {total}");
+        assert!((coverage(2, 2) - 1.0).abs() < 0.01);
+        assert!((coverage(1, 2) - 0.75).abs() < 0.01);
+        assert!((coverage(2, 1) - 0.5).abs() < 0.01);
+        assert!((coverage(1, 1) - 0.375).abs() < 0.01);
+        assert_eq!(coverage(5, 2), 0.0);
+    }
+
+    #[test]
+    fn small_text_is_antialiased_and_stays_in_its_box() {
+        let (small, seven) = ink("Hamburg", 7.0);
+        let (_, fourteen) = ink("Hamburg", 14.0);
+        assert!(seven > 0.0);
+        // Exact coverage: twice the size, four times the ink.
+        assert!(
+            (fourteen / seven - 4.0).abs() < 0.4,
+            "ink {seven} → {fourteen}"
+        );
+        assert!(
+            small.pixels().any(|p| (1..255).contains(&p.0[0])),
+            "no partial pixels"
+        );
+
+        let right = 4.0 + text_width("Hamburg", 7.0) + 1.0;
+        let bottom = 4.0 + line_height(7.0) + 1.0;
+        for (x, y, p) in small.enumerate_pixels() {
+            if p.0[0] < 255 {
+                assert!(
+                    (3..right.ceil() as u32).contains(&x) && (3..bottom.ceil() as u32).contains(&y),
+                    "ink at {x},{y}"
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,58 @@
+/// Geographic conventions shared by the GIS and mesh exporters.
+///
+/// The world grid starts at longitude 0° and row 0 is the south pole.  GIS
+/// tools expect longitudes in `[-180, 180)` and north-up rasters, so these
+/// helpers re-centre the map on the prime meridian and flip it vertically.
+/// Every tile is generated at its centre ([`crate::grid::Grid::lon_lat`]), so
+/// a raster with one pixel per tile is registered by its pixel edges.
+use crate::world::{Tile, World};
+use std::f64::consts::PI;
+
+/// Radius in metres of the sphere with the world's circumference.
+pub fn sphere_radius_m(world: &World) -> f64 {
+    world.circumference_km as f64 * 1000.0 / (2.0 * PI)
+}
+
+/// Longitude in degrees of the west edge of a [`raster_tile`] raster
+/// (exactly `-180` for an even width).
+pub fn raster_west_deg(world: &World) -> f64 {
+    (world.width / 2) as f64 * 360.0 / world.width as f64 - 360.0
+}
+
+/// Index into `world.tiles` of pixel `(x, y)` of a north-up raster starting at
+/// [`raster_west_deg`].  Pixel `(x, y)` covers the area of tile
+/// `((x + width/2) mod width, height - 1 - y)`.
+pub fn raster_tile(world: &World, x: i32, y: i32) -> usize {
+    let q = (x + world.width / 2).rem_euclid(world.width);
+    let r = world.height - 1 - y;
+    (q * world.height + r) as usize
+}
+
+/// Index into `world.tiles` of the tile covering `(lon_deg, lat_deg)`, with
+/// `lon_deg` in the grid's convention (`0..360`, wrapping).
+pub fn tile_index(world: &World, lon_deg: f64, lat_deg: f64) -> usize {
+    let q = (lon_deg / 360.0 * world.width as f64).floor() as i32;
+    let r = ((lat_deg + 90.0) / 180.0 * world.height as f64).floor() as i32;
+    let q = q.rem_euclid(world.width);
+    let r = r.clamp(0, world.height - 1);
+    (q * world.height + r) as usize
+}
+
+/// Bilinear sample of a per-tile quantity at any point on the sphere.
+///
+/// `lon_deg` is in the grid's convention (`0..360`, wrapping); `lat_deg` in
+/// `[-90, 90]`.  Tile values sit at tile centres; rows are clamped at the poles.
+pub fn sample(world: &World, lon_deg: f64, lat_deg: f64, f: impl Fn(&Tile) -> f32) -> f32 {
+    let x = lon_deg / 360.0 * world.width as f64 - 0.5;
+    let y = (lat_deg + 90.0) / 180.0 * world.height as f64 - 0.5;
+    let (x0, y0) = (x.floor(), y.floor());
+    let (fx, fy) = ((x - x0) as f32, (y - y0) as f32);
+    let at = |q: f64, r: f64| {
+        let q = (q as i32).rem_euclid(world.width);
+        let r = (r as i32).clamp(0, world.height - 1);
+        f(&world.tiles[(q * world.height + r) as usize])
+    };
+    let south = at(x0, y0) * (1.0 - fx) + at(x0 + 1.0, y0) * fx;
+    let north = at(x0, y0 + 1.0) * (1.0 - fx) + at(x0 + 1.0, y0 + 1.0) * fx;
+    south * (1.0 - fy) + north * fy
+}
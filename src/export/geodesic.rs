@@ -0,0 +1,197 @@
+/// Geodesic (Goldberg polyhedron) worlds: a cell map and a JSON of the cells
+/// with their corners and neighbours, for globe games.
+use super::png::{DASH_ON, PERIOD, REFERENCE_PARALLELS};
+use super::render::{RenderOptions, mix, tile_colors};
+use crate::geodesic::Geodesic;
+use crate::grid::unit_vector;
+use crate::world::*;
+use image::{Rgb, RgbImage};
+use serde_json::json;
+use std::f64::consts::PI;
+
+/// Map pixels between neighbouring cell centres.
+const CELL_PX: f64 = 6.0;
+/// Fraction to darken cell outlines by.
+const OUTLINE_DARKNESS: f32 = 0.25;
+
+fn cells(world: &World) -> &Geodesic {
+    world
+        .geodesic
+        .as_deref()
+        .expect("geodesic export needs a geodesic grid")
+}
+
+/// Writes `world.png` for a geodesic world: an equirectangular map, north up
+/// with 0° at the left edge, where every pixel shows the cell it falls in.
+/// Cell outlines are darkened, and the reference parallels drawn on top.
+pub fn export_geodesic_png(world: &World, options: &RenderOptions, path: &str) {
+    let cells = cells(world);
+    let colors = tile_colors(world, options);
+    // Neighbouring centres are about an icosahedron edge / frequency apart.
+    let px_per_rad = CELL_PX * cells.frequency as f64 / 2f64.atan();
+    let w = (2.0 * PI * px_per_rad).round().max(2.0) as u32;
+    let h = w / 2;
+
+    // Consecutive pixels are usually in the same cell or the next one, so
+    // each lookup starts from the previous answer.
+    let mut cell = vec![0usize; (w * h) as usize];
+    let mut last = cells.locate([0.0, 0.0, 1.0]);
+    for y in 0..h {
+        let lat = PI / 2.0 - (y as f64 + 0.5) / h as f64 * PI;
+        for x in 0..w {
+            let lon = (x as f64 + 0.5) / w as f64 * 2.0 * PI;
+            last = cells.nearest(unit_vector(lon, lat), last);
+            cell[(y * w + x) as usize] = last;
+        }
+    }
+
+    let mut img = RgbImage::new(w, h);
+    for y in 0..h {
+        for x in 0..w {
+            let i = cell[(y * w + x) as usize];
+            let right = cell[(y * w + (x + 1) % w) as usize];
+            let below = cell[((y + 1).min(h - 1) * w + x) as usize];
+            let mut color = colors[i];
+            if right != i || below != i {
+                color = mix(color, [0, 0, 0], OUTLINE_DARKNESS);
+            }
+            img.put_pixel(x, y, Rgb(color));
+        }
+    }
+
+    for &(lat, color) in REFERENCE_PARALLELS {
+        let row = ((90.0 - lat) / 180.0 * h as f64).round() as u32;
+        if row >= h {
+            continue;
+        }
+        for x in (0..w).filter(|x| x % PERIOD < DASH_ON) {
+            img.put_pixel(x, row, Rgb(color));
+        }
+    }
+
+    img.save(path).unwrap();
+}
+
+/// Writes `geodesic.json`: per cell, its centre and corners on the unit
+/// sphere, the ids of its neighbours (in corner order), and its climate and
+/// biome.
+pub fn export_geodesic_json(world: &World, path: &str) {
+    let cells = cells(world);
+    let round = |v: f64, scale: f64| (v * scale).round() / scale;
+    let point = |p: [f64; 3]| p.map(|c| round(c, 1e6));
+    let tiles: Vec<_> = world
+        .tiles
+        .iter()
+        .enumerate()
+        .map(|(i, tile)| {
+            let [x, y, z] = cells.centres[i];
+            let vertices: Vec<[f64; 3]> = cells.corners(i).into_iter().map(point).collect();
+            json!({
+                "id": i,
+                "centre": point(cells.centres[i]),
+                "lon_deg": round(y.atan2(x).to_degrees().rem_euclid(360.0), 1e4),
+                "lat_deg": round(z.asin().to_degrees(), 1e4),
+                "vertices": vertices,
+                "neighbors": cells.neighbors[i],
+                "biome": tile.biome,
+                "elevation_m": round(tile.elevation_m as f64, 10.0),
+                "temperature_c": round(tile.temperature_c as f64, 10.0),
+                "precipitation_mm": round(tile.precipitation_mm as f64, 1.0),
+                "ice": tile.ice_thickness > 0.0,
+            })
+        })
+        .collect();
+    let doc = json!({
+        "frequency": cells.frequency,
+        "cell_count": cells.centres.len(),
+        "pentagons": 12,
+        "axes": "unit sphere; x towards 0°, y towards 90° E, z towards the north pole",
+        "seed": world.seed,
+        "planet_type": world.planet_type,
+        "cells": tiles,
+    });
+    std::fs::write(path, doc.to_string()).expect("failed to write geodesic JSON");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generation::generate_world;
+    use crate::grid::Grid;
+
+    fn geodesic_world() -> World {
+        let grid = Grid::geodesic(8);
+        generate_world(grid, 7, 0.0, 0.5, PlanetType::Terran, 40_000.0, 1.0)
+    }
+
+    #[test]
+    fn map_shows_each_cell_at_its_centre() {
+        let world = geodesic_world();
+        let options = RenderOptions::default();
+        let path = std::env::temp_dir().join(format!("geodesic-{}.png", std::process::id()));
+        export_geodesic_png(&world, &options, path.to_str().unwrap());
+        let img = image::open(&path).unwrap().into_rgb8();
+        std::fs::remove_file(&path).unwrap();
+
+        let (w, h) = img.dimensions();
+        assert_eq!(w, 2 * h);
+        let colors = tile_colors(&world, &options);
+        let cells = cells(&world);
+        let shown = cells
+            .centres
+            .iter()
+            .enumerate()
+            .filter(|&(i, &[x, y, z])| {
+                let lon = y.atan2(x).rem_euclid(2.0 * PI);
+                let px = (lon / (2.0 * PI) * w as f64) as u32 % w;
+                let py = (((PI / 2.0 - z.asin()) / PI * h as f64) as u32).min(h - 1);
+                img.get_pixel(px, py).0 == colors[i]
+            })
+            .count();
+        // A few centres fall on a reference parallel or a cell outline.
+        assert!(shown * 10 > cells.centres.len() * 9, "{shown}");
+    }
+
+    #[test]
+    fn json_cells_have_symmetric_neighbours_and_unit_corners() {
+        let world = geodesic_world();
+        let path = std::env::temp_dir().join(format!("geodesic-{}.json", std::process::id()));
+        export_geodesic_json(&world, path.to_str().unwrap());
+        let doc: serde_json::Value =
+            serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(doc["cell_count"], 10 * 8 * 8 + 2);
+        let cells = doc["cells"].as_array().unwrap();
+        assert_eq!(cells.len(), 642);
+        let mut pentagons = 0;
+        for (i, cell) in cells.iter().enumerate() {
+            let vertices = cell["vertices"].as_array().unwrap();
+            let neighbors = cell["neighbors"].as_array().unwrap();
+            assert_eq!(vertices.len(), neighbors.len(), "cell {i}");
+            pentagons += (vertices.len() == 5) as usize;
+            for v in vertices {
+                let p: Vec<f64> = v
+                    .as_array()
+                    .unwrap()
+                    .iter()
+                    .map(|c| c.as_f64().unwrap())
+                    .collect();
+                assert!((p.iter().map(|c| c * c).sum::<f64>() - 1.0).abs() < 1e-5);
+            }
+            for n in neighbors {
+                let n = n.as_u64().unwrap() as usize;
+                assert!(
+                    cells[n]["neighbors"]
+                        .as_array()
+                        .unwrap()
+                        .contains(&i.into())
+                );
+            }
+            let lat = cell["lat_deg"].as_f64().unwrap();
+            let z = cell["centre"][2].as_f64().unwrap();
+            assert!((lat.to_radians().sin() - z).abs() < 1e-5);
+        }
+        assert_eq!(pentagons, 12);
+    }
+}
@@ -0,0 +1,282 @@
+use super::geo::raster_west_deg;
+use super::render::CONTOUR_LEVELS;
+use super::vector::{Point, isolines, simplify, trace_regions};
+use crate::world::*;
+use serde_json::{Value, json};
+use std::collections::BTreeMap;
+
+/// Latitude lines written to `reference_lines.geojson` — the same lines
+/// `world.png` and `world.svg` overlay.
+const REFERENCE_LINES: &[(&str, f64)] = &[
+    ("Arctic Circle", 66.5),
+    ("Tropic of Cancer", 23.5),
+    ("Equator", 0.0),
+    ("Tropic of Capricorn", -23.5),
+    ("Antarctic Circle", -66.5),
+];
+
+/// Writes GeoJSON (RFC 7946) layers of the world into `dir`:
+///
+/// - `coastlines.geojson`      — the sea-level isoline
+/// - `biomes.geojson`          — one `MultiPolygon` per biome, with `biome` and `name`
+/// - `contours.geojson`        — one `MultiLineString` per level of `CONTOUR_LEVELS`
+/// - `reference_lines.geojson` — equator, tropics and polar circles
+///
+/// Coordinates are longitude `-180..180` and latitude `-90..90` in degrees.
+/// Geometry that crosses the antimeridian is cut there, so no segment wraps
+/// around the map.  `tolerance` is the Douglas–Peucker tolerance in tiles.
+pub fn export_geojson(world: &World, tolerance: f64, dir: &str) {
+    let w = world.width as usize;
+    let h = world.height as usize;
+    // Work on the grid re-centred on the prime meridian: lattice x = 0 is the
+    // antimeridian, so tracing naturally stops there.
+    let tile = |x: usize, y: usize| &world.tiles[((x + w / 2) % w) * h + y];
+
+    // ── Biome regions ─────────────────────────────────────────────────────────
+    let regions = trace_regions(w, h, |x, y| biome_order(tile(x, y).biome) as u32, tolerance);
+    let mut by_biome: BTreeMap<u32, Vec<Value>> = BTreeMap::new();
+    for region in regions {
+        // The first ring is the outer boundary (counter-clockwise, as RFC 7946
+        // wants); the rest are holes.
+        if region.rings.first().is_none_or(|outer| outer.len() < 3) {
+            continue;
+        }
+        let rings: Vec<Value> = region
+            .rings
+            .iter()
+            .filter(|ring| ring.len() >= 3)
+            .map(|ring| {
+                let mut ring: Vec<Point> = ring.clone();
+                ring.push(ring[0]);
+                positions(world, &ring)
+            })
+            .collect();
+        by_biome.entry(region.label).or_default().push(json!(rings));
+    }
+    let biome_of: BTreeMap<u32, Biome> = world
+        .tiles
+        .iter()
+        .map(|t| (biome_order(t.biome) as u32, t.biome))
+        .collect();
+    let biomes = by_biome
+        .into_iter()
+        .map(|(label, polygons)| {
+            let biome = biome_of[&label];
+            let [cr, cg, cb] = biome_color(biome);
+            feature(
+                json!({ "type": "MultiPolygon", "coordinates": polygons }),
+                json!({
+                    "biome": biome,
+                    "name": biome_name(biome),
+                    "color": format!("#{cr:02X}{cg:02X}{cb:02X}"),
+                }),
+            )
+        })
+        .collect();
+    write(&format!("{dir}/biomes.geojson"), biomes);
+
+    // ── Coastlines and contours ───────────────────────────────────────────────
+    let lines = |value: fn(&Tile) -> f32, level: f32| -> Value {
+        json!({
+            "type": "MultiLineString",
+            "coordinates": wrapped_isolines(w, h, |x, y| value(tile(x, y)), level)
+                .iter()
+                .map(|line| positions(world, &simplify(line, tolerance)))
+                .collect::<Vec<_>>(),
+        })
+    };
+    let coastline = feature(
+        lines(|t| t.elevation_m, 0.0),
+        json!({ "name": "Coastline" }),
+    );
+    write(&format!("{dir}/coastlines.geojson"), vec![coastline]);
+
+    let contours = CONTOUR_LEVELS
+        .iter()
+        .map(|&level| {
+            feature(
+                lines(|t| t.elevation, level),
+                json!({
+                    "level": (level as f64 * 100.0).round() / 100.0,
+                    "elevation_m": ((level - world.sea_level) * world.metres_per_unit).round(),
+                }),
+            )
+        })
+        .collect();
+    write(&format!("{dir}/contours.geojson"), contours);
+
+    // ── Reference lines ───────────────────────────────────────────────────────
+    // Vertices every 10° so the lines survive reprojection.
+    let references = REFERENCE_LINES
+        .iter()
+        .map(|&(name, lat)| {
+            let coords: Vec<[f64; 2]> = (-18..=18).map(|k| [k as f64 * 10.0, lat]).collect();
+            feature(
+                json!({ "type": "LineString", "coordinates": coords }),
+                json!({ "name": name, "latitude": lat }),
+            )
+        })
+        .collect();
+    write(&format!("{dir}/reference_lines.geojson"), references);
+}
+
+/// Isolines of a field that wraps east–west, cut at the grid's left and right
+/// edges (the antimeridian).
+///
+/// Marching squares only joins neighbouring sample columns, so the grid is
+/// padded with one wrapped column on each side and the result clipped back to
+/// `0 ≤ x ≤ w`.
+fn wrapped_isolines(
+    w: usize,
+    h: usize,
+    value: impl Fn(usize, usize) -> f32,
+    level: f32,
+) -> Vec<Vec<Point>> {
+    let padded = isolines(w + 2, h, |x, y| value((x + w - 1) % w, y), level);
+    let mut out = Vec::new();
+    for line in padded {
+        let line: Vec<Point> = line.into_iter().map(|(x, y)| (x - 1.0, y)).collect();
+        let closed = line.len() > 2 && line.first() == line.last();
+        let mut pieces = clip_x(&line, w as f64);
+        // A loop cut open at the seam starts and ends mid-piece: rejoin them.
+        if closed && pieces.len() > 1 && line[0].0 > 0.0 && line[0].0 < w as f64 {
+            let first = pieces.remove(0);
+            pieces.last_mut().unwrap().extend(&first[1..]);
+        }
+        out.extend(pieces.into_iter().filter(|p| p.len() >= 2));
+    }
+    out
+}
+
+/// Splits a polyline into its pieces inside `0 ≤ x ≤ max_x`, ending each piece
+/// exactly on the boundary it crosses.
+fn clip_x(line: &[Point], max_x: f64) -> Vec<Vec<Point>> {
+    let inside = |p: Point| (0.0..=max_x).contains(&p.0);
+    let mut pieces: Vec<Vec<Point>> = Vec::new();
+    let mut current: Vec<Point> = Vec::new();
+    for (k, &p) in line.iter().enumerate() {
+        if k > 0 {
+            let a = line[k - 1];
+            // Boundary crossings on this segment, in walking order.
+            let mut cuts: Vec<f64> = [0.0, max_x]
+                .iter()
+                .filter(|&&edge| (a.0 - edge) * (p.0 - edge) < 0.0)
+                .map(|&edge| (edge - a.0) / (p.0 - a.0))
+                .collect();
+            cuts.sort_by(f64::total_cmp);
+            for t in cuts {
+                let cut = (a.0 + t * (p.0 - a.0), a.1 + t * (p.1 - a.1));
+                // Entering starts a piece at the cut; leaving ends one there.
+                let leaving = !current.is_empty();
+                current.push(cut);
+                if leaving {
+                    pieces.push(std::mem::take(&mut current));
+                }
+            }
+        }
+        if inside(p) {
+            current.push(p);
+        } else if !current.is_empty() {
+            pieces.push(std::mem::take(&mut current));
+        }
+    }
+    if !current.is_empty() {
+        pieces.push(current);
+    }
+    pieces
+}
+
+/// Lattice points → `[lon, lat]` positions, rounded to 1e-5°.
+fn positions(world: &World, points: &[Point]) -> Value {
+    let west = raster_west_deg(world);
+    let deg_x = 360.0 / world.width as f64;
+    let deg_y = 180.0 / world.height as f64;
+    let round = |v: f64| (v * 1e5).round() / 1e5;
+    points
+        .iter()
+        .map(|&(x, y)| json!([round(west + x * deg_x), round(y * deg_y - 90.0)]))
+        .collect()
+}
+
+fn feature(geometry: Value, properties: Value) -> Value {
+    json!({ "type": "Feature", "geometry": geometry, "properties": properties })
+}
+
+fn write(path: &str, features: Vec<Value>) {
+    let collection = json!({ "type": "FeatureCollection", "features": features });
+    std::fs::write(path, collection.to_string()).expect("failed to write GeoJSON");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generation::test_world;
+
+    #[test]
+    fn clipping_ends_pieces_on_the_boundary() {
+        let line = [(8.0, 1.0), (12.0, 1.0), (12.0, 3.0), (8.0, 3.0)];
+        let pieces = clip_x(&line, 10.0);
+        assert_eq!(
+            pieces,
+            vec![vec![(8.0, 1.0), (10.0, 1.0)], vec![(10.0, 3.0), (8.0, 3.0)]]
+        );
+
+        // A segment crossing both edges keeps only its middle.
+        let pieces = clip_x(&[(-1.0, 0.0), (11.0, 6.0)], 10.0);
+        assert_eq!(pieces, vec![vec![(0.0, 0.5), (10.0, 5.5)]]);
+    }
+
+    #[test]
+    fn loops_across_the_seam_are_cut_there() {
+        let (w, h) = (16, 8);
+        // A blob centred on the seam: columns 14, 15, 0 and 1, rows 3 and 4.
+        let blob = |x: usize, y: usize| {
+            if x.min(w - 1 - x) <= 1 && (3..=4).contains(&y) {
+                1.0
+            } else {
+                0.0
+            }
+        };
+        let lines = wrapped_isolines(w, h, blob, 0.5);
+        assert_eq!(lines.len(), 2, "one piece on each side of the seam");
+        for line in &lines {
+            assert!(line.iter().all(|p| (0.0..=w as f64).contains(&p.0)));
+            assert!(line.windows(2).all(|s| (s[0].0 - s[1].0).abs() <= 1.0));
+            let ends = [line[0].0, line[line.len() - 1].0];
+            assert!(
+                ends.iter().all(|&x| x == 0.0 || x == w as f64),
+                "ends {ends:?} off the seam"
+            );
+        }
+
+        // The same blob away from the seam stays one closed loop.
+        let inland = |x: usize, y: usize| blob((x + w / 2) % w, y);
+        let lines = wrapped_isolines(w, h, inland, 0.5);
+        assert_eq!(lines.len(), 1);
+        assert_eq!(lines[0].first(), lines[0].last());
+    }
+
+    #[test]
+    fn coastline_positions_stay_within_the_globe() {
+        let world = test_world();
+        let (w, h) = (world.width as usize, world.height as usize);
+        let tile = |x: usize, y: usize| world.tiles[((x + w / 2) % w) * h + y].elevation_m;
+        let lines = wrapped_isolines(w, h, tile, 0.0);
+        assert!(!lines.is_empty());
+        for line in &lines {
+            let coords = positions(&world, &simplify(line, 1.0));
+            let coords: Vec<(f64, f64)> = coords
+                .as_array()
+                .unwrap()
+                .iter()
+                .map(|c| (c[0].as_f64().unwrap(), c[1].as_f64().unwrap()))
+                .collect();
+            for &(lon, lat) in &coords {
+                assert!((-180.0..=180.0).contains(&lon), "longitude {lon}");
+                assert!((-90.0..=90.0).contains(&lat), "latitude {lat}");
+            }
+            // No segment wraps around the map.
+            assert!(coords.windows(2).all(|s| (s[0].0 - s[1].0).abs() < 180.0));
+        }
+    }
+}
@@ -0,0 +1,424 @@
+use super::geo::{raster_tile, raster_west_deg, sphere_radius_m};
+use crate::world::*;
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+
+// TIFF field types.
+const ASCII: u16 = 2;
+const SHORT: u16 = 3;
+const LONG: u16 = 4;
+const DOUBLE: u16 = 12;
+
+// GeoKey codes (GeoTIFF 1.1, OGC 19-008r4).
+const USER_DEFINED: u16 = 32767;
+const MODEL_TYPE_GEOGRAPHIC: u16 = 2;
+const RASTER_PIXEL_IS_AREA: u16 = 1;
+const PRIME_MERIDIAN_GREENWICH: u16 = 8901;
+const LINEAR_UNIT_METRE: u16 = 9001;
+const ANGULAR_UNIT_DEGREE: u16 = 9102;
+
+/// Value of one IFD entry.
+enum Field {
+    Ascii(String),
+    Short(Vec<u16>),
+    Long(Vec<u32>),
+    Double(Vec<f64>),
+}
+
+impl Field {
+    fn kind(&self) -> u16 {
+        match self {
+            Field::Ascii(_) => ASCII,
+            Field::Short(_) => SHORT,
+            Field::Long(_) => LONG,
+            Field::Double(_) => DOUBLE,
+        }
+    }
+
+    fn count(&self) -> u32 {
+        match self {
+            Field::Ascii(s) => s.len() as u32 + 1, // NUL terminator
+            Field::Short(v) => v.len() as u32,
+            Field::Long(v) => v.len() as u32,
+            Field::Double(v) => v.len() as u32,
+        }
+    }
+
+    fn bytes(&self) -> Vec<u8> {
+        match self {
+            Field::Ascii(s) => s.bytes().chain([0]).collect(),
+            Field::Short(v) => v.iter().flat_map(|x| x.to_le_bytes()).collect(),
+            Field::Long(v) => v.iter().flat_map(|x| x.to_le_bytes()).collect(),
+            Field::Double(v) => v.iter().flat_map(|x| x.to_le_bytes()).collect(),
+        }
+    }
+}
+
+/// Pixel data of one single-band raster.
+enum Samples {
+    Float(Vec<f32>),
+    /// 8-bit indices with an RGB palette (256 entries).
+    Indexed(Vec<u8>, Vec<[u8; 3]>),
+}
+
+/// Writes georeferenced GeoTIFFs of the world into `dir`:
+///
+/// - `elevation.tif`   — surface height above sea level in metres (`float32`)
+/// - `moisture.tif`    — normalised moisture `[-1, 1]` (`float32`)
+/// - `temperature.tif` — mean annual temperature in °C (`float32`)
+/// - `biome.tif`       — biome index (`uint8`, palette coloured like `world.png`),
+///   with category names in `biome.tif.aux.xml`
+///
+/// Rasters are north-up and span longitude `-180..180`, latitude `90..-90`, in
+/// a geographic CRS on a sphere with the world's circumference.
+pub fn export_geotiff(world: &World, dir: &str) {
+    let elevation = north_up(world, |t| super::heightmap::surface_elevation_m(world, t));
+    write_geotiff(
+        world,
+        &format!("{dir}/elevation.tif"),
+        Samples::Float(elevation),
+        "elevation",
+        "m",
+    );
+    write_geotiff(
+        world,
+        &format!("{dir}/moisture.tif"),
+        Samples::Float(north_up(world, |t| t.moisture)),
+        "moisture",
+        "",
+    );
+    write_geotiff(
+        world,
+        &format!("{dir}/temperature.tif"),
+        Samples::Float(north_up(world, |t| t.temperature_c)),
+        "temperature",
+        "C",
+    );
+
+    // Biome index = `biome_order`; only biomes present on the map get a palette
+    // entry and a category name.
+    let mut present = BTreeMap::new();
+    for tile in &world.tiles {
+        present.insert(biome_order(tile.biome), tile.biome);
+    }
+    let mut palette = vec![[0u8; 3]; 256];
+    for (&i, &b) in &present {
+        palette[i as usize] = biome_color(b);
+    }
+    let indices = north_up(world, |t| biome_order(t.biome));
+    write_geotiff(
+        world,
+        &format!("{dir}/biome.tif"),
+        Samples::Indexed(indices, palette),
+        "biome",
+        "",
+    );
+
+    // GDAL reads class names from the PAM sidecar.
+    let last = present.keys().next_back().copied().unwrap_or(0);
+    let mut xml = String::from("<PAMDataset>\n  <PAMRasterBand band=\"1\">\n    <CategoryNames>\n");
+    for i in 0..=last {
+        let name = present.get(&i).map(|&b| biome_name(b)).unwrap_or("");
+        writeln!(xml, "      <Category>{name}</Category>").unwrap();
+    }
+    xml.push_str("    </CategoryNames>\n  </PAMRasterBand>\n</PAMDataset>\n");
+    std::fs::write(format!("{dir}/biome.tif.aux.xml"), xml).expect("failed to write aux.xml");
+}
+
+/// Samples `f` for every pixel of a north-up raster, row by row.
+fn north_up<T>(world: &World, f: impl Fn(&Tile) -> T) -> Vec<T> {
+    (0..world.height)
+        .flat_map(|y| (0..world.width).map(move |x| (x, y)))
+        .map(|(x, y)| f(&world.tiles[raster_tile(world, x, y)]))
+        .collect()
+}
+
+/// Writes one GeoTIFF to `path`; see [`encode_geotiff`].
+fn write_geotiff(world: &World, path: &str, samples: Samples, description: &str, unit: &str) {
+    let out = encode_geotiff(world, samples, description, unit);
+    std::fs::write(path, out).expect("failed to write GeoTIFF");
+}
+
+/// Encodes one little-endian, uncompressed, single-strip GeoTIFF.
+///
+/// `description` and `unit` end up in the GDAL metadata tag.
+fn encode_geotiff(world: &World, samples: Samples, description: &str, unit: &str) -> Vec<u8> {
+    let w = world.width as u32;
+    let h = world.height as u32;
+
+    let (mut data, bits, sample_format, photometric, palette) = match samples {
+        Samples::Float(v) => (
+            v.iter().flat_map(|x| x.to_le_bytes()).collect::<Vec<u8>>(),
+            32,
+            3, // IEEE floating point
+            1, // BlackIsZero
+            None,
+        ),
+        Samples::Indexed(v, palette) => (v, 8, 1, 3, Some(palette)), // unsigned int, palette
+    };
+    let data_offset = 8u32;
+    let strip_bytes = data.len() as u32;
+    // Keep the IFD word-aligned.
+    if data.len() % 2 == 1 {
+        data.push(0);
+    }
+
+    // ── GeoKeys ───────────────────────────────────────────────────────────────
+    // Geographic lon/lat in degrees on a user-defined sphere.
+    let radius = sphere_radius_m(world);
+    let citation = format!("Sphere R={radius:.0} m|");
+    let keys: &[[u16; 4]] = &[
+        [1024, 0, 1, MODEL_TYPE_GEOGRAPHIC],     // GTModelTypeGeoKey
+        [1025, 0, 1, RASTER_PIXEL_IS_AREA],      // GTRasterTypeGeoKey
+        [2048, 0, 1, USER_DEFINED],              // GeodeticCRSGeoKey
+        [2049, 34737, citation.len() as u16, 0], // GeodeticCitationGeoKey
+        [2050, 0, 1, USER_DEFINED],              // GeodeticDatumGeoKey
+        [2051, 0, 1, PRIME_MERIDIAN_GREENWICH],  // PrimeMeridianGeoKey
+        [2052, 0, 1, LINEAR_UNIT_METRE],         // GeogLinearUnitsGeoKey
+        [2054, 0, 1, ANGULAR_UNIT_DEGREE],       // GeogAngularUnitsGeoKey
+        [2056, 0, 1, USER_DEFINED],              // EllipsoidGeoKey
+        [2057, 34736, 1, 0],                     // EllipsoidSemiMajorAxisGeoKey
+        [2058, 34736, 1, 1],                     // EllipsoidSemiMinorAxisGeoKey
+    ];
+    let mut directory = vec![1, 1, 1, keys.len() as u16];
+    directory.extend(keys.iter().flatten());
+
+    let pixel_deg_x = 360.0 / w as f64;
+    let pixel_deg_y = 180.0 / h as f64;
+
+    let mut metadata = format!(
+        r#"<GDALMetadata><Item name="DESCRIPTION" sample="0" role="description">{description}</Item>"#
+    );
+    if !unit.is_empty() {
+        write!(
+            metadata,
+            r#"<Item name="UNITTYPE" sample="0" role="unittype">{unit}</Item>"#
+        )
+        .unwrap();
+    }
+    metadata.push_str("</GDALMetadata>");
+
+    // ── IFD entries, in ascending tag order ───────────────────────────────────
+    let mut fields: Vec<(u16, Field)> = vec![
+        (256, Field::Long(vec![w])),            // ImageWidth
+        (257, Field::Long(vec![h])),            // ImageLength
+        (258, Field::Short(vec![bits])),        // BitsPerSample
+        (259, Field::Short(vec![1])),           // Compression: none
+        (262, Field::Short(vec![photometric])), // PhotometricInterpretation
+        (273, Field::Long(vec![data_offset])),  // StripOffsets
+        (277, Field::Short(vec![1])),           // SamplesPerPixel
+        (278, Field::Long(vec![h])),            // RowsPerStrip
+        (279, Field::Long(vec![strip_bytes])),  // StripByteCounts
+        (284, Field::Short(vec![1])),           // PlanarConfiguration: chunky
+    ];
+    if let Some(palette) = palette {
+        // ColorMap: all reds, then greens, then blues, scaled to 16 bits.
+        let map = (0..3)
+            .flat_map(|c| palette.iter().map(move |rgb| rgb[c] as u16 * 257))
+            .collect();
+        fields.push((320, Field::Short(map)));
+    }
+    fields.extend([
+        (339, Field::Short(vec![sample_format])), // SampleFormat
+        (33550, Field::Double(vec![pixel_deg_x, pixel_deg_y, 0.0])), // ModelPixelScale
+        // ModelTiepoint: raster (0, 0) → north-west corner of the map.
+        (
+            33922,
+            Field::Double(vec![0.0, 0.0, 0.0, raster_west_deg(world), 90.0, 0.0]),
+        ),
+        (34735, Field::Short(directory)), // GeoKeyDirectory
+        (34736, Field::Double(vec![radius, radius])), // GeoDoubleParams
+        (34737, Field::Ascii(citation)),  // GeoAsciiParams
+        (42112, Field::Ascii(metadata)),  // GDAL_METADATA
+    ]);
+
+    // ── Layout: header · pixel data · IFD · out-of-line values ────────────────
+    let ifd_offset = data_offset + data.len() as u32;
+    let ifd_len = 2 + 12 * fields.len() as u32 + 4;
+    let mut extra: Vec<u8> = Vec::new();
+    let mut ifd: Vec<u8> = Vec::with_capacity(ifd_len as usize);
+    ifd.extend((fields.len() as u16).to_le_bytes());
+    for (tag, field) in &fields {
+        let bytes = field.bytes();
+        ifd.extend(tag.to_le_bytes());
+        ifd.extend(field.kind().to_le_bytes());
+        ifd.extend(field.count().to_le_bytes());
+        if bytes.len() <= 4 {
+            let mut inline = [0u8; 4];
+            inline[..bytes.len()].copy_from_slice(&bytes);
+            ifd.extend(inline);
+        } else {
+            let offset = ifd_offset + ifd_len + extra.len() as u32;
+            ifd.extend(offset.to_le_bytes());
+            extra.extend(&bytes);
+            if extra.len() % 2 == 1 {
+                extra.push(0);
+            }
+        }
+    }
+    ifd.extend(0u32.to_le_bytes()); // no further IFDs
+
+    let mut out = Vec::with_capacity(8 + data.len() + ifd.len() + extra.len());
+    out.extend(b"II*\0");
+    out.extend(ifd_offset.to_le_bytes());
+    out.extend(data);
+    out.extend(ifd);
+    out.extend(extra);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generation::test_world;
+
+    fn u16_at(tiff: &[u8], at: usize) -> u16 {
+        u16::from_le_bytes([tiff[at], tiff[at + 1]])
+    }
+
+    fn u32_at(tiff: &[u8], at: usize) -> u32 {
+        u32::from_le_bytes(tiff[at..at + 4].try_into().unwrap())
+    }
+
+    /// IFD entries as `(tag, type, count, value or offset)`.
+    fn entries(tiff: &[u8]) -> Vec<(u16, u16, u32, u32)> {
+        let ifd = u32_at(tiff, 4) as usize;
+        (0..u16_at(tiff, ifd) as usize)
+            .map(|k| {
+                let at = ifd + 2 + 12 * k;
+                let tag = u16_at(tiff, at);
+                (
+                    tag,
+                    u16_at(tiff, at + 2),
+                    u32_at(tiff, at + 4),
+                    u32_at(tiff, at + 8),
+                )
+            })
+            .collect()
+    }
+
+    fn size_of(kind: u16) -> u32 {
+        match kind {
+            ASCII => 1,
+            SHORT => 2,
+            LONG => 4,
+            DOUBLE => 8,
+            _ => panic!("unexpected field type {kind}"),
+        }
+    }
+
+    fn doubles(tiff: &[u8], entry: (u16, u16, u32, u32)) -> Vec<f64> {
+        let (_, kind, count, offset) = entry;
+        assert_eq!(kind, DOUBLE);
+        (0..count as usize)
+            .map(|k| f64::from_le_bytes(tiff[offset as usize + 8 * k..][..8].try_into().unwrap()))
+            .collect()
+    }
+
+    #[test]
+    fn header_and_directory_are_well_formed() {
+        let world = test_world();
+        let samples = Samples::Float(north_up(&world, |t| t.moisture));
+        let tiff = encode_geotiff(&world, samples, "moisture", "");
+
+        assert_eq!(&tiff[..4], b"II*\0");
+        let ifd = u32_at(&tiff, 4) as usize;
+        assert_eq!(ifd % 2, 0, "IFD must start on a word boundary");
+        let entries = entries(&tiff);
+        // No further IFDs.
+        assert_eq!(u32_at(&tiff, ifd + 2 + 12 * entries.len()), 0);
+
+        let tags: Vec<u16> = entries.iter().map(|e| e.0).collect();
+        assert!(
+            tags.windows(2).all(|p| p[0] < p[1]),
+            "tags out of order: {tags:?}"
+        );
+        for &(tag, kind, count, offset) in &entries {
+            let len = size_of(kind) * count;
+            if len > 4 {
+                assert_eq!(offset % 2, 0, "tag {tag} value is not word-aligned");
+                assert!(
+                    (offset + len) as usize <= tiff.len(),
+                    "tag {tag} value runs past the end of the file"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn strip_holds_the_samples() {
+        let world = test_world();
+        let values = north_up(&world, |t| t.moisture);
+        let tiff = encode_geotiff(&world, Samples::Float(values.clone()), "moisture", "");
+        let entries = entries(&tiff);
+        let value = |tag| entries.iter().find(|e| e.0 == tag).unwrap().3;
+
+        assert_eq!(value(256), world.width as u32);
+        assert_eq!(value(257), world.height as u32);
+        assert_eq!(value(258), 32);
+        assert_eq!(value(339), 3);
+        let (offset, len) = (value(273) as usize, value(279) as usize);
+        assert_eq!(len, 4 * values.len());
+        let strip: Vec<f32> = tiff[offset..offset + len]
+            .chunks(4)
+            .map(|b| f32::from_le_bytes(b.try_into().unwrap()))
+            .collect();
+        assert_eq!(strip, values);
+    }
+
+    #[test]
+    fn georeferencing_covers_the_globe() {
+        let world = test_world();
+        let samples = Samples::Indexed(
+            north_up(&world, |t| biome_order(t.biome)),
+            vec![[0; 3]; 256],
+        );
+        let tiff = encode_geotiff(&world, samples, "biome", "");
+        let entries = entries(&tiff);
+        let entry = |tag| *entries.iter().find(|e| e.0 == tag).unwrap();
+
+        // Palette: 3 × 256 shorts.
+        assert_eq!(entry(320).2, 768);
+        let scale = doubles(&tiff, entry(33550));
+        assert_eq!(scale[0] * world.width as f64, 360.0);
+        assert_eq!(scale[1] * world.height as f64, 180.0);
+        let tiepoint = doubles(&tiff, entry(33922));
+        assert_eq!(tiepoint[3], raster_west_deg(&world));
+        assert_eq!(tiepoint[4], 90.0);
+
+        // GeoKeyDirectory: header then one 4-short entry per key.
+        let (_, kind, count, offset) = entry(34735);
+        assert_eq!(kind, SHORT);
+        let keys: Vec<u16> = (0..count as usize)
+            .map(|k| u16_at(&tiff, offset as usize + 2 * k))
+            .collect();
+        assert_eq!(&keys[..3], &[1, 1, 1]);
+        assert_eq!(count, 4 + 4 * keys[3] as u32);
+        let model = keys[4..].chunks(4).find(|k| k[0] == 1024).unwrap();
+        assert_eq!(model[3], MODEL_TYPE_GEOGRAPHIC);
+        let radius = doubles(&tiff, entry(34736));
+        assert_eq!(radius, vec![sphere_radius_m(&world); 2]);
+    }
+
+    #[test]
+    fn pixel_centres_are_where_tiles_were_sampled() {
+        let world = test_world();
+        let grid = crate::grid::Grid::new(world.width, world.height);
+        let tiff = encode_geotiff(&world, Samples::Float(north_up(&world, |t| t.elevation)), "", "");
+        let entries = entries(&tiff);
+        let entry = |tag| *entries.iter().find(|e| e.0 == tag).unwrap();
+        let scale = doubles(&tiff, entry(33550));
+        let tiepoint = doubles(&tiff, entry(33922));
+
+        for (x, y) in [(0, 0), (5, 17), (world.width - 1, world.height - 1)] {
+            // PixelIsArea: the tiepoint is the north-west corner of pixel (0, 0).
+            let lon = tiepoint[3] + (x as f64 + 0.5) * scale[0];
+            let lat = tiepoint[4] - (y as f64 + 0.5) * scale[1];
+            let tile = &world.tiles[raster_tile(&world, x, y)];
+            let (tile_lon, tile_lat) = grid.lon_lat(tile.q, tile.r);
+            let dlon = (lon - tile_lon.to_degrees()).rem_euclid(360.0);
+            assert!(dlon.min(360.0 - dlon) < 1e-9);
+            assert!((lat - tile_lat.to_degrees()).abs() < 1e-9);
+        }
+    }
+}
@@ -0,0 +1,228 @@
+use crate::world::{Tile, World};
+use image::{ImageBuffer, Luma};
+use serde::Serialize;
+
+/// How surface heights are mapped onto the 16-bit range.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum HeightRange {
+    /// Stretch this world's lowest and highest surface to `0` and `65 535`
+    /// for maximum precision.
+    Auto,
+    /// Map the full elevation scale (`-1.0 ..= 1.0` around sea level) so
+    /// heightmaps of different worlds share one vertical scale.  Ice can
+    /// stack a surface above the top of that scale; the range then reaches
+    /// up to it, so ice-capped summits are never clipped.
+    Fixed,
+}
+
+/// Options for [`export_heightmap`].
+#[derive(Clone, Copy, Debug)]
+pub struct HeightmapOptions {
+    pub range: HeightRange,
+    /// Clamp everything below sea level to sea level (flat water surface).
+    pub flatten_oceans: bool,
+}
+
+impl Default for HeightmapOptions {
+    fn default() -> Self {
+        HeightmapOptions {
+            range: HeightRange::Auto,
+            flatten_oceans: false,
+        }
+    }
+}
+
+/// Sidecar metadata telling an engine how to scale the raw samples.
+#[derive(Serialize)]
+struct HeightmapInfo {
+    width: i32,
+    height: i32,
+    /// Sample encoding shared by the PNG and RAW files.
+    format: &'static str,
+    /// Byte order of the `.r16` file.
+    byte_order: &'static str,
+    /// Elevation in metres of sample value `0`.
+    min_elevation_m: f32,
+    /// Elevation in metres of sample value `65535`.
+    max_elevation_m: f32,
+    /// Metres per sample step: `elevation_m = min_elevation_m + value × metres_per_value`.
+    metres_per_value: f32,
+    /// Sample value of sea level.
+    sea_level_value: u16,
+    /// Vertical scale of the world: metres per normalised elevation unit.
+    metres_per_unit: f32,
+    /// Ground distance between two columns at the equator, in metres.
+    metres_per_pixel_x: f32,
+    /// Ground distance between two rows (constant for this projection), in metres.
+    metres_per_pixel_y: f32,
+    /// `true` when oceans were flattened to sea level.
+    flatten_oceans: bool,
+}
+
+/// Height of the terrain surface (bedrock + ice) above sea level in metres.
+pub fn surface_elevation_m(world: &World, tile: &Tile) -> f32 {
+    tile.elevation_m + tile.ice_thickness * world.metres_per_unit
+}
+
+/// Writes a heightmap for game-engine terrain import, next to each other:
+///
+/// - `<base>.png`  — 16-bit grayscale PNG
+/// - `<base>.r16`  — headerless 16-bit little-endian RAW, rows in image order
+/// - `<base>.json` — vertical and horizontal scale of the samples
+///
+/// Both rasters use the `world.png` layout (column `q`, row `r`).  Heights are
+/// the terrain surface in metres, ice sheets included.
+pub fn export_heightmap(world: &World, options: &HeightmapOptions, base: &str) {
+    let (samples, info) = encode_heightmap(world, options);
+    let (w, h) = (world.width as u32, world.height as u32);
+
+    let raw: Vec<u8> = samples.iter().flat_map(|v| v.to_le_bytes()).collect();
+    std::fs::write(format!("{base}.r16"), raw).expect("failed to write RAW heightmap");
+
+    let img: ImageBuffer<Luma<u16>, Vec<u16>> = ImageBuffer::from_raw(w, h, samples).unwrap();
+    img.save(format!("{base}.png")).unwrap();
+
+    let json = serde_json::to_string_pretty(&info).unwrap();
+    std::fs::write(format!("{base}.json"), json).expect("failed to write heightmap sidecar");
+}
+
+/// Row-major 16-bit samples of [`export_heightmap`] and their sidecar.
+fn encode_heightmap(world: &World, options: &HeightmapOptions) -> (Vec<u16>, HeightmapInfo) {
+    let w = world.width as usize;
+    let h = world.height as usize;
+
+    let surface = |tile: &Tile| {
+        let m = surface_elevation_m(world, tile);
+        if options.flatten_oceans {
+            m.max(0.0)
+        } else {
+            m
+        }
+    };
+
+    let (lo, hi) = match options.range {
+        HeightRange::Auto => world
+            .tiles
+            .iter()
+            .map(surface)
+            .fold((f32::MAX, f32::MIN), |(lo, hi), v| (lo.min(v), hi.max(v))),
+        HeightRange::Fixed => {
+            let top = (1.0 - world.sea_level) * world.metres_per_unit;
+            let bottom = (-1.0 - world.sea_level) * world.metres_per_unit;
+            let highest = world.tiles.iter().map(surface).fold(top, f32::max);
+            (if options.flatten_oceans { 0.0 } else { bottom }, highest)
+        }
+    };
+    // Guard against a perfectly flat world.
+    let span = (hi - lo).max(1.0);
+    let to_value = |m: f32| {
+        (((m - lo) / span) * u16::MAX as f32)
+            .round()
+            .clamp(0.0, u16::MAX as f32) as u16
+    };
+
+    // Row-major samples (row r, then column q) — the order both files need.
+    let mut samples = vec![0u16; w * h];
+    for tile in &world.tiles {
+        samples[tile.r as usize * w + tile.q as usize] = to_value(surface(tile));
+    }
+
+    let circumference_m = world.circumference_km * 1000.0;
+    let info = HeightmapInfo {
+        width: world.width,
+        height: world.height,
+        format: "uint16",
+        byte_order: "little-endian",
+        min_elevation_m: lo,
+        max_elevation_m: lo + span,
+        metres_per_value: span / u16::MAX as f32,
+        sea_level_value: to_value(0.0),
+        metres_per_unit: world.metres_per_unit,
+        metres_per_pixel_x: circumference_m / world.width as f32,
+        // Rows cover pole to pole: half the circumference.
+        metres_per_pixel_y: circumference_m * 0.5 / world.height as f32,
+        flatten_oceans: options.flatten_oceans,
+    };
+    (samples, info)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generation::test_world;
+
+    /// Height in metres a sample stands for, per the sidecar.
+    fn decode(info: &HeightmapInfo, value: u16) -> f32 {
+        info.min_elevation_m + value as f32 * info.metres_per_value
+    }
+
+    #[test]
+    fn samples_decode_to_the_surface() {
+        let world = test_world();
+        let w = world.width as usize;
+        for range in [HeightRange::Auto, HeightRange::Fixed] {
+            let options = HeightmapOptions {
+                range,
+                flatten_oceans: false,
+            };
+            let (samples, info) = encode_heightmap(&world, &options);
+            assert_eq!(samples.len(), world.tiles.len());
+            for tile in &world.tiles {
+                let value = samples[tile.r as usize * w + tile.q as usize];
+                let error = decode(&info, value) - surface_elevation_m(&world, tile);
+                assert!(
+                    error.abs() <= info.metres_per_value * 0.5 + 0.05,
+                    "{range:?}: off by {error}"
+                );
+            }
+            assert!(decode(&info, info.sea_level_value).abs() <= info.metres_per_value * 0.5);
+            if range == HeightRange::Auto {
+                assert_eq!(samples.iter().min(), Some(&0));
+                assert_eq!(samples.iter().max(), Some(&u16::MAX));
+            }
+        }
+    }
+
+    #[test]
+    fn fixed_range_covers_the_scale_and_the_ice_on_top() {
+        let mut world = test_world();
+        let mpu = world.metres_per_unit;
+        let options = HeightmapOptions {
+            range: HeightRange::Fixed,
+            flatten_oceans: false,
+        };
+        let (_, info) = encode_heightmap(&world, &options);
+        assert_eq!(info.min_elevation_m, (-1.0 - world.sea_level) * mpu);
+        assert_eq!(info.max_elevation_m, (1.0 - world.sea_level) * mpu);
+
+        // A thick ice cap on a summit at the top of the scale.
+        let peak = &mut world.tiles[0];
+        peak.elevation = 1.0;
+        peak.elevation_m = (1.0 - world.sea_level) * mpu;
+        peak.ice_thickness = 0.4;
+        let (samples, info) = encode_heightmap(&world, &options);
+        let top = surface_elevation_m(&world, &world.tiles[0]);
+        assert!((info.max_elevation_m - top).abs() < 1.0);
+        let (q, r) = (world.tiles[0].q as usize, world.tiles[0].r as usize);
+        assert_eq!(samples[r * world.width as usize + q], u16::MAX);
+        assert!(
+            samples.iter().filter(|&&v| v == u16::MAX).count() == 1,
+            "other summits clip"
+        );
+
+        let flat = HeightmapOptions {
+            flatten_oceans: true,
+            ..options
+        };
+        let (samples, info) = encode_heightmap(&world, &flat);
+        assert_eq!((info.min_elevation_m, info.sea_level_value), (0.0, 0));
+        // Water flattens to sea level; floating ice still stands on it.
+        let wet = |t: &&Tile| surface_elevation_m(&world, t) <= 0.0;
+        for tile in world.tiles.iter().filter(wet) {
+            assert_eq!(
+                samples[tile.r as usize * world.width as usize + tile.q as usize],
+                0
+            );
+        }
+    }
+}
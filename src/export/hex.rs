@@ -0,0 +1,350 @@
+/// Hex-grid worlds drawn as hexagons, plus a compact JSON for game engines.
+///
+/// Maps are north-up with longitude 0° at the left edge and wrap east–west:
+/// hexes cut off at one edge continue at the other.
+use super::png::{DASH_ON, PERIOD, REFERENCE_PARALLELS};
+use super::render::{RenderOptions, mix, tile_colors};
+use crate::grid::{Grid, HexOrientation};
+use crate::world::*;
+use image::{Rgb, RgbImage};
+use serde_json::json;
+use std::io::Write;
+
+/// Hex radius (centre to vertex) in pixels.
+const HEX_RADIUS_PX: f64 = 6.0;
+/// Fraction to darken hex outlines by in the PNG.
+const OUTLINE_DARKNESS: f32 = 0.25;
+
+/// Pixel geometry of a hex map.
+struct HexMap {
+    grid: Grid,
+    orientation: HexOrientation,
+    width: f64,
+    height: f64,
+}
+
+impl HexMap {
+    fn new(world: &World) -> Self {
+        let grid = world.grid();
+        let Layout::Hex(orientation) = world.layout else {
+            panic!("hex export needs a hex grid");
+        };
+        let (w, h, s) = (grid.width as f64, grid.height as f64, HEX_RADIUS_PX);
+        let (width, height) = match orientation {
+            HexOrientation::Pointy => (s * 3f64.sqrt() * w, s * (1.5 * h + 0.5)),
+            HexOrientation::Flat => (1.5 * s * w, s * 3f64.sqrt() * (h + 0.5)),
+        };
+        HexMap {
+            grid,
+            orientation,
+            width,
+            height,
+        }
+    }
+
+    /// Size of the image in whole pixels.
+    fn size(&self) -> (u32, u32) {
+        (self.width.ceil() as u32, self.height.ceil() as u32)
+    }
+
+    /// Pixel centre of hex `(q, r)`, unwrapped.
+    fn centre(&self, q: i32, r: i32) -> (f64, f64) {
+        let s = HEX_RADIUS_PX;
+        let (q, r) = (q as f64, r as f64);
+        // Axial → plane, measured upwards from the bottom edge.
+        let (x, y) = match self.orientation {
+            HexOrientation::Pointy => (
+                s * 3f64.sqrt() * (q + r / 2.0) + s * 3f64.sqrt() / 2.0,
+                s * 1.5 * r + s,
+            ),
+            HexOrientation::Flat => (
+                s * 1.5 * q + s,
+                s * 3f64.sqrt() * (r + q / 2.0) + s * 3f64.sqrt() / 2.0,
+            ),
+        };
+        (x, self.height - y)
+    }
+
+    /// Hex under pixel position `(x, y)` as a flat tile index, with how close
+    /// the point is to the hex's outline (`1.0` on it, `0.0` at the centre).
+    fn hex_at(&self, x: f64, y: f64) -> Option<(usize, f64)> {
+        let s = HEX_RADIUS_PX;
+        let (x0, y0) = self.centre(0, 0);
+        let (px, py) = (x - x0, y0 - y);
+        let (qf, rf) = match self.orientation {
+            HexOrientation::Pointy => (
+                (3f64.sqrt() / 3.0 * px - py / 3.0) / s,
+                (2.0 / 3.0 * py) / s,
+            ),
+            HexOrientation::Flat => (
+                (2.0 / 3.0 * px) / s,
+                (-px / 3.0 + 3f64.sqrt() / 3.0 * py) / s,
+            ),
+        };
+        // Cube rounding: round all three coordinates, then fix the one that
+        // moved furthest so they still sum to zero.
+        let sf = -qf - rf;
+        let (mut q, mut r, s_) = (qf.round(), rf.round(), sf.round());
+        let (dq, dr, ds) = ((q - qf).abs(), (r - rf).abs(), (s_ - sf).abs());
+        if dq > dr && dq > ds {
+            q = -r - s_;
+        } else if dr > ds {
+            r = -q - s_;
+        }
+        let (dq, dr) = (qf - q, rf - r);
+        let ds = -dq - dr;
+        let edge = (dq - dr).abs().max((dr - ds).abs()).max((ds - dq).abs());
+
+        let (q, r) = (q as i32, r as i32);
+        let row = self.grid.offset(q, r).1;
+        (row >= 0 && row < self.grid.height).then(|| (self.grid.index(q, r), edge))
+    }
+
+    /// Vertices of the hex centred at `(x, y)`.
+    fn corners(&self, x: f64, y: f64) -> [(f64, f64); 6] {
+        let start = match self.orientation {
+            HexOrientation::Pointy => 30.0f64,
+            HexOrientation::Flat => 0.0,
+        };
+        std::array::from_fn(|k| {
+            let a = (start + 60.0 * k as f64).to_radians();
+            (x + HEX_RADIUS_PX * a.cos(), y - HEX_RADIUS_PX * a.sin())
+        })
+    }
+
+    /// Pixel row of latitude `lat_deg`, matching the latitudes of hex centres.
+    fn parallel_y(&self, lat_deg: f64) -> f64 {
+        let s = HEX_RADIUS_PX;
+        let rows = (lat_deg + 90.0) / 180.0 * self.grid.height as f64;
+        let y = match self.orientation {
+            HexOrientation::Pointy => s + 1.5 * s * (rows - 0.5),
+            HexOrientation::Flat => s * 3f64.sqrt() * (rows - 0.25 + 0.5),
+        };
+        self.height - y
+    }
+}
+
+/// Writes `world.png` for a hex world: one filled hexagon per tile with a
+/// faint outline, and the reference parallels.
+pub fn export_hex_png(world: &World, options: &RenderOptions, path: &str) {
+    let map = HexMap::new(world);
+    let (w, h) = map.size();
+    let colors = tile_colors(world, options);
+    // In-radius in pixels: how far the outline is from the centre.
+    let inradius = HEX_RADIUS_PX * 3f64.sqrt() / 2.0;
+
+    let mut img = RgbImage::new(w, h);
+    for y in 0..h {
+        for x in 0..w {
+            let Some((i, edge)) = map.hex_at(x as f64 + 0.5, y as f64 + 0.5) else {
+                continue;
+            };
+            let mut color = colors[i];
+            if (1.0 - edge) * inradius < 0.6 {
+                color = mix(color, [0, 0, 0], OUTLINE_DARKNESS);
+            }
+            img.put_pixel(x, y, Rgb(color));
+        }
+    }
+
+    for &(lat, color) in REFERENCE_PARALLELS {
+        let row = map.parallel_y(lat).round() as u32;
+        if row >= h {
+            continue;
+        }
+        for x in (0..w).filter(|x| x % PERIOD < DASH_ON) {
+            img.put_pixel(x, row, Rgb(color));
+        }
+    }
+
+    img.save(path).unwrap();
+}
+
+/// Writes `world.svg` for a hex world: one `<polygon>` per tile carrying its
+/// axial `data-q` / `data-r`, and the reference parallels.
+pub fn export_hex_svg(world: &World, options: &RenderOptions, path: &str) {
+    let map = HexMap::new(world);
+    let (w, h) = (map.width, map.height);
+    let colors = tile_colors(world, options);
+
+    let mut out: Vec<u8> = Vec::with_capacity(world.tiles.len() * 160);
+    writeln!(out, r#"<?xml version="1.0" encoding="UTF-8"?>"#).unwrap();
+    writeln!(
+        out,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{w:.0}" height="{h:.0}" viewBox="0 0 {w:.1} {h:.1}">"#
+    )
+    .unwrap();
+
+    writeln!(
+        out,
+        r##"<g id="hexes" stroke="#000000" stroke-opacity="{OUTLINE_DARKNESS}" stroke-width="0.5">"##
+    )
+    .unwrap();
+    for (i, tile) in world.tiles.iter().enumerate() {
+        let (x, y) = map.centre(tile.q, tile.r);
+        let x = x.rem_euclid(w);
+        let [cr, cg, cb] = colors[i];
+        // Hexes across the left or right edge are drawn on both sides.
+        for dx in [-w, 0.0, w] {
+            if x + dx + HEX_RADIUS_PX < 0.0 || x + dx - HEX_RADIUS_PX > w {
+                continue;
+            }
+            let points: Vec<String> = map
+                .corners(x + dx, y)
+                .iter()
+                .map(|(px, py)| format!("{px:.2},{py:.2}"))
+                .collect();
+            writeln!(
+                out,
+                r##"<polygon points="{}" fill="#{cr:02X}{cg:02X}{cb:02X}" data-q="{}" data-r="{}"/>"##,
+                points.join(" "),
+                tile.q,
+                tile.r
+            )
+            .unwrap();
+        }
+    }
+    writeln!(out, "</g>").unwrap();
+
+    for (lat, stroke) in [
+        (0.0, "#FF3333"),
+        (23.5, "#FFA500"),
+        (-23.5, "#FFA500"),
+        (66.5, "#00CFFF"),
+        (-66.5, "#00CFFF"),
+    ] {
+        let y = map.parallel_y(lat);
+        writeln!(
+            out,
+            r##"<line x1="0" y1="{y:.1}" x2="{w:.1}" y2="{y:.1}" stroke="{stroke}" stroke-width="1" stroke-dasharray="6,4" opacity="0.75"/>"##
+        )
+        .unwrap();
+    }
+
+    writeln!(out, "</svg>").unwrap();
+    std::fs::write(path, &out).expect("failed to write SVG");
+}
+
+/// Writes `hex.json`: the grid's shape and, per hex, its axial and offset
+/// coordinates, centre, climate, biome and the axial coordinates of its
+/// neighbours (wrapped east–west).
+pub fn export_hex_json(world: &World, path: &str) {
+    let map = HexMap::new(world);
+    let grid = map.grid;
+    let round = |v: f64, scale: f64| (v * scale).round() / scale;
+    let tiles: Vec<_> = world
+        .tiles
+        .iter()
+        .map(|tile| {
+            let (col, row) = grid.offset(tile.q, tile.r);
+            let (lon, lat) = grid.lon_lat(tile.q, tile.r);
+            let neighbors: Vec<[i32; 2]> = grid
+                .hex_neighbors(tile.q, tile.r)
+                .map(|(q, r)| [q, r])
+                .collect();
+            json!({
+                "q": tile.q,
+                "r": tile.r,
+                "col": col.rem_euclid(grid.width),
+                "row": row,
+                "lon_deg": round(lon.to_degrees(), 1e4),
+                "lat_deg": round(lat.to_degrees(), 1e4),
+                "biome": tile.biome,
+                "elevation_m": round(tile.elevation_m as f64, 10.0),
+                "temperature_c": round(tile.temperature_c as f64, 10.0),
+                "precipitation_mm": round(tile.precipitation_mm as f64, 1.0),
+                "ice": tile.ice_thickness > 0.0,
+                "neighbors": neighbors,
+            })
+        })
+        .collect();
+    let orientation = match map.orientation {
+        HexOrientation::Pointy => "pointy",
+        HexOrientation::Flat => "flat",
+    };
+    let doc = json!({
+        "orientation": orientation,
+        "coordinates": "axial",
+        "columns": grid.width,
+        "rows": grid.height,
+        "wrap": "east-west",
+        "seed": world.seed,
+        "planet_type": world.planet_type,
+        "tiles": tiles,
+    });
+    std::fs::write(path, doc.to_string()).expect("failed to write hex JSON");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generation::generate_world;
+
+    fn hex_world(orientation: HexOrientation) -> World {
+        let grid = Grid::hex(orientation, 32);
+        generate_world(grid, 7, 0.0, 0.5, PlanetType::Terran, 40_000.0, 1.0)
+    }
+
+    #[test]
+    fn pixels_find_the_hex_they_are_drawn_for() {
+        for orientation in [HexOrientation::Pointy, HexOrientation::Flat] {
+            let world = hex_world(orientation);
+            let map = HexMap::new(&world);
+            for (i, tile) in world.tiles.iter().enumerate() {
+                // Hexes past the right edge are found again at the left.
+                let (x, y) = map.centre(tile.q, tile.r);
+                let (found, edge) = map.hex_at(x.rem_euclid(map.width), y).unwrap();
+                assert_eq!(found, i, "{orientation:?} ({}, {})", tile.q, tile.r);
+                assert!(edge < 1e-9);
+                // Just inside a corner the hex is still the same, on its outline.
+                for (cx, cy) in map.corners(x, y) {
+                    let (found, edge) = map
+                        .hex_at(x + 0.95 * (cx - x), y + 0.95 * (cy - y))
+                        .unwrap();
+                    assert_eq!(found, i);
+                    assert!(edge > 0.9);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn json_lists_every_hex_with_wrapped_neighbours() {
+        let world = hex_world(HexOrientation::Pointy);
+        let path = std::env::temp_dir().join(format!("hex-{}.json", std::process::id()));
+        export_hex_json(&world, path.to_str().unwrap());
+        let doc: serde_json::Value =
+            serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        let grid = world.grid();
+        assert_eq!(doc["columns"], grid.width);
+        assert_eq!(doc["rows"], grid.height);
+        let tiles = doc["tiles"].as_array().unwrap();
+        assert_eq!(tiles.len(), world.tiles.len());
+        for (json, tile) in tiles.iter().zip(&world.tiles) {
+            assert_eq!(
+                (json["q"].as_i64(), json["r"].as_i64()),
+                (Some(tile.q as i64), Some(tile.r as i64))
+            );
+            let col = json["col"].as_i64().unwrap();
+            assert!((0..grid.width as i64).contains(&col));
+            // Hexes away from the poles have all six neighbours, and each
+            // lists this hex back.
+            let neighbors = json["neighbors"].as_array().unwrap();
+            let row = json["row"].as_i64().unwrap();
+            if row > 0 && row < grid.height as i64 - 1 {
+                assert_eq!(neighbors.len(), 6);
+            }
+            for n in neighbors {
+                let (q, r) = (n[0].as_i64().unwrap() as i32, n[1].as_i64().unwrap() as i32);
+                let back = &tiles[grid.index(q, r)]["neighbors"];
+                let listed = back.as_array().unwrap().iter().any(|b| {
+                    grid.index(b[0].as_i64().unwrap() as i32, b[1].as_i64().unwrap() as i32)
+                        == grid.index(tile.q, tile.r)
+                });
+                assert!(listed, "({}, {}) → ({q}, {r})", tile.q, tile.r);
+            }
+        }
+    }
+}
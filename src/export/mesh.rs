@@ -0,0 +1,411 @@
+use super::geo::{sample, sphere_radius_m};
+use super::heightmap::surface_elevation_m;
+use super::render::{RenderOptions, tile_colors};
+use crate::world::World;
+use image::{ImageFormat, RgbImage};
+use serde_json::json;
+use std::io::{Cursor, Write};
+
+/// Frame the normals of the globe's normal map are given in.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum NormalSpace {
+    /// Relative to the surface: east, north, up.
+    Tangent,
+    /// The globe's model space, north pole at `+Y`.
+    Object,
+}
+
+/// Options for the 3-D exporters.
+#[derive(Clone, Copy, Debug)]
+pub struct MeshOptions {
+    /// Vertical exaggeration of the relief.  At `1.0` the tallest mountains
+    /// are about a thousandth of the planet radius — invisible on a globe.
+    pub exaggeration: f32,
+    /// Vertices around the equator; the globe has half as many rings.
+    pub segments: u32,
+    /// Frame of the normal map.
+    pub normal_space: NormalSpace,
+}
+
+impl Default for MeshOptions {
+    fn default() -> Self {
+        MeshOptions {
+            exaggeration: 40.0,
+            segments: 720,
+            normal_space: NormalSpace::Tangent,
+        }
+    }
+}
+
+/// Indexed triangle mesh with per-vertex normals and texture coordinates.
+struct Mesh {
+    positions: Vec<[f32; 3]>,
+    normals: Vec<[f32; 3]>,
+    /// Texture coordinates with `(0, 0)` at the top-left of the texture.
+    uvs: Vec<[f32; 2]>,
+    triangles: Vec<[u32; 3]>,
+}
+
+/// Distance from the planet centre of the displaced surface, on a unit sphere.
+///
+/// Oceans are drawn at the water surface, so only land is displaced.
+pub fn surface_radius(world: &World, lon_deg: f64, lat_deg: f64, exaggeration: f32) -> f32 {
+    let height = sample(world, lon_deg, lat_deg, |t| {
+        surface_elevation_m(world, t).max(0.0)
+    });
+    1.0 + exaggeration * height / sphere_radius_m(world) as f32
+}
+
+/// Unit-sphere position of a point, Y-up (north pole at `+Y`).
+pub fn sphere_point(lon_deg: f64, lat_deg: f64, radius: f32) -> [f32; 3] {
+    let (lon, lat) = (lon_deg.to_radians(), lat_deg.to_radians());
+    [
+        (lat.cos() * lon.cos()) as f32 * radius,
+        lat.sin() as f32 * radius,
+        (-lat.cos() * lon.sin()) as f32 * radius,
+    ]
+}
+
+/// Builds a UV sphere with `segments + 1` columns (the seam is duplicated so
+/// the texture wraps) and `segments / 2 + 1` rings, pole to pole.
+fn uv_sphere(world: &World, options: &MeshOptions) -> Mesh {
+    let cols = options.segments.max(8);
+    let rings = cols / 2;
+    let stride = cols + 1;
+
+    let mut positions = Vec::with_capacity(((rings + 1) * stride) as usize);
+    let mut uvs = Vec::with_capacity(positions.capacity());
+    for i in 0..=rings {
+        // Ring 0 is the south pole, matching row 0 of the biome texture.
+        let v = i as f64 / rings as f64;
+        let lat = v * 180.0 - 90.0;
+        for j in 0..=cols {
+            let u = j as f64 / cols as f64;
+            let lon = u * 360.0;
+            let radius = surface_radius(world, lon, lat, options.exaggeration);
+            positions.push(sphere_point(lon, lat, radius));
+            uvs.push([u as f32, v as f32]);
+        }
+    }
+
+    // Counter-clockwise seen from outside: east, then north.
+    let mut triangles = Vec::with_capacity((2 * rings * cols) as usize);
+    for i in 0..rings {
+        for j in 0..cols {
+            let a = i * stride + j;
+            let b = a + 1;
+            let c = a + stride;
+            let d = c + 1;
+            if i > 0 {
+                triangles.push([a, b, c]);
+            }
+            if i < rings - 1 {
+                triangles.push([b, d, c]);
+            }
+        }
+    }
+
+    let mut normals = vec![[0.0f32; 3]; positions.len()];
+    for &[a, b, c] in &triangles {
+        let n = face_normal(
+            positions[a as usize],
+            positions[b as usize],
+            positions[c as usize],
+        );
+        for k in [a, b, c] {
+            for (acc, x) in normals[k as usize].iter_mut().zip(n) {
+                *acc += x;
+            }
+        }
+    }
+    // Duplicated vertices (seam column, pole rings) must share one normal.
+    for i in 0..=rings {
+        let row = (i * stride) as usize..((i + 1) * stride) as usize;
+        if i == 0 || i == rings {
+            let sum = normals[row.clone()]
+                .iter()
+                .fold([0.0; 3], |s, n| add(s, *n));
+            normals[row].fill(sum);
+        } else {
+            let sum = add(normals[row.start], normals[row.end - 1]);
+            normals[row.start] = sum;
+            normals[row.end - 1] = sum;
+        }
+    }
+    for n in &mut normals {
+        let len = (n[0] * n[0] + n[1] * n[1] + n[2] * n[2])
+            .sqrt()
+            .max(f32::EPSILON);
+        *n = n.map(|x| x / len);
+    }
+
+    Mesh {
+        positions,
+        normals,
+        uvs,
+        triangles,
+    }
+}
+
+/// Area-weighted normal of triangle `abc` (counter-clockwise front face).
+pub fn face_normal(a: [f32; 3], b: [f32; 3], c: [f32; 3]) -> [f32; 3] {
+    let u = [b[0] - a[0], b[1] - a[1], b[2] - a[2]];
+    let v = [c[0] - a[0], c[1] - a[1], c[2] - a[2]];
+    [
+        u[1] * v[2] - u[2] * v[1],
+        u[2] * v[0] - u[0] * v[2],
+        u[0] * v[1] - u[1] * v[0],
+    ]
+}
+
+fn add(a: [f32; 3], b: [f32; 3]) -> [f32; 3] {
+    [a[0] + b[0], a[1] + b[1], a[2] + b[2]]
+}
+
+/// PNG bytes of the biome map used as the globe texture (no reference lines).
+fn texture_png(world: &World, render: &RenderOptions) -> Vec<u8> {
+    let h = world.height as usize;
+    let colors = tile_colors(world, render);
+    let img = RgbImage::from_fn(world.width as u32, world.height as u32, |q, r| {
+        image::Rgb(colors[q as usize * h + r as usize])
+    });
+    let mut png = Vec::new();
+    img.write_to(&mut Cursor::new(&mut png), ImageFormat::Png)
+        .unwrap();
+    png
+}
+
+/// Writes a displaced, biome-textured globe into `dir`:
+///
+/// - `globe.glb`            — binary glTF 2.0 with the texture embedded
+/// - `globe.obj` + `.mtl`   — Wavefront OBJ referencing `globe_texture.png`
+/// - `globe_texture.png`    — the biome map (same colours as `world.png`)
+///
+/// The globe has radius 1 with the north pole at `+Y`.
+pub fn export_mesh(world: &World, options: &MeshOptions, render: &RenderOptions, dir: &str) {
+    let mesh = uv_sphere(world, options);
+    let png = texture_png(world, render);
+    std::fs::write(format!("{dir}/globe_texture.png"), &png).expect("failed to write texture");
+    write_glb(&mesh, &png, &format!("{dir}/globe.glb"));
+    write_obj(&mesh, dir);
+}
+
+/// Binary glTF 2.0: one mesh, one material, the texture as an embedded PNG.
+fn write_glb(mesh: &Mesh, png: &[u8], path: &str) {
+    // ── Binary buffer: positions · normals · uvs · indices · image ────────────
+    let mut bin: Vec<u8> = Vec::new();
+    let mut views = Vec::new();
+    let mut push_view = |bin: &mut Vec<u8>, bytes: Vec<u8>, target: Option<u32>| {
+        while !bin.len().is_multiple_of(4) {
+            bin.push(0);
+        }
+        let mut view = json!({ "buffer": 0, "byteOffset": bin.len(), "byteLength": bytes.len() });
+        if let Some(target) = target {
+            view["target"] = json!(target);
+        }
+        views.push(view);
+        bin.extend(bytes);
+        views.len() - 1
+    };
+    const ARRAY_BUFFER: Option<u32> = Some(34962);
+    const ELEMENT_ARRAY_BUFFER: Option<u32> = Some(34963);
+
+    let floats = |v: &[f32]| v.iter().flat_map(|x| x.to_le_bytes()).collect::<Vec<u8>>();
+    let positions = push_view(
+        &mut bin,
+        floats(mesh.positions.as_flattened()),
+        ARRAY_BUFFER,
+    );
+    let normals = push_view(&mut bin, floats(mesh.normals.as_flattened()), ARRAY_BUFFER);
+    let uvs = push_view(&mut bin, floats(mesh.uvs.as_flattened()), ARRAY_BUFFER);
+    let indices = mesh
+        .triangles
+        .as_flattened()
+        .iter()
+        .flat_map(|i| i.to_le_bytes())
+        .collect();
+    let indices = push_view(&mut bin, indices, ELEMENT_ARRAY_BUFFER);
+    let image = push_view(&mut bin, png.to_vec(), None);
+    while !bin.len().is_multiple_of(4) {
+        bin.push(0);
+    }
+
+    let (min, max) = mesh
+        .positions
+        .iter()
+        .fold(([f32::MAX; 3], [f32::MIN; 3]), |(lo, hi), p| {
+            (
+                std::array::from_fn(|k| lo[k].min(p[k])),
+                std::array::from_fn(|k| hi[k].max(p[k])),
+            )
+        });
+    let count = mesh.positions.len();
+
+    const FLOAT: u32 = 5126;
+    const UNSIGNED_INT: u32 = 5125;
+    const REPEAT: u32 = 10497;
+    const CLAMP_TO_EDGE: u32 = 33071;
+    let gltf = json!({
+        "asset": { "version": "2.0", "generator": "map-generator" },
+        "scene": 0,
+        "scenes": [{ "nodes": [0] }],
+        "nodes": [{ "mesh": 0, "name": "globe" }],
+        "meshes": [{
+            "name": "globe",
+            "primitives": [{
+                "attributes": { "POSITION": 0, "NORMAL": 1, "TEXCOORD_0": 2 },
+                "indices": 3,
+                "material": 0,
+            }],
+        }],
+        "materials": [{
+            "name": "biomes",
+            "pbrMetallicRoughness": {
+                "baseColorTexture": { "index": 0 },
+                "metallicFactor": 0.0,
+                "roughnessFactor": 1.0,
+            },
+        }],
+        "textures": [{ "sampler": 0, "source": 0 }],
+        // Wrap around the seam, clamp at the poles.
+        "samplers": [{ "wrapS": REPEAT, "wrapT": CLAMP_TO_EDGE }],
+        "images": [{ "bufferView": image, "mimeType": "image/png" }],
+        "accessors": [
+            { "bufferView": positions, "componentType": FLOAT, "count": count, "type": "VEC3",
+              "min": min, "max": max },
+            { "bufferView": normals, "componentType": FLOAT, "count": count, "type": "VEC3" },
+            { "bufferView": uvs, "componentType": FLOAT, "count": count, "type": "VEC2" },
+            { "bufferView": indices, "componentType": UNSIGNED_INT,
+              "count": mesh.triangles.len() * 3, "type": "SCALAR" },
+        ],
+        "bufferViews": views,
+        "buffers": [{ "byteLength": bin.len() }],
+    });
+    let mut json = serde_json::to_vec(&gltf).unwrap();
+    while !json.len().is_multiple_of(4) {
+        json.push(b' ');
+    }
+
+    // ── GLB container: header · JSON chunk · BIN chunk ────────────────────────
+    let total = 12 + 8 + json.len() + 8 + bin.len();
+    let mut out: Vec<u8> = Vec::with_capacity(total);
+    out.extend(b"glTF");
+    out.extend(2u32.to_le_bytes());
+    out.extend((total as u32).to_le_bytes());
+    out.extend((json.len() as u32).to_le_bytes());
+    out.extend(b"JSON");
+    out.extend(json);
+    out.extend((bin.len() as u32).to_le_bytes());
+    out.extend(b"BIN\0");
+    out.extend(bin);
+    std::fs::write(path, out).expect("failed to write glb");
+}
+
+/// Wavefront OBJ + MTL.  OBJ texture coordinates start at the bottom-left.
+fn write_obj(mesh: &Mesh, dir: &str) {
+    let mut mtl: Vec<u8> = Vec::new();
+    writeln!(mtl, "newmtl biomes").unwrap();
+    writeln!(mtl, "Ka 1 1 1\nKd 1 1 1\nKs 0 0 0\nillum 1").unwrap();
+    writeln!(mtl, "map_Kd globe_texture.png").unwrap();
+    std::fs::write(format!("{dir}/globe.mtl"), mtl).expect("failed to write MTL");
+
+    let mut out: Vec<u8> = Vec::with_capacity(mesh.positions.len() * 96);
+    writeln!(out, "mtllib globe.mtl\no globe").unwrap();
+    for p in &mesh.positions {
+        writeln!(out, "v {:.6} {:.6} {:.6}", p[0], p[1], p[2]).unwrap();
+    }
+    for n in &mesh.normals {
+        writeln!(out, "vn {:.5} {:.5} {:.5}", n[0], n[1], n[2]).unwrap();
+    }
+    for uv in &mesh.uvs {
+        writeln!(out, "vt {:.6} {:.6}", uv[0], 1.0 - uv[1]).unwrap();
+    }
+    writeln!(out, "usemtl biomes").unwrap();
+    for t in &mesh.triangles {
+        let [a, b, c] = t.map(|i| i + 1);
+        writeln!(out, "f {a}/{a}/{a} {b}/{b}/{b} {c}/{c}/{c}").unwrap();
+    }
+    std::fs::write(format!("{dir}/globe.obj"), out).expect("failed to write OBJ");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generation::test_world;
+
+    fn dot(a: [f32; 3], b: [f32; 3]) -> f32 {
+        a[0] * b[0] + a[1] * b[1] + a[2] * b[2]
+    }
+
+    #[test]
+    fn globe_faces_outwards_and_rises_only_on_land() {
+        let world = test_world();
+        let options = MeshOptions {
+            segments: 64,
+            ..MeshOptions::default()
+        };
+        let mesh = uv_sphere(&world, &options);
+        // 33 rings of 65 vertices; the pole rows have one triangle per quad.
+        assert_eq!(mesh.positions.len(), 33 * 65);
+        assert_eq!(mesh.triangles.len(), 2 * 32 * 64 - 2 * 64);
+        for &[a, b, c] in &mesh.triangles {
+            let [a, b, c] = [a, b, c].map(|i| mesh.positions[i as usize]);
+            let centroid = std::array::from_fn(|k| a[k] + b[k] + c[k]);
+            assert!(dot(face_normal(a, b, c), centroid) > 0.0);
+        }
+        for ((p, n), uv) in mesh.positions.iter().zip(&mesh.normals).zip(&mesh.uvs) {
+            let radius = dot(*p, *p).sqrt();
+            assert!((1.0 - 1e-6..1.1).contains(&radius), "{radius}");
+            assert!((dot(*n, *n) - 1.0).abs() < 1e-4);
+            assert!(dot(*n, *p) > 0.0);
+            assert!(uv.iter().all(|t| (0.0..=1.0).contains(t)));
+        }
+    }
+
+    #[test]
+    fn glb_holds_the_mesh_and_its_texture() {
+        let world = test_world();
+        let dir = std::env::temp_dir().join(format!("mesh-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let options = MeshOptions {
+            segments: 32,
+            ..MeshOptions::default()
+        };
+        export_mesh(
+            &world,
+            &options,
+            &RenderOptions::default(),
+            dir.to_str().unwrap(),
+        );
+        let glb = std::fs::read(dir.join("globe.glb")).unwrap();
+        let obj = std::fs::read_to_string(dir.join("globe.obj")).unwrap();
+        let texture = image::open(dir.join("globe_texture.png")).unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        let u32_at = |at: usize| u32::from_le_bytes(glb[at..at + 4].try_into().unwrap());
+        assert_eq!(&glb[..4], b"glTF");
+        assert_eq!(u32_at(8) as usize, glb.len());
+        let json_len = u32_at(12) as usize;
+        assert_eq!(&glb[16..20], b"JSON");
+        let gltf: serde_json::Value = serde_json::from_slice(&glb[20..20 + json_len]).unwrap();
+        assert_eq!(&glb[24 + json_len..28 + json_len], b"BIN\0");
+        assert_eq!(
+            u32_at(20 + json_len) as u64,
+            gltf["buffers"][0]["byteLength"]
+        );
+
+        let mesh = uv_sphere(&world, &options);
+        assert_eq!(gltf["accessors"][0]["count"], mesh.positions.len());
+        assert_eq!(gltf["accessors"][3]["count"], 3 * mesh.triangles.len());
+        let vertices = obj.lines().filter(|l| l.starts_with("v ")).count();
+        let faces = obj.lines().filter(|l| l.starts_with("f ")).count();
+        assert_eq!(
+            (vertices, faces),
+            (mesh.positions.len(), mesh.triangles.len())
+        );
+        assert_eq!(
+            (texture.width(), texture.height()),
+            (world.width as u32, world.height as u32)
+        );
+    }
+}
@@ -1,10 +1,53 @@
-/// Export backends — PNG, SVG, JSON and diagnostic noise maps.
+/// Export backends — PNG, SVG, JSON, heightmap, bathymetry and GeoTIFF rasters,
+/// GeoJSON layers, slippy-map tiles, Tiled maps, cube-sphere faces, hex maps,
+/// geodesic cells, game-engine terrain bundles, 3-D globe meshes with normal
+/// and ambient-occlusion maps, printable STL solids, diagnostic noise maps,
+/// colour-blind safe palettes, and print posters.
+pub mod bathymetry;
+pub mod cube;
+pub mod engine;
+mod font;
+mod geo;
+pub mod geodesic;
+pub mod geojson;
+pub mod geotiff;
+pub mod heightmap;
+pub mod hex;
 pub mod json;
+pub mod mesh;
 pub mod noise_maps;
+mod overlay;
+pub mod palette;
 pub mod png;
+pub mod poster;
+mod projection;
+pub mod relief;
+pub mod render;
+pub mod stl;
 pub mod svg;
+pub mod theme;
+pub mod tiled;
+pub mod tiles;
+mod vector;
 
+pub use bathymetry::export_bathymetry;
+pub use cube::export_cube;
+pub use engine::export_engine_bundle;
+pub use geodesic::{export_geodesic_json, export_geodesic_png};
+pub use geojson::export_geojson;
+pub use geotiff::export_geotiff;
+pub use heightmap::{HeightRange, HeightmapOptions, export_heightmap};
+pub use hex::{export_hex_json, export_hex_png, export_hex_svg};
 pub use json::export_json;
+pub use mesh::{MeshOptions, NormalSpace, export_mesh};
 pub use noise_maps::export_noise_maps;
+pub use palette::{DEFAULT_THRESHOLD, PALETTES};
 pub use png::{export_legend_png, export_png};
-pub use svg::export_svg;
+pub use poster::{PAGE_SIZES, PageSize, PosterOptions, export_poster};
+pub use relief::{export_ambient_occlusion, export_normal_map};
+pub use render::{Projection, RenderMode, RenderOptions, RenderStyle};
+pub use stl::{StlRegion, export_stl};
+pub use svg::{SvgMode, SvgOptions, export_svg};
+pub use theme::{THEMES, Theme};
+pub use tiled::{TiledOptions, export_tiled};
+pub use tiles::{TileOptions, TileRenderer, TileScheme, export_tiles, viewer_html};
@@ -74,6 +74,7 @@ fn save_map(data: &[f32], width: u32, height: u32, path: &str, colorize: impl Fn
 /// Re-samples all intermediate noise maps and writes them as false-colour PNGs
 /// into `dir/`.  The generation parameters must match the ones used in
 /// `generate_world` so the maps correspond to the actual world output.
+#[allow(clippy::too_many_arguments)]
 pub fn export_noise_maps(
     width: i32,
     height: i32,
@@ -0,0 +1,321 @@
+/// Map furniture for `world.png` and `world.svg`: a labelled graticule, a
+/// scale bar and a north arrow.
+///
+/// Everything is laid out here, in pixel coordinates of the rendered map and
+/// through its [`Projection`], so the PNG and SVG writers only draw it.  The
+/// graticule is labelled along the meridian and parallel through
+/// [`Projection::reference`], and the scale bar and north arrow are measured
+/// there too: on the equirectangular map, which is south-up, the arrow points
+/// down.
+use super::font;
+use super::geo::sphere_radius_m;
+use super::projection::Projection;
+use crate::world::World;
+
+/// Size of label text in pixels per em.
+pub const LABEL_SIZE: f64 = 11.0;
+/// Distance of the scale bar and north arrow panels from the map edge.
+const MARGIN: f64 = 10.0;
+/// Space between a panel's edge and its contents.
+const PADDING: f64 = 6.0;
+/// Height of the scale bar.
+const BAR_HEIGHT: f64 = 6.0;
+/// Longest scale bar, as a share of the map width.
+const BAR_MAX_SHARE: f64 = 0.2;
+/// Side of the square north arrow panel.
+const ARROW_PANEL: f64 = 48.0;
+/// Angular step, in radians, the local scale and north are measured over.
+const PROBE: f64 = 1e-3;
+
+/// Text placed with its top-left corner at `(x, y)`.
+pub struct Label {
+    pub text: String,
+    pub x: f64,
+    pub y: f64,
+}
+
+impl Label {
+    /// Width of the text in pixels.
+    pub fn width(&self) -> f64 {
+        font::text_width(&self.text, LABEL_SIZE)
+    }
+}
+
+/// Height of a line of label text in pixels.
+pub fn label_height() -> f64 {
+    font::line_height(LABEL_SIZE)
+}
+
+/// Meridians and parallels every so many degrees, with their labels.
+pub struct Graticule {
+    pub lines: Vec<Vec<(f64, f64)>>,
+    pub labels: Vec<Label>,
+}
+
+/// A bar of alternating ink and paper segments standing for a round distance.
+pub struct ScaleBar {
+    /// Background panel: `[x, y, width, height]`.
+    pub panel: [f64; 4],
+    /// The bar: `[x, y, width, height]`.
+    pub bar: [f64; 4],
+    pub segments: u32,
+    /// `0` and the distance over the bar's ends, and the latitude the scale
+    /// holds at under it.
+    pub labels: Vec<Label>,
+}
+
+/// A needle pointing north, with an `N` at its tip.
+pub struct NorthArrow {
+    /// Background panel: `[x, y, width, height]`.
+    pub panel: [f64; 4],
+    /// Outline of the needle: tip, right wing, notch, left wing.
+    pub needle: [(f64, f64); 4],
+    pub label: Label,
+}
+
+/// Graticule of `world` on `projection` with lines every `spacing` degrees.
+pub fn graticule(world: &World, projection: Projection, spacing: f64) -> Graticule {
+    let (w, h) = projection.size(world);
+    let (lon0, lat0) = projection.reference();
+    let mut lines = Vec::new();
+    let mut labels = Vec::new();
+
+    let n = (90.0 / spacing).ceil() as i32;
+    for k in -n..=n {
+        let lat = k as f64 * spacing;
+        if lat.abs() >= 90.0 {
+            continue;
+        }
+        lines.extend(projection.parallel(world, lat));
+        if let Some((x, y)) = projection.point(world, lon0, lat) {
+            labels.push(place(
+                latitude(lat),
+                x + 3.0,
+                y - label_height() - 2.0,
+                w,
+                h,
+            ));
+        }
+    }
+    let mut k = 0;
+    while (k as f64) * spacing < 360.0 - 1e-9 {
+        let lon = k as f64 * spacing;
+        lines.extend(projection.meridian(world, lon));
+        if let Some((x, y)) = projection.point(world, lon, lat0) {
+            labels.push(place(longitude(lon), x + 3.0, y + 3.0, w, h));
+        }
+        k += 1;
+    }
+    Graticule { lines, labels }
+}
+
+/// Scale bar in the bottom-left corner, true at [`Projection::reference`].
+pub fn scale_bar(world: &World, projection: Projection) -> Option<ScaleBar> {
+    let (w, h) = projection.size(world);
+    let (lon0, lat0) = projection.reference();
+    let west = projection.point(world, lon0 - PROBE.to_degrees(), lat0)?;
+    let east = projection.point(world, lon0 + PROBE.to_degrees(), lat0)?;
+    let probe_km = 2.0 * PROBE * sphere_radius_m(world) * lat0.to_radians().cos() / 1000.0;
+    let km_per_px = probe_km / (east.0 - west.0).hypot(east.1 - west.1);
+
+    // The longest 1, 2 or 5 × 10ⁿ km that fits.
+    let longest = w as f64 * BAR_MAX_SHARE * km_per_px;
+    let power = 10f64.powf(longest.log10().floor());
+    let lead = [5.0, 2.0, 1.0]
+        .into_iter()
+        .find(|&m| m * power <= longest)
+        .unwrap_or(1.0);
+    let km = lead * power;
+    let length = km / km_per_px;
+
+    let start = Label {
+        text: "0".into(),
+        x: 0.0,
+        y: 0.0,
+    };
+    let end = Label {
+        text: format!("{} km", km.round()),
+        x: 0.0,
+        y: 0.0,
+    };
+    let caption = Label {
+        text: format!("scale at {}", latitude(lat0.round())),
+        x: 0.0,
+        y: 0.0,
+    };
+    let left = start.width() / 2.0;
+    let width = (left + length + end.width() / 2.0).max(caption.width()) + 2.0 * PADDING;
+    let text_h = label_height();
+    let height = 2.0 * PADDING + text_h + 3.0 + BAR_HEIGHT + 4.0 + text_h;
+    let (px, py) = (MARGIN, h as f64 - MARGIN - height);
+    let (bx, by) = (px + PADDING + left, py + PADDING + text_h + 3.0);
+    let labels = vec![
+        Label {
+            x: bx - start.width() / 2.0,
+            y: py + PADDING,
+            ..start
+        },
+        Label {
+            x: bx + length - end.width() / 2.0,
+            y: py + PADDING,
+            ..end
+        },
+        Label {
+            x: px + PADDING,
+            y: by + BAR_HEIGHT + 4.0,
+            ..caption
+        },
+    ];
+    Some(ScaleBar {
+        panel: [px, py, width, height],
+        bar: [bx, by, length, BAR_HEIGHT],
+        segments: if lead == 5.0 { 5 } else { 4 },
+        labels,
+    })
+}
+
+/// North arrow in the top-right corner, pointing north at
+/// [`Projection::reference`].
+pub fn north_arrow(world: &World, projection: Projection) -> Option<NorthArrow> {
+    let (w, _) = projection.size(world);
+    let (lon0, lat0) = projection.reference();
+    let here = projection.point(world, lon0, lat0)?;
+    let ahead = projection.point(world, lon0, lat0 + PROBE.to_degrees())?;
+    let (dx, dy) = (ahead.0 - here.0, ahead.1 - here.1);
+    let len = dx.hypot(dy);
+    let (dx, dy) = (dx / len, dy / len);
+    let (nx, ny) = (-dy, dx);
+
+    let panel = [
+        w as f64 - MARGIN - ARROW_PANEL,
+        MARGIN,
+        ARROW_PANEL,
+        ARROW_PANEL,
+    ];
+    let (cx, cy) = (panel[0] + ARROW_PANEL / 2.0, panel[1] + ARROW_PANEL / 2.0);
+    let at =
+        |along: f64, across: f64| (cx + along * dx + across * nx, cy + along * dy + across * ny);
+    let (lx, ly) = at(16.0, 0.0);
+    let label = Label {
+        text: "N".into(),
+        x: 0.0,
+        y: 0.0,
+    };
+    Some(NorthArrow {
+        panel,
+        needle: [at(8.0, 0.0), at(-10.0, 5.0), at(-6.0, 0.0), at(-10.0, -5.0)],
+        label: Label {
+            x: lx - label.width() / 2.0,
+            y: ly - label_height() / 2.0,
+            ..label
+        },
+    })
+}
+
+/// `text` at `(x, y)`, moved inside a `w` × `h` map if it sticks out.
+fn place(text: String, x: f64, y: f64, w: u32, h: u32) -> Label {
+    let width = font::text_width(&text, LABEL_SIZE);
+    Label {
+        x: x.clamp(2.0, (w as f64 - width - 2.0).max(2.0)),
+        y: y.clamp(2.0, (h as f64 - label_height() - 2.0).max(2.0)),
+        text,
+    }
+}
+
+/// `30°N`, `0°`, `45°S`.
+fn latitude(lat: f64) -> String {
+    match lat {
+        0.0 => "0°".into(),
+        _ if lat > 0.0 => format!("{}N", degrees(lat)),
+        _ => format!("{}S", degrees(-lat)),
+    }
+}
+
+/// Grid longitude (`0..360` east) as `90°E`, `180°`, `30°W`.
+fn longitude(lon: f64) -> String {
+    let lon = lon.rem_euclid(360.0);
+    match lon {
+        0.0 | 180.0 => degrees(lon),
+        _ if lon < 180.0 => format!("{}E", degrees(lon)),
+        _ => format!("{}W", degrees(360.0 - lon)),
+    }
+}
+
+fn degrees(v: f64) -> String {
+    if v.fract() == 0.0 {
+        format!("{v:.0}°")
+    } else {
+        format!("{v:.1}°")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generation::test_world;
+
+    fn inside(label: &Label, (w, h): (u32, u32)) -> bool {
+        label.x >= 0.0
+            && label.y >= 0.0
+            && label.x + label.width() <= w as f64
+            && label.y + label_height() <= h as f64
+    }
+
+    #[test]
+    fn labels_read_as_degrees() {
+        assert_eq!(latitude(0.0), "0°");
+        assert_eq!(latitude(30.0), "30°N");
+        assert_eq!(latitude(-12.5), "12.5°S");
+        assert_eq!(longitude(0.0), "0°");
+        assert_eq!(longitude(90.0), "90°E");
+        assert_eq!(longitude(180.0), "180°");
+        assert_eq!(longitude(270.0), "90°W");
+        assert_eq!(longitude(-30.0), "30°W");
+        assert_eq!(longitude(360.0), "0°");
+    }
+
+    #[test]
+    fn graticule_labels_every_line_inside_the_map() {
+        let world = test_world();
+        for projection in [Projection::Equirectangular, Projection::Mollweide] {
+            let size = projection.size(&world);
+            let graticule = graticule(&world, projection, 30.0);
+            let texts: Vec<&str> = graticule.labels.iter().map(|l| l.text.as_str()).collect();
+            // Five parallels and twelve meridians; 0° names both the equator
+            // and the prime meridian.
+            assert_eq!(texts.len(), 17, "{projection:?}: {texts:?}");
+            for text in ["60°S", "30°N", "150°E", "180°", "30°W"] {
+                assert!(texts.contains(&text), "{projection:?}: no {text}");
+            }
+            assert_eq!(texts.iter().filter(|&&t| t == "0°").count(), 2);
+            assert!(graticule.labels.iter().all(|l| inside(l, size)));
+        }
+    }
+
+    #[test]
+    fn scale_bar_shows_a_round_distance_and_the_arrow_points_north() {
+        let world = test_world();
+        for projection in [Projection::Equirectangular, Projection::Mollweide] {
+            let (w, h) = projection.size(&world);
+            let bar = scale_bar(&world, projection).unwrap();
+            let km: f64 = bar.labels[1].text.trim_end_matches(" km").parse().unwrap();
+            let lead = km / 10f64.powf(km.log10().floor());
+            assert!(
+                [1.0, 2.0, 5.0].iter().any(|m| (lead - m).abs() < 1e-9),
+                "{km} km"
+            );
+            assert!(bar.bar[2] <= w as f64 * BAR_MAX_SHARE);
+            assert_eq!(bar.labels[2].text, "scale at 0°");
+            let [x, y, pw, ph] = bar.panel;
+            assert!(x > 0.0 && y > 0.0 && x + pw < w as f64 && y + ph < h as f64);
+
+            // The tip is the needle's first point; the equirectangular map
+            // is south-up.
+            let arrow = north_arrow(&world, projection).unwrap();
+            let centre_y = arrow.panel[1] + arrow.panel[3] / 2.0;
+            let points_down = arrow.needle[0].1 > centre_y;
+            assert_eq!(points_down, projection == Projection::Equirectangular);
+            assert_eq!(arrow.label.text, "N");
+        }
+    }
+}
@@ -0,0 +1,378 @@
+/// Colour-blind safe palettes, and a checker for how well a palette keeps
+/// biomes apart under colour-vision deficiencies.
+///
+/// Deficiencies are simulated with the Machado, Oliveira & Fernandes (2009)
+/// matrices at full severity, applied in linear RGB.  Colours are compared by
+/// their CIEDE2000 difference (ΔE₀₀) in CIELAB under D65: about 1 is the
+/// smallest difference anyone sees side by side, and adjacent map regions
+/// need several times that to tell apart at a glance.
+///
+/// The alternative palettes keep every pair of biomes that can share a map at
+/// least [`DEFAULT_THRESHOLD`] apart for normal vision and for the
+/// deficiencies they are named after.
+use super::theme::Theme;
+use crate::world::*;
+use std::collections::HashMap;
+
+/// Smallest ΔE₀₀ between two biome colours [`check`] accepts by default.
+pub const DEFAULT_THRESHOLD: f32 = 8.0;
+
+/// Names of the built-in palettes, for messages.
+pub const PALETTES: [&str; 3] = ["standard", "red-green", "blue-yellow"];
+
+/// Colour vision a palette is checked against.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Vision {
+    Normal,
+    /// No long-wavelength (red) cones.
+    Protanopia,
+    /// No medium-wavelength (green) cones; the most common deficiency.
+    Deuteranopia,
+    /// No short-wavelength (blue) cones.
+    Tritanopia,
+}
+
+pub const VISIONS: [Vision; 4] = [
+    Vision::Normal,
+    Vision::Protanopia,
+    Vision::Deuteranopia,
+    Vision::Tritanopia,
+];
+
+impl std::fmt::Display for Vision {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Vision::Normal => "normal vision",
+            Vision::Protanopia => "protanopia",
+            Vision::Deuteranopia => "deuteranopia",
+            Vision::Tritanopia => "tritanopia",
+        })
+    }
+}
+
+/// Two biomes whose colours are closer than the threshold for one vision.
+#[derive(Clone, Copy, Debug)]
+pub struct Clash {
+    pub vision: Vision,
+    pub a: Biome,
+    pub b: Biome,
+    /// ΔE₀₀ between the two colours as seen with `vision`.
+    pub delta_e: f32,
+}
+
+/// Biome colours of the palette called `name`; `standard` is empty, leaving
+/// the canonical [`biome_color`]s.
+pub fn palette(name: &str) -> Option<HashMap<Biome, [u8; 3]>> {
+    match name {
+        "standard" => Some(HashMap::new()),
+        "red-green" => Some(HashMap::from(RED_GREEN)),
+        "blue-yellow" => Some(HashMap::from(BLUE_YELLOW)),
+        _ => None,
+    }
+}
+
+/// Every pair of biomes that can appear on the same map whose colours under
+/// `theme` ([`Theme::region_color`]) are less than `threshold` ΔE₀₀ apart,
+/// for each of [`VISIONS`], closest first within each vision.
+pub fn check(theme: &Theme, threshold: f32) -> Vec<Clash> {
+    let mut clashes = Vec::new();
+    for vision in VISIONS {
+        let seen: Vec<[u8; 3]> = BIOMES
+            .iter()
+            .map(|&b| simulate(theme.region_color(b), vision))
+            .collect();
+        let start = clashes.len();
+        for i in 0..BIOMES.len() {
+            for j in i + 1..BIOMES.len() {
+                let (a, b) = (BIOMES[i], BIOMES[j]);
+                if !can_meet(a, b) {
+                    continue;
+                }
+                let delta_e = delta_e(seen[i], seen[j]);
+                if delta_e < threshold {
+                    clashes.push(Clash {
+                        vision,
+                        a,
+                        b,
+                        delta_e,
+                    });
+                }
+            }
+        }
+        clashes[start..].sort_by(|x, y| x.delta_e.total_cmp(&y.delta_e));
+    }
+    clashes
+}
+
+/// Whether `a` and `b` can appear on one map: biomes exclusive to different
+/// planet types never do.
+fn can_meet(a: Biome, b: Biome) -> bool {
+    match (exclusive_to(a), exclusive_to(b)) {
+        (Some(pa), Some(pb)) => pa == pb,
+        _ => true,
+    }
+}
+
+/// The planet type `b` only occurs on, if any.
+fn exclusive_to(b: Biome) -> Option<PlanetType> {
+    match b {
+        Biome::MagmaSea | Biome::ScorchedWaste => Some(PlanetType::Volcanic),
+        Biome::FrozenOcean | Biome::GlacialPlain => Some(PlanetType::Frozen),
+        Biome::CausticLake | Biome::ToxicSwamp | Biome::AcidFlatland => Some(PlanetType::Caustic),
+        Biome::RockyWaste | Biome::DustPlain => Some(PlanetType::Barren),
+        _ => None,
+    }
+}
+
+/// `color` as it looks with `vision`.
+pub fn simulate(color: [u8; 3], vision: Vision) -> [u8; 3] {
+    let m = match vision {
+        Vision::Normal => return color,
+        Vision::Protanopia => PROTANOPIA,
+        Vision::Deuteranopia => DEUTERANOPIA,
+        Vision::Tritanopia => TRITANOPIA,
+    };
+    let c = color.map(to_linear);
+    std::array::from_fn(|i| from_linear(m[i][0] * c[0] + m[i][1] * c[1] + m[i][2] * c[2]))
+}
+
+/// CIEDE2000 colour difference between two sRGB colours.
+pub fn delta_e(c1: [u8; 3], c2: [u8; 3]) -> f32 {
+    let ([l1, a1, b1], [l2, a2, b2]) = (lab(c1), lab(c2));
+    let c_mean = (a1.hypot(b1) + a2.hypot(b2)) / 2.0;
+    let g = 0.5 * (1.0 - (c_mean.powi(7) / (c_mean.powi(7) + 25f64.powi(7))).sqrt());
+    let (a1, a2) = (a1 * (1.0 + g), a2 * (1.0 + g));
+    let (c1, c2) = (a1.hypot(b1), a2.hypot(b2));
+    let hue = |a: f64, b: f64| {
+        if a == 0.0 && b == 0.0 {
+            0.0
+        } else {
+            b.atan2(a).to_degrees().rem_euclid(360.0)
+        }
+    };
+    let (h1, h2) = (hue(a1, b1), hue(a2, b2));
+
+    let dl = l2 - l1;
+    let dc = c2 - c1;
+    let dh = match h2 - h1 {
+        _ if c1 * c2 == 0.0 => 0.0,
+        d if d > 180.0 => d - 360.0,
+        d if d < -180.0 => d + 360.0,
+        d => d,
+    };
+    let dh = 2.0 * (c1 * c2).sqrt() * (dh / 2.0).to_radians().sin();
+
+    let l_mean = (l1 + l2) / 2.0;
+    let c_mean = (c1 + c2) / 2.0;
+    let h_mean = if c1 * c2 == 0.0 {
+        h1 + h2
+    } else if (h1 - h2).abs() <= 180.0 {
+        (h1 + h2) / 2.0
+    } else if h1 + h2 < 360.0 {
+        (h1 + h2 + 360.0) / 2.0
+    } else {
+        (h1 + h2 - 360.0) / 2.0
+    };
+    let cos = |deg: f64| deg.to_radians().cos();
+    let t =
+        1.0 - 0.17 * cos(h_mean - 30.0) + 0.24 * cos(2.0 * h_mean) + 0.32 * cos(3.0 * h_mean + 6.0)
+            - 0.20 * cos(4.0 * h_mean - 63.0);
+    let rotation = 30.0 * (-((h_mean - 275.0) / 25.0).powi(2)).exp();
+    let rc = 2.0 * (c_mean.powi(7) / (c_mean.powi(7) + 25f64.powi(7))).sqrt();
+    let sl = 1.0 + 0.015 * (l_mean - 50.0).powi(2) / (20.0 + (l_mean - 50.0).powi(2)).sqrt();
+    let sc = 1.0 + 0.045 * c_mean;
+    let sh = 1.0 + 0.015 * c_mean * t;
+    let rt = -(2.0 * rotation).to_radians().sin() * rc;
+    let (dl, dc, dh) = (dl / sl, dc / sc, dh / sh);
+    (dl * dl + dc * dc + dh * dh + rt * dc * dh).sqrt() as f32
+}
+
+/// CIELAB coordinates of an sRGB colour, D65 white.
+fn lab(color: [u8; 3]) -> [f64; 3] {
+    let [r, g, b] = color.map(to_linear);
+    let x = (0.4124 * r + 0.3576 * g + 0.1805 * b) / 0.95047;
+    let y = 0.2126 * r + 0.7152 * g + 0.0722 * b;
+    let z = (0.0193 * r + 0.1192 * g + 0.9505 * b) / 1.08883;
+    let f = |t: f64| {
+        if t > 0.008856 {
+            t.cbrt()
+        } else {
+            7.787 * t + 16.0 / 116.0
+        }
+    };
+    let (fx, fy, fz) = (f(x), f(y), f(z));
+    [116.0 * fy - 16.0, 500.0 * (fx - fy), 200.0 * (fy - fz)]
+}
+
+fn to_linear(c: u8) -> f64 {
+    let c = c as f64 / 255.0;
+    if c <= 0.04045 {
+        c / 12.92
+    } else {
+        ((c + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+fn from_linear(c: f64) -> u8 {
+    let c = c.clamp(0.0, 1.0);
+    let c = if c <= 0.0031308 {
+        12.92 * c
+    } else {
+        1.055 * c.powf(1.0 / 2.4) - 0.055
+    };
+    (c * 255.0).round() as u8
+}
+
+// ── Simulation matrices (Machado et al. 2009, severity 1.0) ───────────────────
+
+const PROTANOPIA: [[f64; 3]; 3] = [
+    [0.152286, 1.052583, -0.204868],
+    [0.114503, 0.786281, 0.099216],
+    [-0.003882, -0.048116, 1.051998],
+];
+const DEUTERANOPIA: [[f64; 3]; 3] = [
+    [0.367322, 0.860646, -0.227968],
+    [0.280085, 0.672501, 0.047413],
+    [-0.011820, 0.042940, 0.968881],
+];
+const TRITANOPIA: [[f64; 3]; 3] = [
+    [1.255528, -0.076749, -0.178779],
+    [-0.078411, 0.930809, 0.147602],
+    [0.004733, 0.691367, 0.303900],
+];
+
+// ── Palettes ──────────────────────────────────────────────────────────────────
+// Tuned from the standard colours: each biome keeps its family (blue water,
+// green forest, white snow) and moves only as far as the threshold needs.
+
+/// Safe for protanopia and deuteranopia: greens are split by lightness and
+/// pushed towards yellow or teal, reds towards orange or pink.
+const RED_GREEN: [(Biome, [u8; 3]); 32] = [
+    (Biome::DeepOcean, [1, 21, 140]),
+    (Biome::Ocean, [0, 69, 206]),
+    (Biome::Shelf, [61, 120, 210]),
+    (Biome::Trench, [7, 8, 78]),
+    (Biome::Reef, [72, 196, 181]),
+    (Biome::Beach, [255, 252, 138]),
+    (Biome::Wetland, [146, 195, 143]),
+    (Biome::IceCap, [207, 234, 255]),
+    (Biome::Tundra, [150, 174, 93]),
+    (Biome::Taiga, [10, 61, 44]),
+    (Biome::Shrubland, [175, 187, 34]),
+    (Biome::Plain, [142, 218, 119]),
+    (Biome::Forest, [14, 70, 0]),
+    (Biome::Savanna, [238, 219, 53]),
+    (Biome::Desert, [252, 228, 176]),
+    (Biome::Jungle, [31, 117, 84]),
+    (Biome::Mountain, [132, 84, 6]),
+    (Biome::Snow, [250, 249, 250]),
+    (Biome::Moraine, [118, 139, 33]),
+    (Biome::Fjord, [50, 96, 140]),
+    (Biome::Volcano, [233, 56, 98]),
+    (Biome::LavaField, [244, 74, 0]),
+    (Biome::AshLand, [85, 77, 76]),
+    (Biome::MagmaSea, [141, 68, 56]),
+    (Biome::ScorchedWaste, [55, 24, 6]),
+    (Biome::FrozenOcean, [155, 195, 220]),
+    (Biome::GlacialPlain, [156, 185, 255]),
+    (Biome::CausticLake, [7, 139, 78]),
+    (Biome::ToxicSwamp, [48, 91, 51]),
+    (Biome::AcidFlatland, [134, 162, 130]),
+    (Biome::RockyWaste, [129, 125, 115]),
+    (Biome::DustPlain, [161, 151, 131]),
+];
+
+/// Safe for tritanopia: blues and greens are split by lightness, yellows
+/// pushed towards orange or lime.
+const BLUE_YELLOW: [(Biome, [u8; 3]); 32] = [
+    (Biome::DeepOcean, [1, 21, 143]),
+    (Biome::Ocean, [22, 65, 200]),
+    (Biome::Shelf, [60, 121, 215]),
+    (Biome::Trench, [8, 9, 79]),
+    (Biome::Reef, [25, 214, 203]),
+    (Biome::Beach, [235, 224, 133]),
+    (Biome::Wetland, [90, 139, 78]),
+    (Biome::IceCap, [207, 219, 223]),
+    (Biome::Tundra, [153, 185, 157]),
+    (Biome::Taiga, [30, 92, 61]),
+    (Biome::Shrubland, [163, 169, 82]),
+    (Biome::Plain, [108, 198, 78]),
+    (Biome::Forest, [8, 120, 28]),
+    (Biome::Savanna, [197, 189, 28]),
+    (Biome::Desert, [238, 198, 99]),
+    (Biome::Jungle, [39, 73, 9]),
+    (Biome::Mountain, [126, 113, 102]),
+    (Biome::Snow, [249, 247, 251]),
+    (Biome::Moraine, [149, 139, 124]),
+    (Biome::Fjord, [41, 101, 158]),
+    (Biome::Volcano, [255, 47, 0]),
+    (Biome::LavaField, [194, 86, 21]),
+    (Biome::AshLand, [96, 80, 70]),
+    (Biome::MagmaSea, [179, 24, 0]),
+    (Biome::ScorchedWaste, [69, 36, 15]),
+    (Biome::FrozenOcean, [141, 189, 233]),
+    (Biome::GlacialPlain, [192, 223, 252]),
+    (Biome::CausticLake, [61, 168, 33]),
+    (Biome::ToxicSwamp, [69, 102, 34]),
+    (Biome::AcidFlatland, [171, 199, 71]),
+    (Biome::RockyWaste, [99, 97, 86]),
+    (Biome::DustPlain, [193, 161, 103]),
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Clashes of the classic theme with palette `name` for `vision`.
+    fn clashes(name: &str, vision: Vision) -> Vec<Clash> {
+        let theme = Theme {
+            biome_colors: palette(name).unwrap(),
+            ..Theme::default()
+        };
+        check(&theme, DEFAULT_THRESHOLD)
+            .into_iter()
+            .filter(|c| c.vision == vision)
+            .collect()
+    }
+
+    #[test]
+    fn palettes_pass_for_the_visions_they_are_named_after() {
+        for (name, visions) in [
+            (
+                "red-green",
+                &[Vision::Normal, Vision::Protanopia, Vision::Deuteranopia][..],
+            ),
+            ("blue-yellow", &[Vision::Normal, Vision::Tritanopia][..]),
+        ] {
+            for &vision in visions {
+                let found = clashes(name, vision);
+                assert!(found.is_empty(), "{name} with {vision}: {found:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn standard_palette_clashes_for_red_green_deficiencies() {
+        for vision in [Vision::Protanopia, Vision::Deuteranopia] {
+            let found = clashes("standard", vision);
+            assert!(!found.is_empty(), "no clashes with {vision}");
+            assert!(found.windows(2).all(|w| w[0].delta_e <= w[1].delta_e));
+            assert!(found.iter().all(|c| can_meet(c.a, c.b)));
+        }
+    }
+
+    #[test]
+    fn simulation_keeps_greys_and_normal_vision() {
+        for vision in VISIONS {
+            for v in [0, 64, 128, 255] {
+                let [r, g, b] = simulate([v, v, v], vision);
+                assert!(
+                    r.abs_diff(v) <= 1 && g.abs_diff(v) <= 1 && b.abs_diff(v) <= 1,
+                    "{vision}: {v} → {r},{g},{b}"
+                );
+            }
+        }
+        assert_eq!(simulate([200, 30, 90], Vision::Normal), [200, 30, 90]);
+        assert_eq!(delta_e([10, 20, 30], [10, 20, 30]), 0.0);
+    }
+}
@@ -1,73 +1,51 @@
+use super::font::{self, draw_text};
+use super::overlay::{self, LABEL_SIZE, Label};
+use super::render::{Projection, RenderOptions, mix, tile_colors};
 use crate::world::*;
-use font8x8::UnicodeFonts;
 use image::{Rgb, RgbImage};
 
-// Contour lines are drawn whenever a tile and a neighbour straddle one of these levels.
-const CONTOUR_LEVELS: &[f32] = &[-0.45, -0.15, 0.0, 0.15, 0.30, 0.45, 0.60, 0.75, 0.90];
-// Fraction to darken a pixel by when it sits on a contour line (0.0 = no change, 1.0 = black).
-const CONTOUR_DARKNESS: f32 = 0.40;
+/// Reference parallels overlaid on the map: latitude in degrees and colour.
+pub const REFERENCE_PARALLELS: &[(f64, [u8; 3])] = &[
+    (0.0, [220, 50, 50]),   // equator — red
+    (23.5, [220, 150, 0]),  // Tropic of Cancer — amber
+    (-23.5, [220, 150, 0]), // Tropic of Capricorn — amber
+    (66.5, [0, 200, 240]),  // Arctic Circle — cyan
+    (-66.5, [0, 200, 240]), // Antarctic Circle — cyan
+];
+// Dash pattern: 6 px on, 4 px off
+pub const DASH_ON: u32 = 6;
+const DASH_OFF: u32 = 4;
+pub const PERIOD: u32 = DASH_ON + DASH_OFF;
+/// Colour of the area around a projected map.
+pub const BACKGROUND: [u8; 3] = [16, 16, 24];
+/// How far graticule lines pull the map towards the theme's ink.
+const GRATICULE_INK: f32 = 0.45;
+/// How far the scale bar and north arrow panels cover the map with paper.
+const PANEL_PAPER: f32 = 0.8;
+
+pub fn export_png(world: &World, options: &RenderOptions, path: &str) {
+    render_png(world, options).save(path).unwrap();
+}
 
-pub fn export_png(world: &World, path: &str) {
+/// The image `world.png` holds.
+pub fn render_png(world: &World, options: &RenderOptions) -> RgbImage {
+    if options.projection != Projection::Equirectangular {
+        return render_projected_png(world, options);
+    }
     let w = world.width as u32;
     let h = world.height as u32;
     let mut img = RgbImage::new(w, h);
 
-    // Build a fast elevation lookup indexed by [q * height + r].
-    let mut elevation: Vec<f32> = vec![0.0; (w * h) as usize];
-    for tile in &world.tiles {
-        elevation[(tile.q as u32 * h + tile.r as u32) as usize] = tile.elevation;
-    }
-
-    let elev_at = |q: i32, r: i32| -> Option<f32> {
-        if q < 0 || r < 0 || q >= world.width || r >= world.height {
-            return None;
-        }
-        Some(elevation[(q as u32 * h + r as u32) as usize])
-    };
-
-    // Returns true if the edge between elevations `a` and `b` crosses any contour level.
-    let crosses_contour =
-        |a: f32, b: f32| -> bool { CONTOUR_LEVELS.iter().any(|&lvl| (a < lvl) != (b < lvl)) };
-
+    let colors = tile_colors(world, options);
     for tile in &world.tiles {
-        let mut color = biome_color(tile.biome);
-        let e = tile.elevation;
-
-        // Check the 4-connected neighbours.
-        let is_contour = [
-            elev_at(tile.q - 1, tile.r),
-            elev_at(tile.q + 1, tile.r),
-            elev_at(tile.q, tile.r - 1),
-            elev_at(tile.q, tile.r + 1),
-        ]
-        .iter()
-        .filter_map(|n| *n)
-        .any(|ne| crosses_contour(e, ne));
-
-        if is_contour {
-            // Darken the biome colour proportionally.
-            color = color.map(|c| (c as f32 * (1.0 - CONTOUR_DARKNESS)) as u8);
-        }
-
+        let color = colors[(tile.q as u32 * h + tile.r as u32) as usize];
         img.put_pixel(tile.q as u32, tile.r as u32, Rgb(color));
     }
 
     // ── Overlay equator and tropic reference lines (dotted) ───────────────────
     // Latitude → row: r = height * (0.5 + lat_deg / 180)
-    let line_rows: &[(f64, [u8; 3])] = &[
-        (h as f64 * 0.5, [220, 50, 50]),                  // equator — red
-        (h as f64 * (0.5 + 23.5 / 180.0), [220, 150, 0]), // Tropic of Cancer — amber
-        (h as f64 * (0.5 - 23.5 / 180.0), [220, 150, 0]), // Tropic of Capricorn — amber
-        (h as f64 * (0.5 + 66.5 / 180.0), [0, 200, 240]), // Arctic Circle — cyan
-        (h as f64 * (0.5 - 66.5 / 180.0), [0, 200, 240]), // Antarctic Circle — cyan
-    ];
-    // Dash pattern: 6 px on, 4 px off
-    const DASH_ON: u32 = 6;
-    const DASH_OFF: u32 = 4;
-    const PERIOD: u32 = DASH_ON + DASH_OFF;
-
-    for &(row_f, color) in line_rows {
-        let row = row_f.round() as u32;
+    for &(lat, color) in REFERENCE_PARALLELS {
+        let row = (h as f64 * (0.5 + lat / 180.0)).round() as u32;
         if row >= h {
             continue;
         }
@@ -78,49 +56,174 @@ pub fn export_png(world: &World, path: &str) {
         }
     }
 
-    img.save(path).unwrap();
+    draw_overlay(&mut img, world, options);
+    img
 }
 
-// ── Legend PNG ────────────────────────────────────────────────────────────────
+/// `world.png` in another projection: the tile colours resampled through
+/// `options.projection`, with the reference parallels reprojected.
+fn render_projected_png(world: &World, options: &RenderOptions) -> RgbImage {
+    let projection = options.projection;
+    let (w, h) = projection.size(world);
+    let raster = projection.raster(world, &tile_colors(world, options));
+    let mut img = RgbImage::from_pixel(w, h, Rgb(BACKGROUND));
+    for (i, color) in raster.into_iter().enumerate() {
+        if let Some(color) = color {
+            img.put_pixel(i as u32 % w, i as u32 / w, Rgb(color));
+        }
+    }
 
-/// Scale factor for the bitmap font (each logical pixel becomes `SCALE` screen pixels).
-const FONT_SCALE: u32 = 2;
-/// Width of one character in screen pixels.
-const CHAR_W: u32 = 8 * FONT_SCALE;
-/// Height of one character in screen pixels.
-const CHAR_H: u32 = 8 * FONT_SCALE;
-
-/// Draw a single character at (x, y) using the 8×8 bitmap font.
-fn draw_char(img: &mut RgbImage, c: char, x: u32, y: u32, color: [u8; 3]) {
-    let Some(glyph) = font8x8::BASIC_FONTS.get(c) else {
-        return;
-    };
-    for (row, &byte) in glyph.iter().enumerate() {
-        for col in 0u32..8 {
-            if byte & (1 << col) != 0 {
-                for dy in 0..FONT_SCALE {
-                    for dx in 0..FONT_SCALE {
-                        let px = x + col * FONT_SCALE + dx;
-                        let py = y + row as u32 * FONT_SCALE + dy;
-                        if px < img.width() && py < img.height() {
-                            img.put_pixel(px, py, Rgb(color));
-                        }
-                    }
+    // Dashes follow the curve.
+    for &(lat, color) in REFERENCE_PARALLELS {
+        for line in projection.parallel(world, lat) {
+            walk(&line, |travelled, x, y| {
+                let on = travelled % (PERIOD as f64) < DASH_ON as f64;
+                if on && x >= 0.0 && y >= 0.0 && x < w as f64 && y < h as f64 {
+                    img.put_pixel(x as u32, y as u32, Rgb(color));
                 }
-            }
+            });
         }
     }
+
+    draw_overlay(&mut img, world, options);
+    img
 }
 
-/// Draw a string starting at (x, y).
-fn draw_str(img: &mut RgbImage, s: &str, x: u32, y: u32, color: [u8; 3]) {
-    for (i, c) in s.chars().enumerate() {
-        draw_char(img, c, x + i as u32 * CHAR_W, y, color);
+/// Walks a polyline in half-pixel steps, calling `visit` with the distance
+/// travelled and the position.
+fn walk(line: &[(f64, f64)], mut visit: impl FnMut(f64, f64, f64)) {
+    let mut travelled = 0.0;
+    for segment in line.windows(2) {
+        let ((x0, y0), (x1, y1)) = (segment[0], segment[1]);
+        let length = (x1 - x0).hypot(y1 - y0);
+        let steps = (length * 2.0).ceil().max(1.0) as u32;
+        for k in 0..steps {
+            let t = k as f64 / steps as f64;
+            visit(
+                travelled + t * length,
+                x0 + t * (x1 - x0),
+                y0 + t * (y1 - y0),
+            );
+        }
+        travelled += length;
     }
 }
 
+// ── Map furniture ─────────────────────────────────────────────────────────────
+
+/// Draws the graticule, scale bar and north arrow `options` asks for, in the
+/// theme's ink on its paper.
+fn draw_overlay(img: &mut RgbImage, world: &World, options: &RenderOptions) {
+    let (ink, paper) = (options.theme.ink, options.theme.paper);
+    let (w, h) = img.dimensions();
+
+    if let Some(spacing) = options.graticule {
+        let graticule = overlay::graticule(world, options.projection, spacing);
+        // Mark the pixels first so crossings are not inked twice.
+        let mut on = vec![false; (w * h) as usize];
+        for line in &graticule.lines {
+            walk(line, |_, x, y| {
+                if x >= 0.0 && y >= 0.0 && x < w as f64 && y < h as f64 {
+                    on[(y as u32 * w + x as u32) as usize] = true;
+                }
+            });
+        }
+        for i in (0..on.len()).filter(|&i| on[i]) {
+            let (x, y) = (i as u32 % w, i as u32 / w);
+            let pixel = img.get_pixel(x, y).0;
+            img.put_pixel(x, y, Rgb(mix(pixel, ink, GRATICULE_INK)));
+        }
+        for label in &graticule.labels {
+            draw_label(img, label, ink, paper);
+        }
+    }
+
+    if options.scale_bar
+        && let Some(scale) = overlay::scale_bar(world, options.projection)
+    {
+        draw_panel(img, scale.panel, ink, paper);
+        let [x, y, length, height] = scale.bar;
+        for k in 0..scale.segments {
+            let x0 = x + length * k as f64 / scale.segments as f64;
+            let x1 = x + length * (k + 1) as f64 / scale.segments as f64;
+            let color = if k % 2 == 0 { ink } else { paper };
+            let (x0, x1) = (x0.round() as u32, x1.round() as u32);
+            fill_rect(img, x0, y as u32, x1 - x0, height as u32, color);
+        }
+        outline_rect(
+            img,
+            x.round() as u32,
+            y as u32,
+            length.round() as u32 + 1,
+            height as u32,
+            ink,
+        );
+        for label in &scale.labels {
+            draw_text(img, &label.text, label.x, label.y, LABEL_SIZE, ink);
+        }
+    }
+
+    if options.north_arrow
+        && let Some(arrow) = overlay::north_arrow(world, options.projection)
+    {
+        draw_panel(img, arrow.panel, ink, paper);
+        fill_polygon(img, &arrow.needle, ink);
+        let label = &arrow.label;
+        draw_text(img, &label.text, label.x, label.y, LABEL_SIZE, ink);
+    }
+}
+
+/// A label in ink, ringed with paper so it reads over any colour.
+fn draw_label(img: &mut RgbImage, label: &Label, ink: [u8; 3], paper: [u8; 3]) {
+    let (x, y) = (label.x, label.y);
+    for (dx, dy) in [(-1.0, 0.0), (1.0, 0.0), (0.0, -1.0), (0.0, 1.0)] {
+        draw_text(img, &label.text, x + dx, y + dy, LABEL_SIZE, paper);
+    }
+    draw_text(img, &label.text, x, y, LABEL_SIZE, ink);
+}
+
+/// Paper laid over the map in `[x, y, width, height]`, with an ink border.
+fn draw_panel(img: &mut RgbImage, [x, y, w, h]: [f64; 4], ink: [u8; 3], paper: [u8; 3]) {
+    let (x, y, w, h) = (x as u32, y as u32, w as u32, h as u32);
+    for py in y..(y + h).min(img.height()) {
+        for px in x..(x + w).min(img.width()) {
+            let pixel = img.get_pixel(px, py).0;
+            img.put_pixel(px, py, Rgb(mix(pixel, paper, PANEL_PAPER)));
+        }
+    }
+    outline_rect(img, x, y, w, h, ink);
+}
+
+/// Fills the polygon `points` (even-odd rule) with `color`.
+fn fill_polygon(img: &mut RgbImage, points: &[(f64, f64)], color: [u8; 3]) {
+    let x0 = points.iter().map(|p| p.0).fold(f64::MAX, f64::min).max(0.0) as u32;
+    let x1 = points.iter().map(|p| p.0).fold(f64::MIN, f64::max).ceil() as u32;
+    let y0 = points.iter().map(|p| p.1).fold(f64::MAX, f64::min).max(0.0) as u32;
+    let y1 = points.iter().map(|p| p.1).fold(f64::MIN, f64::max).ceil() as u32;
+    for py in y0..=y1.min(img.height() - 1) {
+        for px in x0..=x1.min(img.width() - 1) {
+            let (x, y) = (px as f64 + 0.5, py as f64 + 0.5);
+            let mut inside = false;
+            for (k, &(ax, ay)) in points.iter().enumerate() {
+                let (bx, by) = points[(k + 1) % points.len()];
+                if (ay > y) != (by > y) && x < ax + (y - ay) / (by - ay) * (bx - ax) {
+                    inside = !inside;
+                }
+            }
+            if inside {
+                img.put_pixel(px, py, Rgb(color));
+            }
+        }
+    }
+}
+
+// ── Legend PNG ────────────────────────────────────────────────────────────────
+
+/// Size of the legend's text in pixels per em.
+const TEXT_SIZE: f64 = 14.0;
+
 /// Fill a rectangular area with `color`.
-fn fill_rect(img: &mut RgbImage, x: u32, y: u32, w: u32, h: u32, color: [u8; 3]) {
+pub fn fill_rect(img: &mut RgbImage, x: u32, y: u32, w: u32, h: u32, color: [u8; 3]) {
     for dy in 0..h {
         for dx in 0..w {
             let px = x + dx;
@@ -133,7 +236,7 @@ fn fill_rect(img: &mut RgbImage, x: u32, y: u32, w: u32, h: u32, color: [u8; 3])
 }
 
 /// Draw a 1-pixel border around a rectangle.
-fn outline_rect(img: &mut RgbImage, x: u32, y: u32, w: u32, h: u32, color: [u8; 3]) {
+pub fn outline_rect(img: &mut RgbImage, x: u32, y: u32, w: u32, h: u32, color: [u8; 3]) {
     for dx in 0..w {
         img.put_pixel(x + dx, y, Rgb(color));
         img.put_pixel(x + dx, y + h - 1, Rgb(color));
@@ -144,9 +247,8 @@ fn outline_rect(img: &mut RgbImage, x: u32, y: u32, w: u32, h: u32, color: [u8;
     }
 }
 
-/// Generate a legend PNG listing every biome that actually appears on the map.
-pub fn export_legend_png(world: &World, path: &str) {
-    // ── Collect biomes present on this map, in canonical order ────────────────
+/// Biomes that appear on the map, in legend order.
+pub fn legend_biomes(world: &World) -> Vec<Biome> {
     let mut seen = std::collections::HashSet::new();
     let mut biomes: Vec<Biome> = Vec::new();
     for tile in &world.tiles {
@@ -155,8 +257,11 @@ pub fn export_legend_png(world: &World, path: &str) {
         }
     }
     biomes.sort_by_key(|&b| biome_order(b));
+    biomes
+}
 
-    // ── Planet metadata lines (key, value) ───────────────────────────────────
+/// Planet metadata lines of the legend: `(key, value)`.
+pub fn legend_metadata(world: &World) -> Vec<(&'static str, String)> {
     // Capitalize the planet type name for display.
     let planet_str = {
         let s = format!("{}", world.planet_type);
@@ -166,51 +271,82 @@ pub fn export_legend_png(world: &World, path: &str) {
             Some(f) => f.to_uppercase().collect::<String>() + chars.as_str(),
         }
     };
-    let meta: &[(&str, String)] = &[
+    // Physical ranges across the whole map (see `units.rs` for the calibration).
+    let range = |f: fn(&Tile) -> f32| {
+        world
+            .tiles
+            .iter()
+            .map(f)
+            .fold((f32::MAX, f32::MIN), |(lo, hi), v| (lo.min(v), hi.max(v)))
+    };
+    let (elev_lo, elev_hi) = range(|t| t.elevation_m);
+    let (temp_lo, temp_hi) = range(|t| t.temperature_c);
+    let (prec_lo, prec_hi) = range(|t| t.precipitation_mm);
+
+    vec![
         ("Planet", planet_str),
         ("Seed", format!("{}", world.seed)),
         ("Sea level", format!("{:+.2}", world.sea_level)),
         ("Volcanic", format!("{:.2}", world.volcanic_intensity)),
         ("Circumference", format!("{:.0} km", world.circumference_km)),
         ("Gravity", format!("{:.2} g", world.gravity_modifier)),
-    ];
+        ("Stellar flux", format!("{:.2} × Earth", world.stellar_flux)),
+        ("Elevation", format!("{elev_lo:+.0} .. {elev_hi:+.0} m")),
+        ("Temperature", format!("{temp_lo:+.0} .. {temp_hi:+.0} °C")),
+        (
+            "Precipitation",
+            format!("{prec_lo:.0} .. {prec_hi:.0} mm/yr"),
+        ),
+    ]
+}
+
+/// Generate a legend PNG listing every biome that actually appears on the map,
+/// in the theme's colours.
+pub fn export_legend_png(world: &World, options: &RenderOptions, path: &str) {
+    let biomes = legend_biomes(world);
+    let meta = legend_metadata(world);
 
     // ── Layout constants ──────────────────────────────────────────────────────
+    let line = font::line_height(TEXT_SIZE).ceil() as u32;
+    let width = |s: &str| font::text_width(s, TEXT_SIZE).ceil() as u32;
     const PAD: u32 = 14;
     const SWATCH_W: u32 = 48;
-    const SWATCH_H: u32 = CHAR_H;
+    let swatch_h = line;
     const SWATCH_GAP: u32 = 8;
-    const ROW_H: u32 = SWATCH_H + 6;
-    const META_ROW_H: u32 = CHAR_H + 5;
+    let row_h = swatch_h + 6;
+    let meta_row_h = line + 5;
     // Space added before and after each horizontal divider line.
     const SECTION_GAP: u32 = 8;
 
     // Width is the maximum of: title, metadata block, biome block.
     let title = "BIOME LEGEND";
-    let max_biome_len = biomes
+    let max_biome_w = biomes
         .iter()
-        .map(|&b| biome_name(b).len())
+        .map(|&b| width(biome_name(b)))
         .max()
-        .unwrap_or(10) as u32;
-    let biome_col_w = SWATCH_W + SWATCH_GAP + max_biome_len * CHAR_W;
+        .unwrap_or(0);
+    let biome_col_w = SWATCH_W + SWATCH_GAP + max_biome_w;
 
-    // For metadata we align values at a fixed column (longest key + ": ").
-    let max_key_len = meta.iter().map(|(k, _)| k.len()).max().unwrap_or(0) as u32;
-    let key_col_chars = max_key_len + 2; // +2 for ": "
-    let max_val_len = meta.iter().map(|(_, v)| v.len()).max().unwrap_or(0) as u32;
-    let meta_col_w = (key_col_chars + max_val_len) * CHAR_W;
+    // For metadata we align values at a fixed column (widest key + ": ").
+    let key_col_w = meta
+        .iter()
+        .map(|(k, _)| width(&format!("{k}: ")))
+        .max()
+        .unwrap_or(0);
+    let max_val_w = meta.iter().map(|(_, v)| width(v)).max().unwrap_or(0);
+    let meta_col_w = key_col_w + max_val_w;
 
-    let content_w = biome_col_w.max(meta_col_w).max(title.len() as u32 * CHAR_W);
+    let content_w = biome_col_w.max(meta_col_w).max(width(title));
     let img_w = PAD + content_w + PAD;
 
     // Height = title + meta section (2 dividers + rows) + biome rows.
     let divider_block_h = SECTION_GAP + 1 + SECTION_GAP; // gap · line · gap
     let img_h = PAD
-        + CHAR_H                                          // title
+        + line                                            // title
         + divider_block_h                                 // divider above meta
-        + meta.len() as u32 * META_ROW_H                 // meta rows
+        + meta.len() as u32 * meta_row_h                 // meta rows
         + divider_block_h                                 // divider below meta
-        + biomes.len() as u32 * ROW_H                    // biome rows
+        + biomes.len() as u32 * row_h                    // biome rows
         + PAD;
 
     const BG: [u8; 3] = [22, 22, 35];
@@ -222,11 +358,14 @@ pub fn export_legend_png(world: &World, path: &str) {
     const DIVIDER_COLOR: [u8; 3] = [55, 60, 88];
 
     let mut img = RgbImage::from_pixel(img_w, img_h, Rgb(BG));
+    let draw_str = |img: &mut RgbImage, s: &str, x: u32, y: u32, color: [u8; 3]| {
+        draw_text(img, s, x as f64, y as f64, TEXT_SIZE, color);
+    };
 
     // ── Title ─────────────────────────────────────────────────────────────────
-    let title_x = (img_w.saturating_sub(title.len() as u32 * CHAR_W)) / 2;
+    let title_x = (img_w.saturating_sub(width(title))) / 2;
     draw_str(&mut img, title, title_x, PAD, TITLE_COLOR);
-    let mut y = PAD + CHAR_H;
+    let mut y = PAD + line;
 
     // ── Helper: draw a horizontal divider line ────────────────────────────────
     let draw_divider = |img: &mut RgbImage, y: u32| {
@@ -240,12 +379,12 @@ pub fn export_legend_png(world: &World, path: &str) {
     draw_divider(&mut img, y);
     y += 1 + SECTION_GAP;
 
-    let val_x = PAD + key_col_chars * CHAR_W;
-    for (key, val) in meta {
+    let val_x = PAD + key_col_w;
+    for (key, val) in &meta {
         let label = format!("{}: ", key);
         draw_str(&mut img, &label, PAD, y, KEY_COLOR);
         draw_str(&mut img, val, val_x, y, VAL_COLOR);
-        y += META_ROW_H;
+        y += meta_row_h;
     }
 
     y += SECTION_GAP;
@@ -254,9 +393,9 @@ pub fn export_legend_png(world: &World, path: &str) {
 
     // ── One row per biome ─────────────────────────────────────────────────────
     for &b in &biomes {
-        let color = biome_color(b);
-        fill_rect(&mut img, PAD, y, SWATCH_W, SWATCH_H, color);
-        outline_rect(&mut img, PAD, y, SWATCH_W, SWATCH_H, BORDER_COLOR);
+        let color = options.theme.region_color(b);
+        fill_rect(&mut img, PAD, y, SWATCH_W, swatch_h, color);
+        outline_rect(&mut img, PAD, y, SWATCH_W, swatch_h, BORDER_COLOR);
         draw_str(
             &mut img,
             biome_name(b),
@@ -264,7 +403,7 @@ pub fn export_legend_png(world: &World, path: &str) {
             y,
             TEXT_COLOR,
         );
-        y += ROW_H;
+        y += row_h;
     }
 
     img.save(path).unwrap();
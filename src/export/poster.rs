@@ -0,0 +1,465 @@
+/// Print-ready poster: the map, its legend, the planet's metadata and
+/// optional climate insets laid out on one page, as `poster.png` and
+/// `poster.svg`.
+///
+/// Pages are landscape and sized in millimetres; the DPI sets how many pixels
+/// that is.  The layout is worked out once as a list of [`Mark`]s, which both
+/// writers draw: the PNG at the page's pixel size, the SVG at its physical
+/// size with text and swatches as vectors and the map and insets embedded as
+/// PNGs, so it needs no other files.
+use super::font::{self, FAMILY, draw_text, text_width};
+use super::png::{fill_rect, legend_biomes, legend_metadata, outline_rect, render_png};
+use super::render::RenderOptions;
+use super::theme::ramp;
+use crate::world::*;
+use image::imageops::{self, FilterType};
+use image::{ImageFormat, Rgb, RgbImage};
+use std::io::Write;
+
+/// Paper sizes, all laid out landscape.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum PageSize {
+    A4,
+    A3,
+    A2,
+    A1,
+    Letter,
+    Tabloid,
+}
+
+/// Names of the page sizes, for messages.
+pub const PAGE_SIZES: [&str; 6] = ["a4", "a3", "a2", "a1", "letter", "tabloid"];
+
+impl PageSize {
+    /// Page size called `name` (`"a3"`, `"letter"`, …).
+    pub fn named(name: &str) -> Option<PageSize> {
+        match name {
+            "a4" => Some(PageSize::A4),
+            "a3" => Some(PageSize::A3),
+            "a2" => Some(PageSize::A2),
+            "a1" => Some(PageSize::A1),
+            "letter" => Some(PageSize::Letter),
+            "tabloid" => Some(PageSize::Tabloid),
+            _ => None,
+        }
+    }
+
+    /// Landscape width and height in millimetres.
+    pub fn mm(self) -> (f64, f64) {
+        match self {
+            PageSize::A4 => (297.0, 210.0),
+            PageSize::A3 => (420.0, 297.0),
+            PageSize::A2 => (594.0, 420.0),
+            PageSize::A1 => (841.0, 594.0),
+            PageSize::Letter => (279.4, 215.9),
+            PageSize::Tabloid => (431.8, 279.4),
+        }
+    }
+}
+
+/// Options for [`export_poster`].
+#[derive(Clone, Copy, Debug)]
+pub struct PosterOptions {
+    pub page: PageSize,
+    /// Printing resolution: pixels per inch of the PNG, and of the SVG's
+    /// coordinates.
+    pub dpi: u32,
+    /// Add temperature and precipitation thumbnails under the map.
+    pub insets: bool,
+}
+
+impl Default for PosterOptions {
+    fn default() -> Self {
+        PosterOptions {
+            page: PageSize::A3,
+            dpi: 150,
+            insets: false,
+        }
+    }
+}
+
+/// Page margin and the gap between blocks, in millimetres.
+const MARGIN_MM: f64 = 12.0;
+const GUTTER_MM: f64 = 8.0;
+/// Size of body text and of the title, in millimetres per em.
+const TEXT_MM: f64 = 3.0;
+const TITLE_MM: f64 = 7.0;
+/// Share of the page width, inside the margins, taken by the legend column.
+const SIDEBAR_SHARE: f64 = 0.24;
+/// Line spacing as a multiple of the font's line height.
+const LINE: f64 = 1.4;
+/// Steps the inset colour bars are drawn in.
+const BAR_STEPS: u32 = 64;
+
+/// Inset colours from the lowest value on the map (`0.0`) to the highest
+/// (`1.0`).
+const TEMPERATURE_RAMP: [(f32, [u8; 3]); 5] = [
+    (0.0, [40, 60, 160]),
+    (0.25, [90, 150, 220]),
+    (0.5, [240, 240, 210]),
+    (0.75, [240, 160, 70]),
+    (1.0, [180, 30, 30]),
+];
+const PRECIPITATION_RAMP: [(f32, [u8; 3]); 4] = [
+    (0.0, [225, 200, 150]),
+    (0.35, [170, 200, 110]),
+    (0.7, [60, 150, 120]),
+    (1.0, [30, 70, 160]),
+];
+
+/// A climate thumbnail under the map.
+struct Inset {
+    name: &'static str,
+    value: fn(&Tile) -> f32,
+    stops: &'static [(f32, [u8; 3])],
+    unit: &'static str,
+}
+
+const INSETS: [Inset; 2] = [
+    Inset {
+        name: "Temperature",
+        value: |t| t.temperature_c,
+        stops: &TEMPERATURE_RAMP,
+        unit: "°C",
+    },
+    Inset {
+        name: "Precipitation",
+        value: |t| t.precipitation_mm,
+        stops: &PRECIPITATION_RAMP,
+        unit: "mm/yr",
+    },
+];
+
+/// One thing drawn on the page, in page pixels.
+enum Mark {
+    /// An image stretched over `[x, y, width, height]`, framed in ink.
+    Image(RgbImage, [f64; 4]),
+    /// A filled rectangle, framed in ink if the flag is set.
+    Rect([f64; 4], [u8; 3], bool),
+    /// Ink text with the top-left corner of its line at `(x, y)`, at a size
+    /// in pixels per em.
+    Text(String, f64, f64, f64),
+}
+
+/// Writes `poster.png` and `poster.svg` into `dir`.
+pub fn export_poster(world: &World, render: &RenderOptions, poster: &PosterOptions, dir: &str) {
+    let (width, height, marks) = compose(world, render, poster);
+    let (ink, paper) = (render.theme.ink, render.theme.paper);
+    write_png(
+        width,
+        height,
+        &marks,
+        ink,
+        paper,
+        &format!("{dir}/poster.png"),
+    );
+    let (mm_w, mm_h) = poster.page.mm();
+    let svg = SvgPage {
+        width,
+        height,
+        mm_w,
+        mm_h,
+        ink,
+        paper,
+    };
+    write_svg(&svg, &marks, &format!("{dir}/poster.svg"));
+}
+
+/// Lays the poster out: page size in pixels and everything on it.
+fn compose(world: &World, render: &RenderOptions, poster: &PosterOptions) -> (u32, u32, Vec<Mark>) {
+    let px_mm = poster.dpi as f64 / 25.4;
+    let (mm_w, mm_h) = poster.page.mm();
+    let (width, height) = ((mm_w * px_mm).round() as u32, (mm_h * px_mm).round() as u32);
+    let (margin, gutter) = (MARGIN_MM * px_mm, GUTTER_MM * px_mm);
+    let mut marks = Vec::new();
+
+    // ── Title ─────────────────────────────────────────────────────────────────
+    let title = TITLE_MM * px_mm;
+    let planet = legend_metadata(world)[0].1.clone();
+    marks.push(Mark::Text(
+        format!("{planet} world, seed {}", world.seed),
+        margin,
+        margin,
+        title,
+    ));
+
+    // ── Columns ───────────────────────────────────────────────────────────────
+    let top = margin + font::line_height(title) + gutter;
+    let (content_w, content_h) = (width as f64 - 2.0 * margin, height as f64 - top - margin);
+    let sidebar_w = content_w * SIDEBAR_SHARE;
+    let column_w = content_w - sidebar_w - gutter;
+
+    // ── Insets: sized first, the map gets the rest of the column ─────────────
+    let text = TEXT_MM * px_mm;
+    let text_h = font::line_height(text);
+    let inset_w = (column_w - gutter) / 2.0;
+    let inset_h = inset_w * world.height as f64 / world.width as f64;
+    let inset_block = LINE * text_h + inset_h + 0.5 * text_h + text_h + LINE * text_h;
+    let map_room = content_h
+        - if poster.insets {
+            inset_block + gutter
+        } else {
+            0.0
+        };
+
+    // ── Map ───────────────────────────────────────────────────────────────────
+    let map = render_png(world, render);
+    let aspect = map.width() as f64 / map.height() as f64;
+    let (mut map_w, mut map_h) = (column_w, column_w / aspect);
+    if map_h > map_room {
+        (map_w, map_h) = (map_room * aspect, map_room);
+    }
+    let map_x = margin + (column_w - map_w) / 2.0;
+    marks.push(Mark::Image(map, [map_x, top, map_w, map_h]));
+
+    if poster.insets {
+        let y = top + map_h + gutter;
+        for (k, spec) in INSETS.iter().enumerate() {
+            let x = margin + k as f64 * (inset_w + gutter);
+            inset(&mut marks, world, spec, [x, y, inset_w, inset_h], text);
+        }
+    }
+
+    // ── Legend and metadata ───────────────────────────────────────────────────
+    let biomes = legend_biomes(world);
+    let meta = legend_metadata(world);
+    let key_w = meta
+        .iter()
+        .map(|(k, _)| text_width(&format!("{k}: "), text))
+        .fold(0.0, f64::max);
+    let widest = meta
+        .iter()
+        .map(|(_, v)| key_w + text_width(v, text))
+        .chain(
+            biomes
+                .iter()
+                .map(|&b| 3.0 * text + text_width(biome_name(b), text)),
+        )
+        .fold(0.0, f64::max);
+    let rows = 2 + biomes.len() + 2 + meta.len();
+    // Smaller text if the column is too short or narrow for it.
+    let fit = (content_h / (rows as f64 * LINE * text_h))
+        .min(sidebar_w / widest)
+        .min(1.0);
+    let (text, key_w) = (text * fit, key_w * fit);
+    let line = LINE * font::line_height(text);
+    let x = margin + column_w + gutter;
+    let mut y = top;
+    marks.push(Mark::Text("Legend".into(), x, y, text));
+    y += 2.0 * line;
+    for &b in &biomes {
+        let color = render.theme.region_color(b);
+        let swatch_h = 0.8 * font::line_height(text);
+        let swatch = [x, y + 0.1 * swatch_h, 2.2 * text, swatch_h];
+        marks.push(Mark::Rect(swatch, color, true));
+        marks.push(Mark::Text(biome_name(b).into(), x + 3.0 * text, y, text));
+        y += line;
+    }
+    y += line;
+    marks.push(Mark::Text("Planet".into(), x, y, text));
+    y += 2.0 * line;
+    for (key, value) in meta {
+        marks.push(Mark::Text(format!("{key}:"), x, y, text));
+        marks.push(Mark::Text(value, x + key_w, y, text));
+        y += line;
+    }
+
+    (width, height, marks)
+}
+
+/// A climate thumbnail at `[x, y, w, h]`: a caption above it and a colour bar
+/// from the map's lowest to its highest value below.
+fn inset(marks: &mut Vec<Mark>, world: &World, spec: &Inset, [x, y, w, h]: [f64; 4], text: f64) {
+    let Inset {
+        name,
+        value,
+        stops,
+        unit,
+    } = *spec;
+    let text_h = font::line_height(text);
+    let (lo, hi) = world
+        .tiles
+        .iter()
+        .map(value)
+        .fold((f32::MAX, f32::MIN), |(lo, hi), v| (lo.min(v), hi.max(v)));
+    let color = |v: f32| ramp(stops, (v - lo) / (hi - lo).max(f32::EPSILON));
+
+    let mut img = RgbImage::new(world.width as u32, world.height as u32);
+    for tile in &world.tiles {
+        img.put_pixel(tile.q as u32, tile.r as u32, Rgb(color(value(tile))));
+    }
+    marks.push(Mark::Text(name.into(), x, y, text));
+    let y = y + LINE * text_h;
+    marks.push(Mark::Image(img, [x, y, w, h]));
+
+    let y = y + h + 0.5 * text_h;
+    for k in 0..BAR_STEPS {
+        let t = (k as f32 + 0.5) / BAR_STEPS as f32;
+        let step = w / BAR_STEPS as f64;
+        let rect = [x + k as f64 * step, y, step, 0.8 * text_h];
+        marks.push(Mark::Rect(rect, color(lo + t * (hi - lo)), false));
+    }
+    let y = y + text_h;
+    marks.push(Mark::Text(format!("{lo:.0} {unit}"), x, y, text));
+    let high = format!("{hi:.0} {unit}");
+    let high_x = x + w - text_width(&high, text);
+    marks.push(Mark::Text(high, high_x, y, text));
+}
+
+/// Width of the ink frame around images, in pixels.
+fn frame(width: u32) -> u32 {
+    (width / 1500).max(1)
+}
+
+fn write_png(width: u32, height: u32, marks: &[Mark], ink: [u8; 3], paper: [u8; 3], path: &str) {
+    let mut page = RgbImage::from_pixel(width, height, Rgb(paper));
+    let px = |v: f64| v.round().max(0.0) as u32;
+    for mark in marks {
+        match mark {
+            Mark::Image(img, [x, y, w, h]) => {
+                let scaled = imageops::resize(img, px(*w), px(*h), FilterType::Triangle);
+                imageops::replace(&mut page, &scaled, px(*x) as i64, px(*y) as i64);
+                for k in 0..frame(width) {
+                    let (x, y) = (px(*x).saturating_sub(k + 1), px(*y).saturating_sub(k + 1));
+                    outline_rect(
+                        &mut page,
+                        x,
+                        y,
+                        px(*w) + 2 * (k + 1),
+                        px(*h) + 2 * (k + 1),
+                        ink,
+                    );
+                }
+            }
+            Mark::Rect([x, y, w, h], color, framed) => {
+                let (x0, y0) = (px(*x), px(*y));
+                let (x1, y1) = (px(x + w), px(y + h));
+                fill_rect(&mut page, x0, y0, x1 - x0, y1 - y0, *color);
+                if *framed {
+                    outline_rect(&mut page, x0, y0, x1 - x0, y1 - y0, ink);
+                }
+            }
+            Mark::Text(text, x, y, size) => draw_text(&mut page, text, *x, *y, *size, ink),
+        }
+    }
+    page.save(path).expect("failed to write poster");
+}
+
+/// Page of the SVG: its pixel and physical size, and colours.
+struct SvgPage {
+    width: u32,
+    height: u32,
+    mm_w: f64,
+    mm_h: f64,
+    ink: [u8; 3],
+    paper: [u8; 3],
+}
+
+fn write_svg(page: &SvgPage, marks: &[Mark], path: &str) {
+    let hex = |[r, g, b]: [u8; 3]| format!("#{r:02X}{g:02X}{b:02X}");
+    let (ink, paper) = (hex(page.ink), hex(page.paper));
+    let (w, h) = (page.width, page.height);
+    let mut out: Vec<u8> = Vec::new();
+    writeln!(out, r#"<?xml version="1.0" encoding="UTF-8"?>"#).unwrap();
+    writeln!(
+        out,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{}mm" height="{}mm" viewBox="0 0 {w} {h}">"#,
+        page.mm_w, page.mm_h
+    )
+    .unwrap();
+    writeln!(out, r#"<rect width="{w}" height="{h}" fill="{paper}"/>"#).unwrap();
+    let stroke = frame(w);
+    for mark in marks {
+        match mark {
+            Mark::Image(img, [x, y, w, h]) => {
+                let mut png = std::io::Cursor::new(Vec::new());
+                img.write_to(&mut png, ImageFormat::Png)
+                    .expect("failed to encode poster image");
+                writeln!(
+                    out,
+                    r#"<image x="{x:.1}" y="{y:.1}" width="{w:.1}" height="{h:.1}" preserveAspectRatio="none" href="data:image/png;base64,{}"/>"#,
+                    base64(png.get_ref())
+                )
+                .unwrap();
+                writeln!(
+                    out,
+                    r#"<rect x="{x:.1}" y="{y:.1}" width="{w:.1}" height="{h:.1}" fill="none" stroke="{ink}" stroke-width="{}"/>"#,
+                    2 * stroke
+                )
+                .unwrap();
+            }
+            Mark::Rect([x, y, w, h], color, framed) => {
+                let frame = if *framed {
+                    format!(r#" stroke="{ink}" stroke-width="1""#)
+                } else {
+                    String::new()
+                };
+                writeln!(
+                    out,
+                    r#"<rect x="{x:.1}" y="{y:.1}" width="{w:.1}" height="{h:.1}" fill="{}"{frame}/>"#,
+                    hex(*color)
+                )
+                .unwrap();
+            }
+            // Marks place text by its line's top; SVG by its baseline.
+            Mark::Text(text, x, y, size) => writeln!(
+                out,
+                r#"<text x="{x:.1}" y="{:.1}" font-family="{FAMILY}" font-size="{size:.1}" fill="{ink}">{}</text>"#,
+                y + font::ascent(*size),
+                escape(text),
+            )
+            .unwrap(),
+        }
+    }
+    writeln!(out, "</svg>").unwrap();
+    std::fs::write(path, &out).expect("failed to write poster SVG");
+}
+
+/// `text` with the characters XML gives meaning to replaced by entities.
+fn escape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+/// Standard base64 with padding, for `data:` URIs.
+fn base64(bytes: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut out = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let n = chunk
+            .iter()
+            .enumerate()
+            .fold(0u32, |n, (k, &b)| n | (b as u32) << (16 - 8 * k));
+        for k in 0..4 {
+            if k <= chunk.len() {
+                out.push(ALPHABET[(n >> (18 - 6 * k) & 63) as usize] as char);
+            } else {
+                out.push('=');
+            }
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn svg_text_is_escaped() {
+        assert_eq!(
+            escape("Rivers & <Lakes> \"north\""),
+            "Rivers &amp; &lt;Lakes&gt; &quot;north&quot;"
+        );
+        assert_eq!(escape("Île d'Été, 40 000 km"), "Île d'Été, 40 000 km");
+    }
+}
@@ -0,0 +1,410 @@
+/// Map projections for the rendered outputs (`world.png`, `world.svg`).
+///
+/// Every projection maps longitude/latitude to a plane and back; the
+/// renderers resample the world grid through [`Projection::raster`] and draw
+/// reference lines and the graticule through [`Projection::parallel`] and
+/// [`Projection::meridian`].  Apart from the
+/// equirectangular map, which keeps the grid's own layout (south at the top),
+/// every projection is drawn north-up.
+use crate::grid::{CUBE_FACES, CubeFace};
+use crate::world::World;
+use std::f64::consts::{FRAC_PI_2, FRAC_PI_4, PI, SQRT_2};
+
+/// Robinson's tabulated parallel lengths (`X`) and distances from the
+/// equator (`Y`), every 5° of latitude from 0° to 90°.
+const ROBINSON: [(f64, f64); 19] = [
+    (1.0000, 0.0000),
+    (0.9986, 0.0620),
+    (0.9954, 0.1240),
+    (0.9900, 0.1860),
+    (0.9822, 0.2480),
+    (0.9730, 0.3100),
+    (0.9600, 0.3720),
+    (0.9427, 0.4340),
+    (0.9216, 0.4958),
+    (0.8962, 0.5571),
+    (0.8679, 0.6176),
+    (0.8350, 0.6769),
+    (0.7986, 0.7346),
+    (0.7597, 0.7903),
+    (0.7186, 0.8435),
+    (0.6732, 0.8936),
+    (0.6213, 0.9394),
+    (0.5722, 0.9761),
+    (0.5322, 1.0000),
+];
+const ROBINSON_X: f64 = 0.8487;
+const ROBINSON_Y: f64 = 1.3523;
+
+/// How the sphere is laid out on the rendered map.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Projection {
+    /// The world grid as is: longitude `0..360` across, south at the top.
+    Equirectangular,
+    /// Equal-area ellipse, 2:1.
+    Mollweide,
+    /// Compromise projection with flattened poles, as in many world atlases.
+    Robinson,
+    /// The globe seen from space, centred on `(lon, lat)` in degrees.
+    Orthographic { lon: f64, lat: f64 },
+    /// Polar stereographic of the northern hemisphere, prime meridian down.
+    PolarNorth,
+    /// Polar stereographic of the southern hemisphere, prime meridian up.
+    PolarSouth,
+    /// Six gnomonic cube faces unfolded into a cross (4 × 3 faces).
+    Cube,
+}
+
+impl Projection {
+    /// Extent of the projected plane: `[x_min, x_max, y_min, y_max]`.
+    fn bounds(self) -> [f64; 4] {
+        match self {
+            Projection::Equirectangular => [0.0, 2.0 * PI, -FRAC_PI_2, FRAC_PI_2],
+            Projection::Mollweide => [-2.0 * SQRT_2, 2.0 * SQRT_2, -SQRT_2, SQRT_2],
+            Projection::Robinson => [-ROBINSON_X * PI, ROBINSON_X * PI, -ROBINSON_Y, ROBINSON_Y],
+            Projection::Orthographic { .. } => [-1.0, 1.0, -1.0, 1.0],
+            // Out to the equator.
+            Projection::PolarNorth | Projection::PolarSouth => [-2.0, 2.0, -2.0, 2.0],
+            Projection::Cube => [-4.0, 4.0, -3.0, 3.0],
+        }
+    }
+
+    /// Pixel size of the rendered map: the world's width for the wide
+    /// projections, its height for the square ones.
+    pub fn size(self, world: &World) -> (u32, u32) {
+        let [x0, x1, y0, y1] = self.bounds();
+        let aspect = (y1 - y0) / (x1 - x0);
+        match self {
+            Projection::Equirectangular => (world.width as u32, world.height as u32),
+            Projection::Orthographic { .. } | Projection::PolarNorth | Projection::PolarSouth => {
+                (world.height as u32, world.height as u32)
+            }
+            _ => (
+                world.width as u32,
+                (world.width as f64 * aspect).round() as u32,
+            ),
+        }
+    }
+
+    /// Plane coordinates of `(lon, lat)` radians, or `None` where the point
+    /// is not drawn (the far side of the globe, the other hemisphere).
+    fn forward(self, lon: f64, lat: f64) -> Option<(f64, f64)> {
+        // Longitude in [-π, π), as the world-wide projections centre on 0°.
+        let lambda = (lon + PI).rem_euclid(2.0 * PI) - PI;
+        match self {
+            Projection::Equirectangular => Some((lon.rem_euclid(2.0 * PI), lat)),
+            Projection::Mollweide => {
+                let theta = mollweide_theta(lat);
+                Some((
+                    2.0 * SQRT_2 / PI * lambda * theta.cos(),
+                    SQRT_2 * theta.sin(),
+                ))
+            }
+            Projection::Robinson => {
+                let (x, y) = robinson(lat.abs());
+                Some((ROBINSON_X * x * lambda, ROBINSON_Y * y * lat.signum()))
+            }
+            Projection::Orthographic {
+                lon: lon0,
+                lat: lat0,
+            } => {
+                let (lon0, lat0) = (lon0.to_radians(), lat0.to_radians());
+                let dl = lon - lon0;
+                let cos_c = lat0.sin() * lat.sin() + lat0.cos() * lat.cos() * dl.cos();
+                (cos_c >= 0.0).then(|| {
+                    (
+                        lat.cos() * dl.sin(),
+                        lat0.cos() * lat.sin() - lat0.sin() * lat.cos() * dl.cos(),
+                    )
+                })
+            }
+            Projection::PolarNorth => (lat >= 0.0).then(|| {
+                let rho = 2.0 * (FRAC_PI_4 - lat / 2.0).tan();
+                (rho * lon.sin(), -rho * lon.cos())
+            }),
+            Projection::PolarSouth => (lat <= 0.0).then(|| {
+                let rho = 2.0 * (FRAC_PI_4 + lat / 2.0).tan();
+                (rho * lon.sin(), rho * lon.cos())
+            }),
+            Projection::Cube => {
+                let p = [lat.cos() * lon.cos(), lat.cos() * lon.sin(), lat.sin()];
+                let &CubeFace {
+                    normal: n,
+                    right: r,
+                    up: u,
+                    cross: (col, row),
+                    ..
+                } = CUBE_FACES
+                    .iter()
+                    .max_by(|a, b| dot(p, a.normal).total_cmp(&dot(p, b.normal)))
+                    .unwrap();
+                let d = dot(p, n);
+                let (fu, fv) = (dot(p, r) / d, dot(p, u) / d);
+                Some((
+                    -4.0 + 2.0 * col as f64 + 1.0 + fu,
+                    3.0 - 2.0 * row as f64 - 1.0 + fv,
+                ))
+            }
+        }
+    }
+
+    /// `(lon, lat)` radians at plane coordinates `(x, y)`, or `None` outside
+    /// the projected area.  Longitudes come back in `[0, 2π)`.
+    fn inverse(self, x: f64, y: f64) -> Option<(f64, f64)> {
+        let lonlat = match self {
+            Projection::Equirectangular => Some((x, y)),
+            Projection::Mollweide => {
+                let theta = (y / SQRT_2).clamp(-1.0, 1.0).asin();
+                let lat = ((2.0 * theta + (2.0 * theta).sin()) / PI)
+                    .clamp(-1.0, 1.0)
+                    .asin();
+                let lon = PI * x / (2.0 * SQRT_2 * theta.cos());
+                (lon.abs() <= PI).then_some((lon, lat))
+            }
+            Projection::Robinson => {
+                let lat = robinson_lat((y / ROBINSON_Y).abs())? * y.signum();
+                let lon = x / (ROBINSON_X * robinson(lat.abs()).0);
+                (lon.abs() <= PI).then_some((lon, lat))
+            }
+            Projection::Orthographic {
+                lon: lon0,
+                lat: lat0,
+            } => {
+                let (lon0, lat0) = (lon0.to_radians(), lat0.to_radians());
+                let rho = x.hypot(y);
+                if rho > 1.0 {
+                    None
+                } else if rho == 0.0 {
+                    Some((lon0, lat0))
+                } else {
+                    let c = rho.asin();
+                    let lat = (c.cos() * lat0.sin() + y * c.sin() * lat0.cos() / rho).asin();
+                    let lon = lon0
+                        + (x * c.sin())
+                            .atan2(rho * c.cos() * lat0.cos() - y * c.sin() * lat0.sin());
+                    Some((lon, lat))
+                }
+            }
+            Projection::PolarNorth => {
+                let rho = x.hypot(y);
+                (rho <= 2.0).then(|| (x.atan2(-y), FRAC_PI_2 - 2.0 * (rho / 2.0).atan()))
+            }
+            Projection::PolarSouth => {
+                let rho = x.hypot(y);
+                (rho <= 2.0).then(|| (x.atan2(y), 2.0 * (rho / 2.0).atan() - FRAC_PI_2))
+            }
+            Projection::Cube => {
+                let col = ((x + 4.0) / 2.0).floor() as i32;
+                let row = ((3.0 - y) / 2.0).floor() as i32;
+                let face = CUBE_FACES.iter().find(|f| f.cross == (col, row))?;
+                let (n, r, u) = (face.normal, face.right, face.up);
+                let fu = x + 4.0 - 2.0 * col as f64 - 1.0;
+                let fv = y - 3.0 + 2.0 * row as f64 + 1.0;
+                let p: [f64; 3] = std::array::from_fn(|k| n[k] + fu * r[k] + fv * u[k]);
+                let len = dot(p, p).sqrt();
+                Some((p[1].atan2(p[0]), (p[2] / len).asin()))
+            }
+        };
+        lonlat.map(|(lon, lat)| (lon.rem_euclid(2.0 * PI), lat))
+    }
+
+    /// Pixel position `(px, py)` ↔ plane coordinates, through [`Projection::size`].
+    fn plane(self, world: &World, px: f64, py: f64) -> (f64, f64) {
+        let (w, h) = self.size(world);
+        let [x0, x1, y0, y1] = self.bounds();
+        let x = x0 + px / w as f64 * (x1 - x0);
+        let y = if self == Projection::Equirectangular {
+            y0 + py / h as f64 * (y1 - y0)
+        } else {
+            y1 - py / h as f64 * (y1 - y0)
+        };
+        (x, y)
+    }
+
+    fn pixel(self, world: &World, x: f64, y: f64) -> (f64, f64) {
+        let (w, h) = self.size(world);
+        let [x0, x1, y0, y1] = self.bounds();
+        let px = (x - x0) / (x1 - x0) * w as f64;
+        let py = if self == Projection::Equirectangular {
+            (y - y0) / (y1 - y0) * h as f64
+        } else {
+            (y1 - y) / (y1 - y0) * h as f64
+        };
+        (px, py)
+    }
+
+    /// Resamples per-tile colours (column-major, like [`super::render::tile_colors`])
+    /// onto the projected map, row by row.  `None` marks pixels off the map.
+    pub fn raster(self, world: &World, colors: &[[u8; 3]]) -> Vec<Option<[u8; 3]>> {
+        let (w, h) = self.size(world);
+        (0..h)
+            .flat_map(|py| (0..w).map(move |px| (px, py)))
+            .map(|(px, py)| {
+                let (x, y) = self.plane(world, px as f64 + 0.5, py as f64 + 0.5);
+                let (lon, lat) = self.inverse(x, y)?;
+                let tile = super::geo::tile_index(world, lon.to_degrees(), lat.to_degrees());
+                Some(colors[tile])
+            })
+            .collect()
+    }
+
+    /// Pixel position of `(lon_deg, lat_deg)`, or `None` where the point is
+    /// not drawn.
+    pub fn point(self, world: &World, lon_deg: f64, lat_deg: f64) -> Option<(f64, f64)> {
+        self.forward(lon_deg.to_radians(), lat_deg.to_radians())
+            .map(|(x, y)| self.pixel(world, x, y))
+    }
+
+    /// The parallel at `lat_deg` as polylines in pixel coordinates, broken
+    /// wherever it leaves the map or jumps across an interruption.
+    pub fn parallel(self, world: &World, lat_deg: f64) -> Vec<Vec<(f64, f64)>> {
+        self.trace(world, |t| (-180.0 + 360.0 * t, lat_deg))
+    }
+
+    /// The meridian at `lon_deg` from pole to pole, as [`Projection::parallel`].
+    pub fn meridian(self, world: &World, lon_deg: f64) -> Vec<Vec<(f64, f64)>> {
+        self.trace(world, |t| (lon_deg, -90.0 + 180.0 * t))
+    }
+
+    /// Polylines of the curve `t ↦ (lon, lat)` in degrees for `t` in `0..=1`.
+    fn trace(self, world: &World, at: impl Fn(f64) -> (f64, f64)) -> Vec<Vec<(f64, f64)>> {
+        let (w, _) = self.size(world);
+        let mut lines: Vec<Vec<(f64, f64)>> = Vec::new();
+        let mut current: Vec<(f64, f64)> = Vec::new();
+        const STEPS: u32 = 1440;
+        for k in 0..=STEPS {
+            let (lon, lat) = at(k as f64 / STEPS as f64);
+            let point = self.point(world, lon, lat);
+            match point {
+                Some(p) => {
+                    // A long step means the line wrapped to another part of the map.
+                    if let Some(&(qx, qy)) = current.last()
+                        && (p.0 - qx).hypot(p.1 - qy) > w as f64 / 16.0
+                    {
+                        lines.push(std::mem::take(&mut current));
+                    }
+                    current.push(p);
+                }
+                None if !current.is_empty() => lines.push(std::mem::take(&mut current)),
+                None => {}
+            }
+        }
+        lines.push(current);
+        lines.retain(|l| l.len() >= 2);
+        lines
+    }
+
+    /// Point the map is read from, `(lon, lat)` in degrees: where the
+    /// graticule is labelled and the scale bar and north arrow are measured.
+    /// The centre of the map, or halfway to the equator on the polar maps,
+    /// where north has a direction.
+    pub fn reference(self) -> (f64, f64) {
+        match self {
+            Projection::Equirectangular => (180.0, 0.0),
+            Projection::Mollweide | Projection::Robinson | Projection::Cube => (0.0, 0.0),
+            Projection::Orthographic { lon, lat } => (lon, lat.clamp(-89.0, 89.0)),
+            Projection::PolarNorth => (0.0, 45.0),
+            Projection::PolarSouth => (0.0, -45.0),
+        }
+    }
+}
+
+fn dot(a: [f64; 3], b: [f64; 3]) -> f64 {
+    a[0] * b[0] + a[1] * b[1] + a[2] * b[2]
+}
+
+/// Auxiliary angle θ of the Mollweide projection: `2θ + sin 2θ = π sin φ`.
+fn mollweide_theta(lat: f64) -> f64 {
+    if lat.abs() >= FRAC_PI_2 - 1e-9 {
+        return lat.signum() * FRAC_PI_2;
+    }
+    let target = PI * lat.sin();
+    let mut theta = lat;
+    for _ in 0..20 {
+        let f = 2.0 * theta + (2.0 * theta).sin() - target;
+        theta -= f / (2.0 + 2.0 * (2.0 * theta).cos());
+    }
+    theta
+}
+
+/// Robinson's `(X, Y)` at latitude `lat ≥ 0` radians, linearly interpolated.
+fn robinson(lat: f64) -> (f64, f64) {
+    let t = (lat.to_degrees() / 5.0).clamp(0.0, 18.0);
+    let i = (t.floor() as usize).min(17);
+    let f = t - i as f64;
+    let (x0, y0) = ROBINSON[i];
+    let (x1, y1) = ROBINSON[i + 1];
+    (x0 + (x1 - x0) * f, y0 + (y1 - y0) * f)
+}
+
+/// Latitude (radians, `≥ 0`) where Robinson's `Y` equals `y`.
+fn robinson_lat(y: f64) -> Option<f64> {
+    if y > 1.0 {
+        return None;
+    }
+    let i = ROBINSON.windows(2).position(|p| y <= p[1].1)?;
+    let (y0, y1) = (ROBINSON[i].1, ROBINSON[i + 1].1);
+    Some(((i as f64 + (y - y0) / (y1 - y0)) * 5.0).to_radians())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ALL: [Projection; 7] = [
+        Projection::Equirectangular,
+        Projection::Mollweide,
+        Projection::Robinson,
+        Projection::Orthographic {
+            lon: 30.0,
+            lat: 20.0,
+        },
+        Projection::PolarNorth,
+        Projection::PolarSouth,
+        Projection::Cube,
+    ];
+
+    #[test]
+    fn inverse_undoes_forward() {
+        // Odd multiples of 5° stay off the cube's face edges and the poles.
+        let degrees = |k: i32| (5 + 10 * k) as f64;
+        for projection in ALL {
+            let mut drawn = 0;
+            for lon in (-18..18).map(degrees) {
+                for lat in (-9..9).map(degrees) {
+                    let (lon, lat) = (lon.to_radians(), lat.to_radians());
+                    let Some((x, y)) = projection.forward(lon, lat) else {
+                        continue;
+                    };
+                    let (lon2, lat2) = projection.inverse(x, y).unwrap();
+                    let dlon = (lon2 - lon).rem_euclid(2.0 * PI);
+                    assert!(
+                        dlon.min(2.0 * PI - dlon) < 1e-6 && (lat2 - lat).abs() < 1e-6,
+                        "{projection:?}: ({lon}, {lat}) came back as ({lon2}, {lat2})"
+                    );
+                    let [x0, x1, y0, y1] = projection.bounds();
+                    assert!((x0..=x1).contains(&x) && (y0..=y1).contains(&y));
+                    drawn += 1;
+                }
+            }
+            assert!(drawn > 0, "{projection:?} drew nothing");
+        }
+    }
+
+    #[test]
+    fn hidden_points_are_not_drawn() {
+        let (lon, lat) = (0.0_f64.to_radians(), 45.0_f64.to_radians());
+        assert!(Projection::PolarNorth.forward(lon, lat).is_some());
+        assert!(Projection::PolarSouth.forward(lon, lat).is_none());
+        assert!(Projection::PolarNorth.forward(lon, -lat).is_none());
+
+        let globe = Projection::Orthographic { lon: 0.0, lat: 0.0 };
+        assert_eq!(globe.forward(0.0, 0.0), Some((0.0, 0.0)));
+        assert!(globe.forward(PI, 0.0).is_none());
+        assert!(globe.inverse(0.8, 0.8).is_none());
+        // The world-wide projections leave the corners of their frame empty.
+        assert!(Projection::Mollweide.inverse(2.5, 1.2).is_none());
+        assert!(Projection::Robinson.inverse(2.6, 1.3).is_none());
+        assert!(Projection::Cube.inverse(3.0, 2.0).is_none());
+    }
+}
@@ -0,0 +1,163 @@
+/// Normal and ambient-occlusion maps of the globe's relief, for lighting the
+/// 3-D globe in a renderer.
+///
+/// Both maps have one pixel per tile in the `globe_texture.png` layout
+/// (column `q` from 0° eastwards, row 0 at the south pole), so they share the
+/// globe's texture coordinates.  Heights are those of the displaced globe:
+/// the terrain surface on land, the water surface on the oceans, multiplied
+/// by [`MeshOptions::exaggeration`].  Neighbours are looked up across the
+/// 0° / 360° seam, so neither map has an edge there.
+use super::geo::sphere_radius_m;
+use super::heightmap::surface_elevation_m;
+use super::mesh::{MeshOptions, NormalSpace, sphere_point};
+use crate::world::World;
+use image::{GrayImage, Luma, Rgb, RgbImage};
+use std::f64::consts::PI;
+
+/// Directions the horizon is searched in for ambient occlusion.
+const AO_DIRECTIONS: usize = 16;
+/// Distances in pixels the horizon is sampled at along each direction.
+const AO_STEPS: [f64; 10] = [1.0, 2.0, 3.0, 4.0, 6.0, 8.0, 11.0, 16.0, 22.0, 32.0];
+
+/// Globe heights in metres, exaggerated, in `world.tiles` order.
+fn heights(world: &World, options: &MeshOptions) -> Vec<f64> {
+    world
+        .tiles
+        .iter()
+        .map(|t| (surface_elevation_m(world, t).max(0.0) * options.exaggeration) as f64)
+        .collect()
+}
+
+/// Latitude in degrees of the centre of row `r`.
+fn row_lat(world: &World, r: i32) -> f64 {
+    (r as f64 + 0.5) / world.height as f64 * 180.0 - 90.0
+}
+
+/// Writes a normal map of the globe to `path`, each normal encoded as
+/// `(n + 1) / 2 × 255` in RGB.
+///
+/// In [`NormalSpace::Tangent`] red points east, green north and blue out of
+/// the surface, so flat ground is `(128, 128, 255)`.  East and north are the
+/// directions of increasing `u` and `v` on the globe mesh.  In
+/// [`NormalSpace::Object`] the channels are the X, Y and Z of the globe's
+/// model space (north pole at `+Y`).
+pub fn export_normal_map(world: &World, options: &MeshOptions, path: &str) {
+    let (w, h) = (world.width, world.height);
+    let height = heights(world, options);
+    let at = |q: i32, r: i32| height[(q.rem_euclid(w) * h + r.clamp(0, h - 1)) as usize];
+    let radius = sphere_radius_m(world);
+    let column_m = 2.0 * PI * radius / w as f64;
+    let row_m = PI * radius / h as f64;
+
+    let img = RgbImage::from_fn(w as u32, h as u32, |q, r| {
+        let (q, r) = (q as i32, r as i32);
+        let lat = row_lat(world, r);
+        // Central differences; at the poles the missing row is not counted.
+        let dzdx = (at(q + 1, r) - at(q - 1, r)) / (2.0 * column_m * lat.to_radians().cos());
+        let rows = ((r + 1).min(h - 1) - (r - 1).max(0)) as f64;
+        let dzdy = (at(q, r + 1) - at(q, r - 1)) / (rows * row_m);
+        let len = (dzdx * dzdx + dzdy * dzdy + 1.0).sqrt();
+        let [e, n, u] = [-dzdx / len, -dzdy / len, 1.0 / len];
+
+        let normal = match options.normal_space {
+            NormalSpace::Tangent => [e, n, u],
+            NormalSpace::Object => {
+                let lon = (q as f64 + 0.5) / w as f64 * 360.0;
+                let (sin_lon, cos_lon) = lon.to_radians().sin_cos();
+                let (sin_lat, cos_lat) = lat.to_radians().sin_cos();
+                let up = sphere_point(lon, lat, 1.0).map(|c| c as f64);
+                let east = [-sin_lon, 0.0, -cos_lon];
+                let north = [-sin_lat * cos_lon, cos_lat, sin_lat * sin_lon];
+                std::array::from_fn(|k| e * east[k] + n * north[k] + u * up[k])
+            }
+        };
+        Rgb(normal.map(|c| ((c + 1.0) * 0.5 * 255.0).round().clamp(0.0, 255.0) as u8))
+    });
+    img.save(path).expect("failed to write normal map");
+}
+
+/// Writes an ambient-occlusion map of the globe to `path`: 8-bit grayscale,
+/// `255` where the whole sky is open and darker where higher ground around a
+/// point hides part of it.
+///
+/// The horizon is searched in 16 directions up to 32 pixels away; each
+/// direction occludes by the sine of the highest elevation angle found.
+pub fn export_ambient_occlusion(world: &World, options: &MeshOptions, path: &str) {
+    let (w, h) = (world.width, world.height);
+    let height = heights(world, options);
+    let radius = sphere_radius_m(world);
+    let column_m = 2.0 * PI * radius / w as f64;
+    let row_m = PI * radius / h as f64;
+    let directions: Vec<(f64, f64)> = (0..AO_DIRECTIONS)
+        .map(|k| (2.0 * PI * k as f64 / AO_DIRECTIONS as f64).sin_cos())
+        .collect();
+
+    let img = GrayImage::from_fn(w as u32, h as u32, |q, r| {
+        let (q, r) = (q as i32, r as i32);
+        let z = height[(q * h + r) as usize];
+        let x_m = column_m * row_lat(world, r).to_radians().cos();
+        let mut occlusion = 0.0;
+        for &(dx, dy) in &directions {
+            let mut horizon = 0.0f64;
+            for step in AO_STEPS {
+                let sr = (r as f64 + dy * step).round() as i32;
+                if !(0..h).contains(&sr) {
+                    break;
+                }
+                let sq = (q as f64 + dx * step).round() as i32;
+                let rise = height[(sq.rem_euclid(w) * h + sr) as usize] - z;
+                let run = ((dx * step * x_m).powi(2) + (dy * step * row_m).powi(2)).sqrt();
+                horizon = horizon.max(rise / (rise * rise + run * run).sqrt());
+            }
+            occlusion += horizon;
+        }
+        let open = 1.0 - occlusion / AO_DIRECTIONS as f64;
+        Luma([(open * 255.0).round().clamp(0.0, 255.0) as u8])
+    });
+    img.save(path)
+        .expect("failed to write ambient occlusion map");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generation::test_world;
+
+    /// `world` turned east by half a turn: column `q` moves to `q + w/2`.
+    fn turned(world: &World) -> World {
+        let mut turned = test_world();
+        let (w, h) = (world.width, world.height);
+        for (i, tile) in turned.tiles.iter_mut().enumerate() {
+            let (q, r) = (i as i32 / h, i as i32 % h);
+            *tile = world.tiles[((q + w / 2) % w * h + r) as usize].clone();
+            (tile.q, tile.r) = (q, r);
+        }
+        turned
+    }
+
+    fn render(world: &World, write: fn(&World, &MeshOptions, &str), name: &str) -> RgbImage {
+        let path = std::env::temp_dir().join(format!("{name}-{}.png", std::process::id()));
+        write(world, &MeshOptions::default(), path.to_str().unwrap());
+        let img = image::open(&path).unwrap().into_rgb8();
+        std::fs::remove_file(&path).unwrap();
+        img
+    }
+
+    #[test]
+    fn maps_have_no_edge_at_the_antimeridian() {
+        // With the seam in the middle of the map, the pixels either side of it
+        // must come out as they do anywhere else.
+        let world = test_world();
+        let other = turned(&world);
+        let (w, h) = (world.width as u32, world.height as u32);
+        for write in [export_normal_map, export_ambient_occlusion] {
+            let (a, b) = (render(&world, write, "a"), render(&other, write, "b"));
+            for q in 0..w {
+                for r in 0..h {
+                    let moved = b.get_pixel((q + w - w / 2) % w, r);
+                    assert_eq!(a.get_pixel(q, r), moved, "column {q}, row {r}");
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,383 @@
+use super::heightmap::surface_elevation_m;
+pub use super::projection::Projection;
+use super::theme::{Palette, Theme};
+use crate::noise::fbm;
+use crate::world::*;
+use noise::Perlin;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::f64::consts::PI;
+use std::sync::Arc;
+
+// Contour lines are drawn whenever a tile and a neighbour straddle one of these levels.
+pub const CONTOUR_LEVELS: &[f32] = &[-0.45, -0.15, 0.0, 0.15, 0.30, 0.45, 0.60, 0.75, 0.90];
+// Fraction to darken a pixel by when it sits on a contour line (0.0 = no change, 1.0 = black).
+pub const CONTOUR_DARKNESS: f32 = 0.40;
+
+/// Vertical exaggeration of slopes before shading: at map scale real slopes
+/// are too gentle to show.
+const HILLSHADE_EXAGGERATION: f64 = 25.0;
+/// Share of the light that reaches every slope, facing the sun or not.
+const HILLSHADE_AMBIENT: f64 = 0.35;
+/// Multi-directional hillshade: suns at these azimuths relative to the main
+/// sun, with these weights, so ridges running towards the sun still show.
+const HILLSHADE_SUNS: [(f64, f64); 3] = [(-60.0, 0.25), (0.0, 0.5), (60.0, 0.25)];
+
+/// Surface heights in metres above which themes with hatching hatch the
+/// ground, and cross-hatch it.
+const HATCH_ABOVE_M: f32 = 2000.0;
+const CROSS_HATCH_ABOVE_M: f32 = 3000.0;
+/// Tiles between two hatching lines.
+const HATCH_SPACING: i32 = 4;
+/// Tiles out to sea that coastal stippling reaches.
+const STIPPLE_REACH: u32 = 6;
+/// Share of the water tiles next to the coast that get a dot; it falls off
+/// linearly to nothing at [`STIPPLE_REACH`].
+const STIPPLE_DENSITY: f32 = 0.5;
+/// How far hatching, stippling and coastlines pull a tile towards the ink.
+const HATCH_INK: f32 = 0.55;
+const STIPPLE_INK: f32 = 0.6;
+const COASTLINE_INK: f32 = 0.85;
+/// Frequency, per planet radius, of the coarsest octave of theme variation.
+const VARIATION_FREQUENCY: f64 = 12.0;
+
+/// How biome colours are painted.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum RenderMode {
+    /// One flat colour per biome — hard borders.
+    Flat,
+    /// Mixes each tile's colour towards its secondary biome by its ecotone
+    /// blend factor, so borders fade smoothly.
+    Blend,
+}
+
+/// How relief is shown on top of the biome colours.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RenderStyle {
+    /// Contour lines darkened into the biome colours.
+    Contours,
+    /// Shaded relief: every tile lit by the sun according to its slope.
+    Hillshade,
+}
+
+/// Options shared by the map renderers (PNG and SVG).
+#[derive(Clone, Debug)]
+pub struct RenderOptions {
+    pub mode: RenderMode,
+    /// Palette and ink work; see [`Theme`].
+    pub theme: Arc<Theme>,
+    pub style: RenderStyle,
+    /// Direction the sun shines from for [`RenderStyle::Hillshade`], in
+    /// degrees clockwise from north.
+    pub sun_azimuth: f64,
+    /// Height of the sun above the horizon in degrees.
+    pub sun_altitude: f64,
+    /// Colour open ocean by depth instead of by biome.
+    pub ocean_tint: bool,
+    /// Layout of `world.png` and `world.svg`; other outputs stay equirectangular.
+    pub projection: Projection,
+    /// Spacing in degrees of the labelled graticule on `world.png` and
+    /// `world.svg`; `None` for no graticule.
+    pub graticule: Option<f64>,
+    /// Draw a scale bar on `world.png` and `world.svg`.
+    pub scale_bar: bool,
+    /// Draw a north arrow on `world.png` and `world.svg`.
+    pub north_arrow: bool,
+}
+
+impl Default for RenderOptions {
+    fn default() -> Self {
+        RenderOptions {
+            mode: RenderMode::Flat,
+            theme: Arc::new(Theme::default()),
+            style: RenderStyle::Contours,
+            sun_azimuth: 315.0,
+            sun_altitude: 45.0,
+            ocean_tint: false,
+            projection: Projection::Equirectangular,
+            graticule: None,
+            scale_bar: false,
+            north_arrow: false,
+        }
+    }
+}
+
+/// Final colour of every tile (theme palette, then contour darkening or
+/// hillshade, then the theme's ink work), in `world.tiles` order
+/// (column-major on the equirectangular grid: `q * height + r`).
+pub fn tile_colors(world: &World, options: &RenderOptions) -> Vec<[u8; 3]> {
+    let h = world.height as usize;
+    let theme = &*options.theme;
+
+    // Elevation lookup by flat tile index.
+    let elevation: Vec<f32> = world.tiles.iter().map(|t| t.elevation).collect();
+
+    let elev_at = |q: i32, r: i32| -> Option<f32> {
+        if q < 0 || r < 0 || q >= world.width || r >= world.height {
+            return None;
+        }
+        Some(elevation[q as usize * h + r as usize])
+    };
+
+    // Returns true if the edge between elevations `a` and `b` crosses any contour level.
+    let crosses_contour =
+        |a: f32, b: f32| -> bool { CONTOUR_LEVELS.iter().any(|&lvl| (a < lvl) != (b < lvl)) };
+
+    let grid = world.grid();
+    let shades = (options.style == RenderStyle::Hillshade).then(|| tile_shades(world, options));
+    let coast = (theme.coastline || theme.stippling).then(|| coast_distance(world));
+    let variation_noise = Perlin::new(world.seed + 700);
+    let mut colors = vec![[0u8; 3]; world.tiles.len()];
+    for (i, tile) in world.tiles.iter().enumerate() {
+        let height = surface_elevation_m(world, tile);
+        let mut color = theme.base_color(
+            options.mode,
+            tile.biome,
+            tile.secondary_biome,
+            tile.blend,
+            height,
+        );
+        if options.ocean_tint
+            && theme.palette != Palette::Elevation
+            && let Some(tint) = theme.ocean_tint(tile.biome, -tile.elevation_m)
+        {
+            color = tint;
+        }
+        if theme.variation > 0.0 {
+            color = vary(color, theme.variation, &variation_noise, grid.centre(i));
+        }
+        color = theme.on_paper(color);
+
+        if let Some(shades) = &shades {
+            color = shade(color, shades[i]);
+        } else {
+            let e = tile.elevation;
+
+            // Check the 4-connected neighbours.  Off the equirectangular raster
+            // (cube seams, hexes) the grid knows which tiles touch.
+            let is_contour = match world.layout {
+                Layout::Equirectangular => [
+                    elev_at(tile.q - 1, tile.r),
+                    elev_at(tile.q + 1, tile.r),
+                    elev_at(tile.q, tile.r - 1),
+                    elev_at(tile.q, tile.r + 1),
+                ]
+                .into_iter()
+                .flatten()
+                .any(|ne| crosses_contour(e, ne)),
+                _ => grid.neighbors4(i).any(|j| crosses_contour(e, elevation[j])),
+            };
+
+            if is_contour {
+                // Pull the colour towards the ink (black by default).
+                color = theme.inked(color, CONTOUR_DARKNESS);
+            }
+        }
+
+        if theme.hatching && height >= HATCH_ABOVE_M {
+            let line = (tile.q + tile.r).rem_euclid(HATCH_SPACING) == 0
+                || (height >= CROSS_HATCH_ABOVE_M
+                    && (tile.q - tile.r).rem_euclid(HATCH_SPACING) == 0);
+            if line {
+                color = theme.inked(color, HATCH_INK);
+            }
+        }
+        if let Some(coast) = &coast {
+            match coast[i] {
+                Some(0) if theme.coastline => color = theme.inked(color, COASTLINE_INK),
+                Some(d) if theme.stippling && d > 0 => {
+                    let density = STIPPLE_DENSITY * (1.0 - (d - 1) as f32 / STIPPLE_REACH as f32);
+                    if grain(i) < density {
+                        color = theme.inked(color, STIPPLE_INK);
+                    }
+                }
+                _ => {}
+            }
+        }
+        colors[i] = color;
+    }
+    colors
+}
+
+/// Tiles from the coast: `Some(0)` on land touching water, `Some(d)` on water
+/// `d` steps from the nearest land, up to [`STIPPLE_REACH`]; `None` elsewhere.
+fn coast_distance(world: &World) -> Vec<Option<u32>> {
+    let grid = world.grid();
+    let water = |i: usize| world.tiles[i].elevation < world.sea_level;
+    let mut distance = vec![None; world.tiles.len()];
+    let mut queue = VecDeque::new();
+    for (i, d) in distance.iter_mut().enumerate() {
+        if !water(i) && grid.neighbors4(i).any(water) {
+            *d = Some(0);
+            queue.push_back(i);
+        }
+    }
+    while let Some(i) = queue.pop_front() {
+        let d = distance[i].unwrap() + 1;
+        if d > STIPPLE_REACH {
+            continue;
+        }
+        for j in grid.neighbors4(i) {
+            if water(j) && distance[j].is_none() {
+                distance[j] = Some(d);
+                queue.push_back(j);
+            }
+        }
+    }
+    distance
+}
+
+/// `color` mottled by the theme's variation at unit-sphere point `p`: a
+/// brightness change and a slight warm / cool shift, both from smooth noise
+/// so neighbouring tiles vary together.
+pub fn vary(color: [u8; 3], strength: f32, noise: &Perlin, p: [f64; 3]) -> [u8; 3] {
+    let [x, y, z] = p.map(|c| c * VARIATION_FREQUENCY);
+    let light = 1.0 + strength * 1.5 * fbm(noise, x, y, z, 4);
+    let warm = strength * 0.5 * fbm(noise, x + 31.7, y - 17.3, z + 5.1, 2);
+    let factor = [light * (1.0 + warm), light, light * (1.0 - warm)];
+    std::array::from_fn(|k| (color[k] as f32 * factor[k]).round().clamp(0.0, 255.0) as u8)
+}
+
+/// Deterministic value in `[0, 1)` for tile `i`, for scattering dots.
+fn grain(i: usize) -> f32 {
+    let mut x = (i as u64).wrapping_add(0x9E37_79B9_7F4A_7C15);
+    x = (x ^ (x >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    x = (x ^ (x >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    x ^= x >> 31;
+    (x >> 40) as f32 / (1u64 << 24) as f32
+}
+
+/// Hillshade factor of every tile, from the slope of the terrain surface
+/// fitted through its neighbours' heights.
+fn tile_shades(world: &World, options: &RenderOptions) -> Vec<f32> {
+    let grid = world.grid();
+    let radius = world.circumference_km as f64 * 1000.0 / (2.0 * PI);
+    let centres: Vec<[f64; 3]> = (0..world.tiles.len()).map(|i| grid.centre(i)).collect();
+    let height: Vec<f64> = world
+        .tiles
+        .iter()
+        .map(|t| surface_elevation_m(world, t) as f64)
+        .collect();
+
+    (0..world.tiles.len())
+        .map(|i| {
+            let p = centres[i];
+            // Local east and north; any pair will do exactly at a pole.
+            let east = normalize([-p[1], p[0], 0.0]).unwrap_or([0.0, 1.0, 0.0]);
+            let north = [
+                p[1] * east[2] - p[2] * east[1],
+                p[2] * east[0] - p[0] * east[2],
+                p[0] * east[1] - p[1] * east[0],
+            ];
+            // Least-squares plane through the neighbours: solve the normal
+            // equations for (dz/dx, dz/dy).
+            let (mut sxx, mut sxy, mut syy, mut sxz, mut syz) = (0.0, 0.0, 0.0, 0.0, 0.0);
+            for j in grid.neighbors8(i) {
+                let d: [f64; 3] = std::array::from_fn(|k| centres[j][k] - p[k]);
+                let dx = radius * (d[0] * east[0] + d[1] * east[1] + d[2] * east[2]);
+                let dy = radius * (d[0] * north[0] + d[1] * north[1] + d[2] * north[2]);
+                let dz = height[j] - height[i];
+                sxx += dx * dx;
+                sxy += dx * dy;
+                syy += dy * dy;
+                sxz += dx * dz;
+                syz += dy * dz;
+            }
+            let det = sxx * syy - sxy * sxy;
+            if det.abs() < 1e-9 {
+                return 1.0;
+            }
+            let dzdx = (sxz * syy - syz * sxy) / det;
+            let dzdy = (syz * sxx - sxz * sxy) / det;
+            hillshade(options, dzdx, dzdy)
+        })
+        .collect()
+}
+
+/// Brightness factor of ground sloping by `dzdx` eastwards and `dzdy`
+/// northwards (metres per metre) under the options' sun; exactly `1.0` on
+/// flat ground, below it on slopes facing away from the sun.
+pub fn hillshade(options: &RenderOptions, dzdx: f64, dzdy: f64) -> f32 {
+    let (gx, gy) = (dzdx * HILLSHADE_EXAGGERATION, dzdy * HILLSHADE_EXAGGERATION);
+    let len = (gx * gx + gy * gy + 1.0).sqrt();
+    let normal = [-gx / len, -gy / len, 1.0 / len];
+    let altitude = options.sun_altitude.to_radians();
+    let light: f64 = HILLSHADE_SUNS
+        .iter()
+        .map(|&(offset, weight)| {
+            let azimuth = (options.sun_azimuth + offset).to_radians();
+            let sun = [
+                azimuth.sin() * altitude.cos(),
+                azimuth.cos() * altitude.cos(),
+                altitude.sin(),
+            ];
+            weight * (normal[0] * sun[0] + normal[1] * sun[1] + normal[2] * sun[2]).max(0.0)
+        })
+        .sum();
+    let lit = |light: f64| HILLSHADE_AMBIENT + (1.0 - HILLSHADE_AMBIENT) * light;
+    (lit(light) / lit(altitude.sin())) as f32
+}
+
+/// `color` scaled by a [`hillshade`] factor.
+pub fn shade(color: [u8; 3], factor: f32) -> [u8; 3] {
+    color.map(|c| (c as f32 * factor).round().clamp(0.0, 255.0) as u8)
+}
+
+fn normalize(a: [f64; 3]) -> Option<[f64; 3]> {
+    let len = (a[0] * a[0] + a[1] * a[1] + a[2] * a[2]).sqrt();
+    (len > 1e-12).then(|| a.map(|c| c / len))
+}
+
+/// Linear interpolation between two colours; `t = 0` gives `a`.
+pub fn mix(a: [u8; 3], b: [u8; 3], t: f32) -> [u8; 3] {
+    std::array::from_fn(|k| (a[k] as f32 + (b[k] as f32 - a[k] as f32) * t).round() as u8)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generation::test_world;
+
+    fn sun(azimuth: f64) -> RenderOptions {
+        RenderOptions {
+            sun_azimuth: azimuth,
+            ..RenderOptions::default()
+        }
+    }
+
+    #[test]
+    fn slopes_facing_the_sun_are_lit() {
+        let north_west = sun(315.0);
+        assert_eq!(hillshade(&north_west, 0.0, 0.0), 1.0);
+        // Rising to the south-east, the ground faces the north-west sun.
+        assert!(hillshade(&north_west, 0.05, -0.05) > 1.0);
+        assert!(hillshade(&north_west, -0.05, 0.05) < 1.0);
+        // Steeper slopes turn further towards or away from the sun.
+        assert!(hillshade(&north_west, -0.1, 0.1) < hillshade(&north_west, -0.05, 0.05));
+        // A slope that faces away from one sun faces the opposite one.
+        let south_east = sun(135.0);
+        assert!(hillshade(&south_east, -0.05, 0.05) > 1.0);
+        assert_eq!(shade([200, 100, 50], 0.5), [100, 50, 25]);
+    }
+
+    #[test]
+    fn tiles_on_a_north_facing_rise_are_shaded_by_the_sun() {
+        // Ground rising towards the south pole faces north.
+        let mut world = test_world();
+        for tile in &mut world.tiles {
+            tile.elevation_m = (world.height - tile.r) as f32 * 50.0;
+            tile.ice_thickness = 0.0;
+        }
+        let mid_latitudes = |i: usize| {
+            let r = world.tiles[i].r;
+            r > world.height / 6 && r < world.height * 5 / 6
+        };
+        for (azimuth, lit) in [(0.0, true), (315.0, true), (180.0, false), (135.0, false)] {
+            let shades = tile_shades(&world, &sun(azimuth));
+            for (i, &s) in shades.iter().enumerate().filter(|&(i, _)| mid_latitudes(i)) {
+                assert_eq!(s > 1.0, lit, "sun at {azimuth}°, tile {i}: {s}");
+            }
+        }
+    }
+}
@@ -0,0 +1,283 @@
+use super::geo::{sample, sphere_radius_m};
+use super::heightmap::surface_elevation_m;
+use super::mesh::{MeshOptions, face_normal, sphere_point, surface_radius};
+use crate::world::World;
+
+/// Diameter of the printed globe in millimetres (slicers read STL units as mm).
+const GLOBE_DIAMETER_MM: f32 = 100.0;
+/// Length of the longer side of a printed region slab in millimetres.
+const REGION_SIZE_MM: f32 = 100.0;
+/// Thickness of the solid base under the lowest point of a region slab (mm).
+const REGION_BASE_MM: f32 = 3.0;
+
+/// Longitude/latitude box of a region slab, in degrees.
+///
+/// Longitudes may use either `0..360` or `-180..180`; a box with
+/// `lon_max < lon_min` crosses the antimeridian.
+#[derive(Clone, Copy, Debug)]
+pub struct StlRegion {
+    pub lon_min: f64,
+    pub lat_min: f64,
+    pub lon_max: f64,
+    pub lat_max: f64,
+}
+
+/// Closed triangle mesh with shared vertices.
+struct Solid {
+    vertices: Vec<[f32; 3]>,
+    triangles: Vec<[u32; 3]>,
+}
+
+/// Writes watertight binary STL files for 3-D printing into `dir`:
+///
+/// - `globe.stl`  — the whole planet, `GLOBE_DIAMETER_MM` across
+/// - `region.stl` — only with `region`: the box extruded as a slab on a flat base
+///
+/// Both share the 3-D globe's relief exaggeration and keep oceans at the
+/// water surface.
+pub fn export_stl(world: &World, options: &MeshOptions, region: Option<StlRegion>, dir: &str) {
+    let globe = globe_solid(world, options);
+    write_stl(&globe, &format!("{dir}/globe.stl"));
+
+    if let Some(region) = region {
+        let slab = region_solid(world, options, region);
+        write_stl(&slab, &format!("{dir}/region.stl"));
+    }
+}
+
+/// Welded sphere: one vertex per pole, no seam column, fans at the poles.
+fn globe_solid(world: &World, options: &MeshOptions) -> Solid {
+    let cols = options.segments.max(8);
+    let rings = cols / 2;
+    let scale = GLOBE_DIAMETER_MM * 0.5;
+    let point = |lon: f64, lat: f64| {
+        let radius = surface_radius(world, lon, lat, options.exaggeration) * scale;
+        sphere_point(lon, lat, radius)
+    };
+
+    // 0 = south pole, 1.. = rings 1..rings-1 (cols vertices each), last = north pole.
+    let mut vertices = vec![point(0.0, -90.0)];
+    for i in 1..rings {
+        let lat = i as f64 / rings as f64 * 180.0 - 90.0;
+        for j in 0..cols {
+            vertices.push(point(j as f64 / cols as f64 * 360.0, lat));
+        }
+    }
+    let north = vertices.len() as u32;
+    vertices.push(point(0.0, 90.0));
+
+    let at = |i: u32, j: u32| 1 + (i - 1) * cols + j % cols;
+    let mut triangles = Vec::with_capacity((2 * rings * cols) as usize);
+    for j in 0..cols {
+        // Same winding as the textured globe: east, then north.
+        triangles.push([0, at(1, j + 1), at(1, j)]);
+        for i in 1..rings - 1 {
+            let (a, b) = (at(i, j), at(i, j + 1));
+            let (c, d) = (at(i + 1, j), at(i + 1, j + 1));
+            triangles.push([a, b, c]);
+            triangles.push([b, d, c]);
+        }
+        triangles.push([at(rings - 1, j), at(rings - 1, j + 1), north]);
+    }
+    Solid {
+        vertices,
+        triangles,
+    }
+}
+
+/// Region box as a heightfield on top of a flat-bottomed slab.
+///
+/// The box is projected locally (longitude scaled by the cosine of its mid
+/// latitude), sampled at the world's tile resolution.
+fn region_solid(world: &World, options: &MeshOptions, region: StlRegion) -> Solid {
+    let lat_min = region.lat_min.clamp(-90.0, 90.0);
+    let lat_max = region.lat_max.clamp(lat_min, 90.0);
+    let lon_min = region.lon_min;
+    let mut lon_span = region.lon_max - lon_min;
+    if lon_span <= 0.0 {
+        lon_span += 360.0;
+    }
+    let lat_span = lat_max - lat_min;
+
+    let tile_deg = 360.0 / world.width as f64;
+    let nx = ((lon_span / tile_deg).ceil() as u32).clamp(1, 2000);
+    let ny = ((lat_span / tile_deg).ceil() as u32).clamp(1, 2000);
+
+    // Ground size of the box in metres → millimetres on the print.
+    let radius_m = sphere_radius_m(world);
+    let mid_lat = ((lat_min + lat_max) * 0.5).to_radians();
+    let width_m = lon_span.to_radians() * mid_lat.cos() * radius_m;
+    let depth_m = lat_span.to_radians() * radius_m;
+    let mm_per_m = REGION_SIZE_MM as f64 / width_m.max(depth_m).max(1.0);
+
+    let heights: Vec<f32> = (0..=ny)
+        .flat_map(|y| (0..=nx).map(move |x| (x, y)))
+        .map(|(x, y)| {
+            let lon = lon_min + lon_span * x as f64 / nx as f64;
+            let lat = lat_min + lat_span * y as f64 / ny as f64;
+            sample(world, lon.rem_euclid(360.0), lat, |t| {
+                surface_elevation_m(world, t).max(0.0)
+            })
+        })
+        .collect();
+    let lowest = heights.iter().copied().fold(f32::MAX, f32::min);
+
+    // Top grid first, then an identical bottom grid at z = 0.
+    let stride = nx + 1;
+    let top_count = stride * (ny + 1);
+    let mut vertices = Vec::with_capacity(2 * top_count as usize);
+    for bottom in [false, true] {
+        for y in 0..=ny {
+            for x in 0..=nx {
+                let px = (width_m * mm_per_m) as f32 * x as f32 / nx as f32;
+                let py = (depth_m * mm_per_m) as f32 * y as f32 / ny as f32;
+                let pz = if bottom {
+                    0.0
+                } else {
+                    let h = heights[(y * stride + x) as usize] - lowest;
+                    REGION_BASE_MM + options.exaggeration * h * mm_per_m as f32
+                };
+                vertices.push([px, py, pz]);
+            }
+        }
+    }
+
+    let top = |x: u32, y: u32| y * stride + x;
+    let bottom = |x: u32, y: u32| top_count + y * stride + x;
+    let mut triangles = Vec::new();
+    for y in 0..ny {
+        for x in 0..nx {
+            // +Z up: counter-clockwise from above on top, reversed underneath.
+            let (a, b, c, d) = (top(x, y), top(x + 1, y), top(x, y + 1), top(x + 1, y + 1));
+            triangles.push([a, b, c]);
+            triangles.push([b, d, c]);
+            let (a, b, c, d) = (
+                bottom(x, y),
+                bottom(x + 1, y),
+                bottom(x, y + 1),
+                bottom(x + 1, y + 1),
+            );
+            triangles.push([a, c, b]);
+            triangles.push([b, c, d]);
+        }
+    }
+    // Side walls, walking the rim counter-clockwise seen from above.
+    let mut rim: Vec<(u32, u32)> = Vec::new();
+    rim.extend((0..nx).map(|x| (x, 0)));
+    rim.extend((0..ny).map(|y| (nx, y)));
+    rim.extend((1..=nx).rev().map(|x| (x, ny)));
+    rim.extend((1..=ny).rev().map(|y| (0, y)));
+    for k in 0..rim.len() {
+        let (x0, y0) = rim[k];
+        let (x1, y1) = rim[(k + 1) % rim.len()];
+        let (t0, t1) = (top(x0, y0), top(x1, y1));
+        let (b0, b1) = (bottom(x0, y0), bottom(x1, y1));
+        triangles.push([b0, b1, t1]);
+        triangles.push([b0, t1, t0]);
+    }
+
+    Solid {
+        vertices,
+        triangles,
+    }
+}
+
+/// Binary STL: 80-byte header, triangle count, then normal + 3 vertices each.
+fn write_stl(solid: &Solid, path: &str) {
+    let mut out: Vec<u8> = Vec::with_capacity(84 + solid.triangles.len() * 50);
+    let mut header = [b' '; 80];
+    let title = b"map-generator planet";
+    header[..title.len()].copy_from_slice(title);
+    out.extend(header);
+    out.extend((solid.triangles.len() as u32).to_le_bytes());
+    for &[a, b, c] in &solid.triangles {
+        let [p, q, r] = [a, b, c].map(|i| solid.vertices[i as usize]);
+        let n = face_normal(p, q, r);
+        let len = (n[0] * n[0] + n[1] * n[1] + n[2] * n[2])
+            .sqrt()
+            .max(f32::EPSILON);
+        for v in [n.map(|x| x / len), p, q, r] {
+            for x in v {
+                out.extend(x.to_le_bytes());
+            }
+        }
+        out.extend(0u16.to_le_bytes());
+    }
+    std::fs::write(path, out).expect("failed to write STL");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generation::test_world as world;
+    use std::collections::HashMap;
+
+    fn options() -> MeshOptions {
+        MeshOptions {
+            segments: 64,
+            ..MeshOptions::default()
+        }
+    }
+
+    /// Checks that `solid` is a closed, consistently oriented 2-manifold: every
+    /// edge is used exactly once in each direction, and no triangle is degenerate.
+    fn validate_manifold(solid: &Solid) -> Result<(), String> {
+        let mut edges: HashMap<(u32, u32), u32> = HashMap::with_capacity(solid.triangles.len() * 3);
+        for (n, &[a, b, c]) in solid.triangles.iter().enumerate() {
+            if a == b || b == c || c == a {
+                return Err(format!("triangle {n} repeats a vertex"));
+            }
+            for edge in [(a, b), (b, c), (c, a)] {
+                *edges.entry(edge).or_default() += 1;
+            }
+        }
+        for (&(a, b), &count) in &edges {
+            if count != 1 {
+                return Err(format!(
+                    "edge {a}→{b} is used {count} times in the same direction"
+                ));
+            }
+            if !edges.contains_key(&(b, a)) {
+                return Err(format!(
+                    "edge {a}→{b} has no opposite edge (hole in the mesh)"
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn globe_is_watertight() {
+        let world = world();
+        validate_manifold(&globe_solid(&world, &options())).unwrap();
+    }
+
+    #[test]
+    fn region_is_watertight() {
+        let world = world();
+        let region = StlRegion {
+            lon_min: -20.0,
+            lat_min: -10.0,
+            lon_max: 30.0,
+            lat_max: 25.0,
+        };
+        validate_manifold(&region_solid(&world, &options(), region)).unwrap();
+    }
+
+    #[test]
+    fn region_across_the_antimeridian_is_watertight() {
+        let world = world();
+        let region = StlRegion {
+            lon_min: 170.0,
+            lat_min: 40.0,
+            lon_max: -170.0,
+            lat_max: 60.0,
+        };
+        let slab = region_solid(&world, &options(), region);
+        validate_manifold(&slab).unwrap();
+        // 20° of longitude, not the 340° the other way round.
+        let width = slab.vertices.iter().map(|v| v[0]).fold(0.0, f32::max);
+        let depth = slab.vertices.iter().map(|v| v[1]).fold(0.0, f32::max);
+        assert!(width < depth * 2.0, "slab is {width} × {depth} mm");
+    }
+}
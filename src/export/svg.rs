@@ -1,55 +1,47 @@
+use super::font::{self, FAMILY};
+use super::overlay::{self, LABEL_SIZE, Label};
+use super::render::{CONTOUR_DARKNESS, CONTOUR_LEVELS, Projection, RenderOptions, tile_colors};
+use super::vector::{isolines, simplify, trace_regions};
 use crate::world::*;
+use std::collections::BTreeMap;
 use std::io::Write;
 
-// Mirror the same contour parameters used by the PNG exporter.
-const CONTOUR_LEVELS: &[f32] = &[-0.45, -0.15, 0.0, 0.15, 0.30, 0.45, 0.60, 0.75, 0.90];
-const CONTOUR_DARKNESS: f32 = 0.40;
-
-pub fn export_svg(world: &World, path: &str) {
-    let w = world.width as usize;
-    let h = world.height as usize;
+/// How the map body of `world.svg` is drawn.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum SvgMode {
+    /// Run-length encoded `<rect>` rows — a pixel-exact copy of `world.png`.
+    Rects,
+    /// Traced biome polygons and contour isolines, editable as regions.
+    Vector,
+}
 
-    // ── 1. Pre-compute elevation grid (column-major: index = q * h + r) ──────
-    let mut elevation = vec![0.0f32; w * h];
-    for tile in &world.tiles {
-        elevation[tile.q as usize * h + tile.r as usize] = tile.elevation;
-    }
+/// Options for [`export_svg`].
+#[derive(Clone, Copy, Debug)]
+pub struct SvgOptions {
+    pub mode: SvgMode,
+    /// Douglas–Peucker tolerance for vector mode, in pixels (`0.0` = exact).
+    pub simplify: f64,
+}
 
-    let elev_at = |q: i32, r: i32| -> Option<f32> {
-        if q < 0 || r < 0 || q >= world.width || r >= world.height {
-            return None;
+impl Default for SvgOptions {
+    fn default() -> Self {
+        SvgOptions {
+            mode: SvgMode::Rects,
+            simplify: 1.0,
         }
-        Some(elevation[q as usize * h + r as usize])
-    };
+    }
+}
 
-    let crosses_contour =
-        |a: f32, b: f32| -> bool { CONTOUR_LEVELS.iter().any(|&lvl| (a < lvl) != (b < lvl)) };
-
-    // ── 2. Pre-compute final pixel colours (biome + contour darkening) ────────
-    let mut pixel_color = vec![[0u8; 3]; w * h];
-    for tile in &world.tiles {
-        let mut color = biome_color(tile.biome);
-        let e = tile.elevation;
-        let is_contour = [
-            elev_at(tile.q - 1, tile.r),
-            elev_at(tile.q + 1, tile.r),
-            elev_at(tile.q, tile.r - 1),
-            elev_at(tile.q, tile.r + 1),
-        ]
-        .into_iter()
-        .flatten()
-        .any(|ne| crosses_contour(e, ne));
-
-        if is_contour {
-            color = color.map(|c| (c as f32 * (1.0 - CONTOUR_DARKNESS)) as u8);
-        }
-        pixel_color[tile.q as usize * h + tile.r as usize] = color;
+/// Writes `world.svg`.  Vector mode traces the equirectangular grid, so any
+/// other projection is drawn as rects.
+pub fn export_svg(world: &World, options: &RenderOptions, svg: &SvgOptions, path: &str) {
+    if options.projection != Projection::Equirectangular {
+        export_projected_svg(world, options, path);
+        return;
     }
+    let w = world.width as usize;
+    let h = world.height as usize;
 
-    // ── 3. Build SVG with per-row run-length encoding ─────────────────────────
-    // Each row (fixed r, varying q) is scanned left-to-right; consecutive pixels
-    // sharing the same colour are merged into a single wider <rect>.  This keeps
-    // the file size manageable (~10-40× fewer elements than one rect per pixel).
     let mut out: Vec<u8> = Vec::with_capacity(w * h * 48);
 
     writeln!(out, r#"<?xml version="1.0" encoding="UTF-8"?>"#).unwrap();
@@ -59,38 +51,13 @@ pub fn export_svg(world: &World, path: &str) {
     )
     .unwrap();
 
-    for r in 0..h {
-        let mut run_start = 0usize;
-        let mut run_color = pixel_color[0 * h + r];
-
-        for q in 1..=w {
-            // Sentinel colour that can never equal the real last colour so
-            // the final run is always flushed without special-casing after the loop.
-            let cur = if q < w {
-                pixel_color[q * h + r]
-            } else {
-                [
-                    run_color[0] ^ 0xFF,
-                    run_color[1] ^ 0xFF,
-                    run_color[2] ^ 0xFF,
-                ]
-            };
-
-            if cur != run_color {
-                let run_len = q - run_start;
-                let [cr, cg, cb] = run_color;
-                writeln!(
-                    out,
-                    r##"<rect x="{run_start}" y="{r}" width="{run_len}" height="1" fill="#{cr:02X}{cg:02X}{cb:02X}"/>"##,
-                )
-                .unwrap();
-                run_start = q;
-                run_color = cur;
-            }
-        }
+    // ── 1. Map body ───────────────────────────────────────────────────────────
+    match svg.mode {
+        SvgMode::Rects => write_rects(&mut out, world, options),
+        SvgMode::Vector => write_vector(&mut out, world, options, svg.simplify),
     }
 
-    // ── 4. Overlay equator and tropic reference lines ─────────────────────────
+    // ── 2. Overlay equator and tropic reference lines ─────────────────────────
     // Latitude → row mapping: lat = (r / height) * π − π/2
     // So r = height * (0.5 + lat_deg / 180)
     let equator_y = h as f64 * 0.5;
@@ -125,7 +92,283 @@ pub fn export_svg(world: &World, path: &str) {
         r##"<line x1="0" y1="{antarctic_y:.1}" x2="{w}" y2="{antarctic_y:.1}" stroke="#00CFFF" stroke-width="1" stroke-dasharray="6,4" opacity="0.75"/>"##
     ).unwrap();
 
+    write_overlay(&mut out, world, options);
     writeln!(out, "</svg>").unwrap();
 
     std::fs::write(path, &out).expect("failed to write SVG");
 }
+
+/// `world.svg` in another projection: run-length `<rect>` rows of the
+/// resampled map, with the reference parallels as reprojected `<path>`s.
+fn export_projected_svg(world: &World, options: &RenderOptions, path: &str) {
+    let projection = options.projection;
+    let (w, h) = projection.size(world);
+    let raster = projection.raster(world, &tile_colors(world, options));
+
+    let mut out: Vec<u8> = Vec::with_capacity(raster.len() * 48);
+    writeln!(out, r#"<?xml version="1.0" encoding="UTF-8"?>"#).unwrap();
+    writeln!(
+        out,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{w}" height="{h}" viewBox="0 0 {w} {h}">"#
+    )
+    .unwrap();
+
+    // ── 1. Map body ───────────────────────────────────────────────────────────
+    // Pixels off the globe are left transparent.
+    for (r, row) in raster.chunks(w as usize).enumerate() {
+        let mut q = 0;
+        while q < row.len() {
+            let Some([cr, cg, cb]) = row[q] else {
+                q += 1;
+                continue;
+            };
+            let run_start = q;
+            while q < row.len() && row[q] == row[run_start] {
+                q += 1;
+            }
+            let run_len = q - run_start;
+            writeln!(
+                out,
+                r##"<rect x="{run_start}" y="{r}" width="{run_len}" height="1" fill="#{cr:02X}{cg:02X}{cb:02X}"/>"##,
+            )
+            .unwrap();
+        }
+    }
+
+    // ── 2. Reference lines, reprojected ───────────────────────────────────────
+    let lines: [(f64, &str); 5] = [
+        (0.0, "#FF3333"),
+        (23.5, "#FFA500"),
+        (-23.5, "#FFA500"),
+        (66.5, "#00CFFF"),
+        (-66.5, "#00CFFF"),
+    ];
+    for (lat, stroke) in lines {
+        for line in projection.parallel(world, lat) {
+            let mut d = String::new();
+            for (k, &(x, y)) in line.iter().enumerate() {
+                d.push_str(&format!("{}{x:.1} {y:.1}", if k == 0 { "M" } else { "L" }));
+            }
+            writeln!(
+                out,
+                r#"<path d="{d}" fill="none" stroke="{stroke}" stroke-width="1" stroke-dasharray="6,4" opacity="0.75"/>"#
+            )
+            .unwrap();
+        }
+    }
+
+    write_overlay(&mut out, world, options);
+    writeln!(out, "</svg>").unwrap();
+    std::fs::write(path, &out).expect("failed to write SVG");
+}
+
+/// Per-row run-length encoded `<rect>`s of the final pixel colours.
+fn write_rects(out: &mut Vec<u8>, world: &World, options: &RenderOptions) {
+    let w = world.width as usize;
+    let h = world.height as usize;
+
+    // Final pixel colours (column-major: index = q * h + r).
+    let pixel_color = tile_colors(world, options);
+
+    // Each row (fixed r, varying q) is scanned left-to-right; consecutive pixels
+    // sharing the same colour are merged into a single wider <rect>.  This keeps
+    // the file size manageable (~10-40× fewer elements than one rect per pixel).
+    for r in 0..h {
+        let mut run_start = 0usize;
+        let mut run_color = pixel_color[r];
+
+        for q in 1..=w {
+            // Sentinel colour that can never equal the real last colour so
+            // the final run is always flushed without special-casing after the loop.
+            let cur = if q < w {
+                pixel_color[q * h + r]
+            } else {
+                [
+                    run_color[0] ^ 0xFF,
+                    run_color[1] ^ 0xFF,
+                    run_color[2] ^ 0xFF,
+                ]
+            };
+
+            if cur != run_color {
+                let run_len = q - run_start;
+                let [cr, cg, cb] = run_color;
+                writeln!(
+                    out,
+                    r##"<rect x="{run_start}" y="{r}" width="{run_len}" height="1" fill="#{cr:02X}{cg:02X}{cb:02X}"/>"##,
+                )
+                .unwrap();
+                run_start = q;
+                run_color = cur;
+            }
+        }
+    }
+}
+
+/// Traced biome regions (one `<path>` each, grouped per biome) and contour
+/// isolines as stroked `<path>`s.
+fn write_vector(out: &mut Vec<u8>, world: &World, options: &RenderOptions, tolerance: f64) {
+    let w = world.width as usize;
+    let h = world.height as usize;
+    let tile = |x: usize, y: usize| &world.tiles[x * h + y];
+
+    // ── Biome regions ─────────────────────────────────────────────────────────
+    let regions = trace_regions(w, h, |x, y| biome_order(tile(x, y).biome) as u32, tolerance);
+    let mut by_biome: BTreeMap<u32, Vec<String>> = BTreeMap::new();
+    for region in regions {
+        let mut d = String::new();
+        for ring in region.rings.iter().filter(|r| r.len() >= 3) {
+            for (k, &(x, y)) in ring.iter().enumerate() {
+                d.push_str(&format!("{}{x} {y}", if k == 0 { "M" } else { "L" }));
+            }
+            d.push('Z');
+        }
+        if d.is_empty() {
+            continue;
+        }
+        by_biome.entry(region.label).or_default().push(d);
+    }
+    let biome_of: BTreeMap<u32, Biome> = world
+        .tiles
+        .iter()
+        .map(|t| (biome_order(t.biome) as u32, t.biome))
+        .collect();
+
+    writeln!(out, r#"<g id="biomes">"#).unwrap();
+    for (label, paths) in &by_biome {
+        let biome = biome_of[label];
+        let [cr, cg, cb] = options.theme.region_color(biome);
+        let class = slug(biome_name(biome));
+        writeln!(
+            out,
+            r##"<g id="biome-{class}" class="biome {class}" fill="#{cr:02X}{cg:02X}{cb:02X}" fill-rule="evenodd">"##
+        )
+        .unwrap();
+        for d in paths {
+            writeln!(out, r#"<path d="{d}"/>"#).unwrap();
+        }
+        writeln!(out, "</g>").unwrap();
+    }
+    writeln!(out, "</g>").unwrap();
+
+    // ── Elevation contours ────────────────────────────────────────────────────
+    // The theme's ink at the raster renderer's darkening strength.
+    let [ir, ig, ib] = options.theme.ink;
+    writeln!(
+        out,
+        r##"<g id="contours" fill="none" stroke="#{ir:02X}{ig:02X}{ib:02X}" stroke-opacity="{CONTOUR_DARKNESS}" stroke-width="1" stroke-linejoin="round">"##
+    )
+    .unwrap();
+    for &level in CONTOUR_LEVELS {
+        writeln!(
+            out,
+            r#"<g id="contour-{level:.2}" class="contour" data-level="{level:.2}">"#
+        )
+        .unwrap();
+        for line in isolines(w, h, |x, y| tile(x, y).elevation, level) {
+            let line = simplify(&line, tolerance);
+            let mut d = String::new();
+            for (k, &(x, y)) in line.iter().enumerate() {
+                d.push_str(&format!("{}{x:.1} {y:.1}", if k == 0 { "M" } else { "L" }));
+            }
+            writeln!(out, r#"<path d="{d}"/>"#).unwrap();
+        }
+        writeln!(out, "</g>").unwrap();
+    }
+    writeln!(out, "</g>").unwrap();
+}
+
+/// The graticule, scale bar and north arrow `options` asks for, laid out as
+/// on `world.png`, in the theme's ink on its paper.
+fn write_overlay(out: &mut Vec<u8>, world: &World, options: &RenderOptions) {
+    let [ir, ig, ib] = options.theme.ink;
+    let [pr, pg, pb] = options.theme.paper;
+    let ink = format!("#{ir:02X}{ig:02X}{ib:02X}");
+    let paper = format!("#{pr:02X}{pg:02X}{pb:02X}");
+    // Labels are laid out by their top edge; SVG places text by its baseline.
+    let text = |out: &mut Vec<u8>, label: &Label| {
+        let (x, y) = (label.x, label.y + font::ascent(LABEL_SIZE));
+        writeln!(out, r#"<text x="{x:.1}" y="{y:.1}">{}</text>"#, label.text).unwrap();
+    };
+    let panel = |out: &mut Vec<u8>, [x, y, w, h]: [f64; 4]| {
+        writeln!(
+            out,
+            r#"<rect x="{x:.1}" y="{y:.1}" width="{w:.1}" height="{h:.1}" fill="{paper}" fill-opacity="0.8" stroke="{ink}" stroke-width="1"/>"#
+        )
+        .unwrap();
+    };
+    let font = format!(r#"font-family="{FAMILY}" font-size="{LABEL_SIZE}""#);
+
+    if let Some(spacing) = options.graticule {
+        let graticule = overlay::graticule(world, options.projection, spacing);
+        writeln!(
+            out,
+            r#"<g id="graticule" fill="none" stroke="{ink}" stroke-opacity="0.45" stroke-width="1">"#
+        )
+        .unwrap();
+        for line in &graticule.lines {
+            let mut d = String::new();
+            for (k, &(x, y)) in line.iter().enumerate() {
+                d.push_str(&format!("{}{x:.1} {y:.1}", if k == 0 { "M" } else { "L" }));
+            }
+            writeln!(out, r#"<path d="{d}"/>"#).unwrap();
+        }
+        writeln!(out, "</g>").unwrap();
+        writeln!(
+            out,
+            r#"<g id="graticule-labels" {font} fill="{ink}" stroke="{paper}" stroke-width="2" paint-order="stroke">"#
+        )
+        .unwrap();
+        for label in &graticule.labels {
+            text(out, label);
+        }
+        writeln!(out, "</g>").unwrap();
+    }
+
+    if options.scale_bar
+        && let Some(scale) = overlay::scale_bar(world, options.projection)
+    {
+        writeln!(out, r#"<g id="scale-bar" {font} fill="{ink}">"#).unwrap();
+        panel(out, scale.panel);
+        let [x, y, length, height] = scale.bar;
+        let segment = length / scale.segments as f64;
+        for k in 0..scale.segments {
+            let fill = if k % 2 == 0 { &ink } else { &paper };
+            let x = x + segment * k as f64;
+            writeln!(
+                out,
+                r#"<rect x="{x:.1}" y="{y:.1}" width="{segment:.1}" height="{height:.1}" fill="{fill}"/>"#
+            )
+            .unwrap();
+        }
+        writeln!(
+            out,
+            r#"<rect x="{x:.1}" y="{y:.1}" width="{length:.1}" height="{height:.1}" fill="none" stroke="{ink}" stroke-width="1"/>"#
+        )
+        .unwrap();
+        for label in &scale.labels {
+            text(out, label);
+        }
+        writeln!(out, "</g>").unwrap();
+    }
+
+    if options.north_arrow
+        && let Some(arrow) = overlay::north_arrow(world, options.projection)
+    {
+        writeln!(out, r#"<g id="north-arrow" {font} fill="{ink}">"#).unwrap();
+        panel(out, arrow.panel);
+        let points: Vec<String> = arrow
+            .needle
+            .iter()
+            .map(|(x, y)| format!("{x:.1},{y:.1}"))
+            .collect();
+        writeln!(out, r#"<polygon points="{}"/>"#, points.join(" ")).unwrap();
+        text(out, &arrow.label);
+        writeln!(out, "</g>").unwrap();
+    }
+}
+
+/// `"Deep Ocean"` → `"deep-ocean"`, for SVG ids and classes.
+fn slug(name: &str) -> String {
+    name.to_lowercase().replace(' ', "-")
+}
@@ -0,0 +1,329 @@
+/// Render themes: the palette a map is painted with and the ink work drawn
+/// over it.
+///
+/// A theme is picked by name (`classic`, `atlas`, `satellite`, `monochrome`,
+/// `hypsometric`) or read from a JSON file holding any of the [`Theme`]
+/// fields.  A file may start from a built-in theme with `"base": "<name>"`
+/// and change only what differs.
+use super::render::{RenderMode, RenderStyle, mix};
+use crate::world::*;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// What the base colour of a tile follows.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Palette {
+    /// The biome colours, with the theme's replacements.
+    Biome,
+    /// The biome colours turned to grey of the same lightness.
+    Gray,
+    /// Height alone: [`Theme::land_ramp`] above sea level and
+    /// [`Theme::water_ramp`] below it.
+    Elevation,
+}
+
+/// Colours as `[r, g, b]` at heights (land) or depths (water) in metres;
+/// values in between are interpolated.
+pub type Ramp = Vec<(f32, [u8; 3])>;
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Theme {
+    pub name: String,
+    pub palette: Palette,
+    /// Replacement colours for single biomes, by biome (`"DeepOcean"`, …).
+    pub biome_colors: HashMap<Biome, [u8; 3]>,
+    /// Land colours by surface height above sea level.
+    pub land_ramp: Ramp,
+    /// Water colours by depth, for the elevation palette and `--ocean-tint`.
+    pub water_ramp: Ramp,
+    /// Colour every tile is washed towards, and how far (`0.0` = not at all).
+    pub paper: [u8; 3],
+    pub paper_mix: f32,
+    /// Colour of contours, coastlines, hatching and stippling.
+    pub ink: [u8; 3],
+    /// Relief drawn when `--style` is not given.
+    pub style: RenderStyle,
+    /// Colour oceans by depth even without `--ocean-tint`.
+    pub ocean_tint: bool,
+    /// Ink line along the coast, on the land side.
+    pub coastline: bool,
+    /// Ink hatching over high ground, crossed over the highest.
+    pub hatching: bool,
+    /// Ink dots in the water along the coast, thinning out to sea.
+    pub stippling: bool,
+    /// Strength of the mottled brightness variation (`0.0` = none).
+    pub variation: f32,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Theme {
+            name: "classic".into(),
+            palette: Palette::Biome,
+            biome_colors: HashMap::new(),
+            land_ramp: vec![
+                (0.0, [90, 150, 90]),
+                (200.0, [140, 185, 105]),
+                (500.0, [205, 205, 125]),
+                (1000.0, [215, 180, 115]),
+                (2000.0, [175, 125, 85]),
+                (3000.0, [145, 105, 85]),
+                (4500.0, [215, 210, 205]),
+                (6000.0, [255, 255, 255]),
+            ],
+            water_ramp: vec![
+                (0.0, [125, 195, 235]),
+                (200.0, [85, 160, 225]),
+                (1000.0, [50, 120, 210]),
+                (3000.0, [25, 70, 170]),
+                (6000.0, [10, 30, 110]),
+                (10000.0, [5, 10, 60]),
+            ],
+            paper: [255, 255, 255],
+            paper_mix: 0.0,
+            ink: [0, 0, 0],
+            style: RenderStyle::Contours,
+            ocean_tint: false,
+            coastline: false,
+            hatching: false,
+            stippling: false,
+            variation: 0.0,
+        }
+    }
+}
+
+/// Names of the built-in themes, for messages.
+pub const THEMES: [&str; 5] = ["classic", "atlas", "satellite", "monochrome", "hypsometric"];
+
+impl Theme {
+    /// Built-in theme called `name`.
+    pub fn named(name: &str) -> Option<Theme> {
+        let classic = Theme::default();
+        let theme = match name {
+            "classic" => classic,
+            "atlas" => Theme {
+                name: "atlas".into(),
+                // Washed-out sea blues; the biome blues turn violet on paper.
+                biome_colors: HashMap::from([
+                    (Biome::DeepOcean, [60, 105, 135]),
+                    (Biome::Ocean, [85, 135, 160]),
+                    (Biome::Shelf, [125, 165, 175]),
+                    (Biome::Trench, [45, 80, 115]),
+                    (Biome::Fjord, [85, 135, 160]),
+                ]),
+                paper: [236, 220, 182],
+                paper_mix: 0.65,
+                ink: [80, 55, 35],
+                coastline: true,
+                hatching: true,
+                stippling: true,
+                variation: 0.05,
+                ..classic
+            },
+            "satellite" => Theme {
+                name: "satellite".into(),
+                biome_colors: HashMap::from([
+                    (Biome::Reef, [45, 120, 135]),
+                    (Biome::Beach, [195, 180, 140]),
+                    (Biome::Wetland, [60, 80, 50]),
+                    (Biome::IceCap, [240, 244, 248]),
+                    (Biome::Tundra, [125, 120, 100]),
+                    (Biome::Taiga, [40, 62, 42]),
+                    (Biome::Shrubland, [130, 125, 80]),
+                    (Biome::Plain, [95, 120, 60]),
+                    (Biome::Forest, [38, 72, 32]),
+                    (Biome::Savanna, [160, 145, 90]),
+                    (Biome::Desert, [210, 180, 130]),
+                    (Biome::Jungle, [28, 60, 25]),
+                    (Biome::Mountain, [115, 105, 95]),
+                    (Biome::Snow, [235, 238, 242]),
+                    (Biome::Moraine, [130, 122, 110]),
+                    (Biome::AshLand, [80, 72, 68]),
+                    (Biome::GlacialPlain, [215, 225, 235]),
+                ]),
+                water_ramp: vec![
+                    (0.0, [45, 95, 120]),
+                    (200.0, [25, 65, 105]),
+                    (1000.0, [15, 40, 80]),
+                    (6000.0, [8, 20, 50]),
+                ],
+                style: RenderStyle::Hillshade,
+                ocean_tint: true,
+                variation: 0.12,
+                ..classic
+            },
+            "monochrome" => Theme {
+                name: "monochrome".into(),
+                palette: Palette::Gray,
+                paper_mix: 0.45,
+                coastline: true,
+                hatching: true,
+                ..classic
+            },
+            "hypsometric" => Theme {
+                name: "hypsometric".into(),
+                palette: Palette::Elevation,
+                ink: [70, 50, 40],
+                ..classic
+            },
+            _ => return None,
+        };
+        Some(theme)
+    }
+
+    /// Theme from the JSON of a theme file.  Fields left out come from the
+    /// theme named by `"base"`, or from `classic`; `biome_colors` is merged
+    /// biome by biome, so a file can change one colour and keep the rest.
+    pub fn from_json(text: &str) -> Result<Theme, String> {
+        let mut fields: serde_json::Map<String, serde_json::Value> =
+            serde_json::from_str(text).map_err(|e| e.to_string())?;
+        let base = match fields.remove("base") {
+            Some(serde_json::Value::String(name)) => {
+                Theme::named(&name).ok_or(format!("unknown base theme '{name}'"))?
+            }
+            Some(_) => return Err("\"base\" must be a theme name".into()),
+            None => Theme::default(),
+        };
+        let serde_json::Value::Object(mut merged) = serde_json::to_value(base).unwrap() else {
+            unreachable!("a theme serializes to an object");
+        };
+        if let (
+            Some(serde_json::Value::Object(base_colors)),
+            Some(serde_json::Value::Object(colors)),
+        ) = (merged.get("biome_colors"), fields.get_mut("biome_colors"))
+        {
+            for (biome, color) in base_colors {
+                colors.entry(biome.clone()).or_insert_with(|| color.clone());
+            }
+        }
+        merged.extend(fields);
+        serde_json::from_value(serde_json::Value::Object(merged)).map_err(|e| e.to_string())
+    }
+
+    /// The theme's colour of biome `b`.
+    pub fn biome_color(&self, b: Biome) -> [u8; 3] {
+        self.biome_colors
+            .get(&b)
+            .copied()
+            .unwrap_or_else(|| biome_color(b))
+    }
+
+    /// Palette colour of a point: its biome (and ecotone neighbour under
+    /// [`RenderMode::Blend`]) and surface height above sea level in metres,
+    /// negative under water.
+    pub fn base_color(
+        &self,
+        mode: RenderMode,
+        biome: Biome,
+        secondary: Biome,
+        blend: f32,
+        height_m: f32,
+    ) -> [u8; 3] {
+        let color = |b: Biome| match self.palette {
+            Palette::Gray => gray(self.biome_color(b)),
+            _ => self.biome_color(b),
+        };
+        match (self.palette, mode) {
+            (Palette::Elevation, _) if height_m < 0.0 => ramp(&self.water_ramp, -height_m),
+            (Palette::Elevation, _) => ramp(&self.land_ramp, height_m),
+            (_, RenderMode::Flat) => color(biome),
+            (_, RenderMode::Blend) => mix(color(biome), color(secondary), blend),
+        }
+    }
+
+    /// Colour of biome `b` where a whole region shares one colour, as in
+    /// vector SVGs.  The elevation palette falls back to the biome colours.
+    pub fn region_color(&self, b: Biome) -> [u8; 3] {
+        self.on_paper(match self.palette {
+            Palette::Gray => gray(self.biome_color(b)),
+            _ => self.biome_color(b),
+        })
+    }
+
+    /// Depth colour of open ocean `depth_m` metres deep; `None` for other
+    /// biomes.
+    pub fn ocean_tint(&self, biome: Biome, depth_m: f32) -> Option<[u8; 3]> {
+        matches!(
+            biome,
+            Biome::DeepOcean | Biome::Ocean | Biome::Shelf | Biome::Trench
+        )
+        .then(|| ramp(&self.water_ramp, depth_m.max(0.0)))
+    }
+
+    /// `color` washed towards the paper.
+    pub fn on_paper(&self, color: [u8; 3]) -> [u8; 3] {
+        if self.paper_mix <= 0.0 {
+            return color;
+        }
+        mix(color, self.paper, self.paper_mix)
+    }
+
+    /// `color` pulled towards the ink by `strength`, rounding down like the
+    /// contour darkening always has.
+    pub fn inked(&self, color: [u8; 3], strength: f32) -> [u8; 3] {
+        std::array::from_fn(|k| {
+            (color[k] as f32 * (1.0 - strength) + self.ink[k] as f32 * strength) as u8
+        })
+    }
+}
+
+/// Colour of `value` on `stops`, clamped to the first and last stop.
+pub fn ramp(stops: &[(f32, [u8; 3])], value: f32) -> [u8; 3] {
+    let Some(k) = stops.iter().position(|&(v, _)| value < v) else {
+        return stops.last().map_or([0, 0, 0], |s| s.1);
+    };
+    if k == 0 {
+        return stops[0].1;
+    }
+    let ((v0, c0), (v1, c1)) = (stops[k - 1], stops[k]);
+    mix(c0, c1, (value - v0) / (v1 - v0))
+}
+
+/// Grey of the same lightness (Rec. 601 luma).
+fn gray(c: [u8; 3]) -> [u8; 3] {
+    let y = (0.299 * c[0] as f32 + 0.587 * c[1] as f32 + 0.114 * c[2] as f32).round() as u8;
+    [y, y, y]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn theme_files_override_their_base() {
+        let theme = Theme::from_json(
+            r#"{ "base": "atlas", "ink": [230, 220, 160], "biome_colors": { "Desert": [120, 90, 60] } }"#,
+        )
+        .unwrap();
+        let atlas = Theme::named("atlas").unwrap();
+        assert_eq!(theme.ink, [230, 220, 160]);
+        assert_eq!(theme.biome_color(Biome::Desert), [120, 90, 60]);
+        // Everything else is the base's, down to its other biome colours.
+        assert_eq!(theme.name, "atlas");
+        assert_eq!(theme.paper, atlas.paper);
+        assert_eq!(theme.paper_mix, atlas.paper_mix);
+        assert!(theme.hatching && theme.stippling);
+        assert_eq!(
+            theme.biome_color(Biome::DeepOcean),
+            atlas.biome_color(Biome::DeepOcean)
+        );
+        assert_eq!(theme.biome_color(Biome::Forest), biome_color(Biome::Forest));
+    }
+
+    #[test]
+    fn theme_files_without_a_base_start_from_classic() {
+        let theme = Theme::from_json(r#"{ "palette": "gray", "variation": 0.2 }"#).unwrap();
+        assert_eq!(theme.palette, Palette::Gray);
+        assert_eq!(theme.variation, 0.2);
+        assert_eq!(theme.name, "classic");
+        assert_eq!(theme.land_ramp, Theme::default().land_ramp);
+
+        assert!(Theme::from_json(r#"{ "base": "neon" }"#).is_err());
+        assert!(Theme::from_json(r#"{ "base": 3 }"#).is_err());
+        assert!(Theme::from_json(r#"{ "palette": "sepia" }"#).is_err());
+        assert!(Theme::from_json(r#"{ "biome_colors": [1, 2, 3] }"#).is_err());
+        assert!(Theme::from_json("not json").is_err());
+    }
+}
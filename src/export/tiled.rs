@@ -0,0 +1,546 @@
+/// Maps for the [Tiled](https://www.mapeditor.org/) level editor.
+///
+/// One map is written twice, as TMX (XML) and TMJ (JSON), with the same
+/// tileset embedded in both.  The map is north-up and centred on the prime
+/// meridian like the GIS exports; each map tile covers a block of world tiles
+/// when the map is smaller than the world.  Generation has no hydrology, so
+/// the rivers on the overlay are traced here from the terrain and rainfall.
+use super::geo::raster_tile;
+use super::heightmap::surface_elevation_m;
+use crate::volcanism::Volcano;
+use crate::world::*;
+use image::{Rgba, RgbaImage};
+use serde_json::json;
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap};
+use std::f64::consts::PI;
+use std::fmt::Write as _;
+
+/// Edge of one tile in the tileset image, in pixels.
+const TILE_PX: u32 = 16;
+/// Tiles per row of the tileset image.
+const TILESET_COLUMNS: u32 = 8;
+/// Catchment a land tile needs to carry a river, in km² of ground that
+/// receives 1 m of rain a year (wetter ground counts for more).
+const RIVER_CATCHMENT_KM2: f64 = 20_000.0;
+
+/// Elevation bands: upper bound in metres (surface, ice included), name and
+/// colour.  Local tile ids follow the biome tiles.
+const ELEVATION_BANDS: &[(f32, &str, [u8; 3])] = &[
+    (-4000.0, "Abyss (below -4000 m)", [8, 24, 88]),
+    (-1000.0, "Deep sea (-4000 to -1000 m)", [24, 64, 150]),
+    (-200.0, "Slope (-1000 to -200 m)", [50, 110, 200]),
+    (0.0, "Shallows (-200 to 0 m)", [110, 170, 230]),
+    (200.0, "Lowland (0 to 200 m)", [70, 140, 70]),
+    (1000.0, "Hills (200 to 1000 m)", [150, 170, 80]),
+    (2000.0, "Highland (1000 to 2000 m)", [170, 130, 70]),
+    (4000.0, "Mountain (2000 to 4000 m)", [130, 90, 60]),
+    (f32::INFINITY, "Peaks (above 4000 m)", [240, 240, 240]),
+];
+
+/// Features on the overlay layer, in order of precedence; local tile ids
+/// follow the elevation bands.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Overlay {
+    ActiveVolcano,
+    ExtinctVolcano,
+    River,
+    Coast,
+    Ice,
+}
+
+impl Overlay {
+    const ALL: [Overlay; 5] = [
+        Overlay::ActiveVolcano,
+        Overlay::ExtinctVolcano,
+        Overlay::River,
+        Overlay::Coast,
+        Overlay::Ice,
+    ];
+
+    fn name(self) -> &'static str {
+        match self {
+            Overlay::ActiveVolcano => "Active volcano",
+            Overlay::ExtinctVolcano => "Extinct volcano",
+            Overlay::River => "River",
+            Overlay::Coast => "Coast",
+            Overlay::Ice => "Ice sheet",
+        }
+    }
+}
+
+/// Options for [`export_tiled`].
+#[derive(Clone, Copy, Debug)]
+pub struct TiledOptions {
+    /// Map width in tiles; the height follows the world's aspect ratio.
+    /// Capped at the world's own width.
+    pub columns: u32,
+}
+
+impl Default for TiledOptions {
+    fn default() -> Self {
+        TiledOptions { columns: 480 }
+    }
+}
+
+/// A tileset tile: local id, class (`biome`, `elevation` or `overlay`) and name.
+struct TileInfo {
+    id: u32,
+    class: &'static str,
+    name: &'static str,
+}
+
+fn tileset() -> Vec<TileInfo> {
+    let biomes = BIOMES.iter().map(|&b| ("biome", biome_name(b)));
+    let bands = ELEVATION_BANDS
+        .iter()
+        .map(|&(_, name, _)| ("elevation", name));
+    let overlays = Overlay::ALL.iter().map(|o| ("overlay", o.name()));
+    biomes
+        .chain(bands)
+        .chain(overlays)
+        .enumerate()
+        .map(|(id, (class, name))| TileInfo {
+            id: id as u32,
+            class,
+            name,
+        })
+        .collect()
+}
+
+fn band_id(band: usize) -> u32 {
+    (BIOMES.len() + band) as u32
+}
+
+fn overlay_id(overlay: Overlay) -> u32 {
+    let k = Overlay::ALL.iter().position(|&o| o == overlay).unwrap();
+    (BIOMES.len() + ELEVATION_BANDS.len() + k) as u32
+}
+
+/// Tiles that carry a river, by flow accumulation over the terrain surface.
+///
+/// Depressions are filled by flooding the land inwards from the sea (a
+/// priority flood), so every land tile drains to the coast through the tile
+/// it was reached from; a world without sea drains to its lowest tile.  Each
+/// tile passes on the rain falling on its catchment, and a tile is a river
+/// once that exceeds [`RIVER_CATCHMENT_KM2`] and some of it comes from
+/// upstream.  Ice-covered tiles carry the flow but are never rivers.
+fn rivers(world: &World) -> Vec<bool> {
+    let grid = world.grid();
+    let n = world.tiles.len();
+    let is_land = |i: usize| world.tiles[i].elevation >= world.sea_level;
+    // Flood levels in centimetres, so they order exactly.
+    let level = |i: usize| (surface_elevation_m(world, &world.tiles[i]) * 100.0).round() as i64;
+
+    let mut reached = vec![false; n];
+    let mut heap = BinaryHeap::new();
+    let outlets: Vec<usize> = match (0..n).filter(|&i| !is_land(i)).collect::<Vec<_>>() {
+        sea if sea.is_empty() => (0..n).min_by_key(|&i| level(i)).into_iter().collect(),
+        sea => sea,
+    };
+    for i in outlets {
+        reached[i] = true;
+        heap.push(Reverse((level(i), i)));
+    }
+    let mut downstream: Vec<Option<usize>> = vec![None; n];
+    let mut order = Vec::with_capacity(n);
+    while let Some(Reverse((fill, i))) = heap.pop() {
+        order.push(i);
+        for j in grid.neighbors8(i) {
+            if !reached[j] {
+                reached[j] = true;
+                downstream[j] = Some(i);
+                heap.push(Reverse((level(j).max(fill), j)));
+            }
+        }
+    }
+
+    // Rain on each land tile, in km² at 1 m a year.
+    let radius_km = world.circumference_km as f64 / (2.0 * PI);
+    let cell_km2 =
+        (2.0 * PI * radius_km / world.width as f64) * (PI * radius_km / world.height as f64);
+    let rain: Vec<f64> = world
+        .tiles
+        .iter()
+        .enumerate()
+        .map(|(i, tile)| {
+            if !is_land(i) {
+                return 0.0;
+            }
+            let (_, lat) = grid.lon_lat(tile.q, tile.r);
+            cell_km2 * lat.cos() * tile.precipitation_mm.max(0.0) as f64 / 1000.0
+        })
+        .collect();
+    // Upstream tiles were reached later, so they are passed on first.
+    let mut flow = rain.clone();
+    for &i in order.iter().rev() {
+        if let Some(d) = downstream[i] {
+            flow[d] += flow[i];
+        }
+    }
+    (0..n)
+        .map(|i| {
+            is_land(i)
+                && world.tiles[i].ice_thickness <= 0.0
+                && flow[i] >= RIVER_CATCHMENT_KM2
+                && flow[i] > rain[i]
+        })
+        .collect()
+}
+
+/// The three layers as Tiled global ids (local id + 1, `0` = empty), row by row.
+struct Layers {
+    width: u32,
+    height: u32,
+    biome: Vec<u32>,
+    elevation: Vec<u32>,
+    overlay: Vec<u32>,
+}
+
+fn layers(world: &World, options: &TiledOptions) -> Layers {
+    let (w, h) = (world.width as u32, world.height as u32);
+    let width = options.columns.clamp(1, w);
+    let height = ((width as f64 * h as f64 / w as f64).round() as u32).clamp(1, h);
+    let vents: HashMap<usize, &Volcano> = world
+        .volcanoes
+        .iter()
+        .map(|v| ((v.q * world.height + v.r) as usize, v))
+        .collect();
+    let river = rivers(world);
+
+    let mut out = Layers {
+        width,
+        height,
+        biome: Vec::with_capacity((width * height) as usize),
+        elevation: Vec::with_capacity((width * height) as usize),
+        overlay: Vec::with_capacity((width * height) as usize),
+    };
+    for cy in 0..height {
+        for cx in 0..width {
+            // World tiles under this map tile.
+            let xs = cx * w / width..((cx + 1) * w / width).max(cx * w / width + 1);
+            let ys = cy * h / height..((cy + 1) * h / height).max(cy * h / height + 1);
+            let block: Vec<usize> = ys
+                .flat_map(|y| xs.clone().map(move |x| (x, y)))
+                .map(|(x, y)| raster_tile(world, x as i32, y as i32))
+                .collect();
+
+            // Most common biome; ties go to the lower biome order.
+            let mut counts = [0u32; BIOMES.len()];
+            for &i in &block {
+                counts[biome_order(world.tiles[i].biome) as usize] += 1;
+            }
+            let biome = (0..counts.len()).rev().max_by_key(|&b| counts[b]).unwrap();
+            out.biome.push(biome as u32 + 1);
+
+            let mean_m = block
+                .iter()
+                .map(|&i| surface_elevation_m(world, &world.tiles[i]))
+                .sum::<f32>()
+                / block.len() as f32;
+            let band = ELEVATION_BANDS
+                .iter()
+                .position(|&(top, _, _)| mean_m < top)
+                .unwrap_or(ELEVATION_BANDS.len() - 1);
+            out.elevation.push(band_id(band) + 1);
+
+            // The liveliest volcano in the block, if any.
+            let volcano = block
+                .iter()
+                .filter_map(|i| vents.get(i))
+                .max_by(|a, b| a.activity.total_cmp(&b.activity));
+            let land = block
+                .iter()
+                .filter(|&&i| world.tiles[i].elevation >= world.sea_level)
+                .count();
+            let ice = block
+                .iter()
+                .filter(|&&i| world.tiles[i].ice_thickness > 0.0)
+                .count();
+            let overlay = match volcano {
+                Some(v) if v.activity > 0.0 => Some(Overlay::ActiveVolcano),
+                Some(_) => Some(Overlay::ExtinctVolcano),
+                None if block.iter().any(|&i| river[i]) => Some(Overlay::River),
+                None if land > 0 && land < block.len() => Some(Overlay::Coast),
+                None if ice * 2 > block.len() => Some(Overlay::Ice),
+                None => None,
+            };
+            out.overlay.push(overlay.map_or(0, |o| overlay_id(o) + 1));
+        }
+    }
+    out
+}
+
+/// Draws the tileset image: flat swatches for biomes and elevation bands,
+/// and transparent symbols for the overlay features.
+fn tileset_image(tiles: &[TileInfo]) -> RgbaImage {
+    let rows = (tiles.len() as u32).div_ceil(TILESET_COLUMNS);
+    let mut img = RgbaImage::new(TILESET_COLUMNS * TILE_PX, rows * TILE_PX);
+    let last = TILE_PX - 1;
+    for tile in tiles {
+        let (x0, y0) = (
+            tile.id % TILESET_COLUMNS * TILE_PX,
+            tile.id / TILESET_COLUMNS * TILE_PX,
+        );
+        let k = tile.id as usize;
+        for y in 0..TILE_PX {
+            for x in 0..TILE_PX {
+                let pixel = if k < BIOMES.len() {
+                    let [r, g, b] = biome_color(BIOMES[k]);
+                    Some([r, g, b, 255])
+                } else if k < BIOMES.len() + ELEVATION_BANDS.len() {
+                    let [r, g, b] = ELEVATION_BANDS[k - BIOMES.len()].2;
+                    Some([r, g, b, 255])
+                } else {
+                    let overlay = Overlay::ALL[k - BIOMES.len() - ELEVATION_BANDS.len()];
+                    // Triangle for volcanoes: apex at the top centre.
+                    let in_cone = (3..=12).contains(&y) && (2 * x).abs_diff(last) <= y - 2;
+                    match overlay {
+                        Overlay::ActiveVolcano if in_cone => Some([220, 40, 20, 255]),
+                        Overlay::ExtinctVolcano if in_cone => Some([90, 80, 75, 255]),
+                        // A wavy stroke across the tile.
+                        Overlay::River if (y as i32 - 7 - (x / 4 % 2) as i32).abs() <= 1 => {
+                            Some([40, 110, 220, 230])
+                        }
+                        Overlay::Coast if x == 0 || y == 0 || x == last || y == last => {
+                            Some([255, 255, 255, 200])
+                        }
+                        Overlay::Ice if (x + y) % 4 == 0 => Some([235, 245, 255, 170]),
+                        _ => None,
+                    }
+                };
+                if let Some(p) = pixel {
+                    img.put_pixel(x0 + x, y0 + y, Rgba(p));
+                }
+            }
+        }
+    }
+    img
+}
+
+/// Writes a Tiled map of `world` into `dir`:
+///
+/// - `tileset.png` — one tile per biome, elevation band and overlay feature
+/// - `world.tmx`   — the map as XML, layers in CSV
+/// - `world.tmj`   — the same map as JSON
+///
+/// Layers are `biome` (most common biome in each block), `elevation` (band
+/// of the mean surface height, hidden by default) and `overlay` (volcanoes,
+/// rivers, coasts and ice sheets, empty elsewhere).  The map carries the world's
+/// seed, planet type and sea level as custom properties; every tile carries
+/// its name.
+pub fn export_tiled(world: &World, options: &TiledOptions, dir: &str) {
+    std::fs::create_dir_all(dir).expect("failed to create tiled directory");
+    let tiles = tileset();
+    let image = tileset_image(&tiles);
+    image
+        .save(format!("{dir}/tileset.png"))
+        .expect("failed to write tileset");
+    let layers = layers(world, options);
+    let planet_type = format!("{:?}", world.planet_type);
+    let layer_list: [(&str, &Vec<u32>, bool); 3] = [
+        ("biome", &layers.biome, true),
+        ("elevation", &layers.elevation, false),
+        ("overlay", &layers.overlay, true),
+    ];
+
+    // ── TMX ───────────────────────────────────────────────────────────────────
+    let mut tmx = String::new();
+    writeln!(tmx, r#"<?xml version="1.0" encoding="UTF-8"?>"#).unwrap();
+    writeln!(
+        tmx,
+        r#"<map version="1.10" tiledversion="1.10.2" orientation="orthogonal" renderorder="right-down" width="{}" height="{}" tilewidth="{TILE_PX}" tileheight="{TILE_PX}" infinite="0" nextlayerid="4" nextobjectid="1">"#,
+        layers.width, layers.height
+    )
+    .unwrap();
+    writeln!(tmx, " <properties>").unwrap();
+    writeln!(
+        tmx,
+        r#"  <property name="seed" type="int" value="{}"/>"#,
+        world.seed
+    )
+    .unwrap();
+    writeln!(
+        tmx,
+        r#"  <property name="planet_type" value="{planet_type}"/>"#
+    )
+    .unwrap();
+    writeln!(
+        tmx,
+        r#"  <property name="sea_level" type="float" value="{}"/>"#,
+        world.sea_level
+    )
+    .unwrap();
+    writeln!(tmx, " </properties>").unwrap();
+    writeln!(
+        tmx,
+        r#" <tileset firstgid="1" name="biomes" tilewidth="{TILE_PX}" tileheight="{TILE_PX}" tilecount="{}" columns="{TILESET_COLUMNS}">"#,
+        tiles.len()
+    )
+    .unwrap();
+    writeln!(
+        tmx,
+        r#"  <image source="tileset.png" width="{}" height="{}"/>"#,
+        image.width(),
+        image.height()
+    )
+    .unwrap();
+    for tile in &tiles {
+        writeln!(
+            tmx,
+            r#"  <tile id="{}" type="{}"><properties><property name="name" value="{}"/></properties></tile>"#,
+            tile.id, tile.class, tile.name
+        )
+        .unwrap();
+    }
+    writeln!(tmx, " </tileset>").unwrap();
+    for (id, (name, data, visible)) in layer_list.iter().enumerate() {
+        let visible = if *visible { "" } else { r#" visible="0""# };
+        writeln!(
+            tmx,
+            r#" <layer id="{}" name="{name}" width="{}" height="{}"{visible}>"#,
+            id + 1,
+            layers.width,
+            layers.height
+        )
+        .unwrap();
+        writeln!(tmx, r#"  <data encoding="csv">"#).unwrap();
+        for (y, row) in data.chunks(layers.width as usize).enumerate() {
+            let row: Vec<String> = row.iter().map(|g| g.to_string()).collect();
+            let comma = if y + 1 < layers.height as usize {
+                ","
+            } else {
+                ""
+            };
+            writeln!(tmx, "{}{comma}", row.join(",")).unwrap();
+        }
+        writeln!(tmx, "  </data>").unwrap();
+        writeln!(tmx, " </layer>").unwrap();
+    }
+    writeln!(tmx, "</map>").unwrap();
+    std::fs::write(format!("{dir}/world.tmx"), tmx).expect("failed to write TMX");
+
+    // ── TMJ ───────────────────────────────────────────────────────────────────
+    let tmj = json!({
+        "type": "map",
+        "version": "1.10",
+        "tiledversion": "1.10.2",
+        "orientation": "orthogonal",
+        "renderorder": "right-down",
+        "width": layers.width,
+        "height": layers.height,
+        "tilewidth": TILE_PX,
+        "tileheight": TILE_PX,
+        "infinite": false,
+        "nextlayerid": 4,
+        "nextobjectid": 1,
+        "properties": [
+            { "name": "seed", "type": "int", "value": world.seed },
+            { "name": "planet_type", "type": "string", "value": planet_type },
+            { "name": "sea_level", "type": "float", "value": world.sea_level },
+        ],
+        "tilesets": [{
+            "firstgid": 1,
+            "name": "biomes",
+            "tilewidth": TILE_PX,
+            "tileheight": TILE_PX,
+            "tilecount": tiles.len(),
+            "columns": TILESET_COLUMNS,
+            "margin": 0,
+            "spacing": 0,
+            "image": "tileset.png",
+            "imagewidth": image.width(),
+            "imageheight": image.height(),
+            "tiles": tiles.iter().map(|tile| json!({
+                "id": tile.id,
+                "type": tile.class,
+                "properties": [{ "name": "name", "type": "string", "value": tile.name }],
+            })).collect::<Vec<_>>(),
+        }],
+        "layers": layer_list.iter().enumerate().map(|(id, (name, data, visible))| json!({
+            "id": id + 1,
+            "name": name,
+            "type": "tilelayer",
+            "x": 0,
+            "y": 0,
+            "width": layers.width,
+            "height": layers.height,
+            "opacity": 1,
+            "visible": visible,
+            "data": data,
+        })).collect::<Vec<_>>(),
+    });
+    std::fs::write(format!("{dir}/world.tmj"), tmj.to_string()).expect("failed to write TMJ");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generation::test_world;
+
+    #[test]
+    fn layers_cover_the_map_with_gids_of_their_class() {
+        let world = test_world();
+        let tiles = tileset();
+        let class = |gid: u32| tiles[gid as usize - 1].class;
+        for (columns, size) in [(32, (32, 18)), (128, (128, 72)), (1000, (128, 72))] {
+            let layers = layers(&world, &TiledOptions { columns });
+            assert_eq!((layers.width, layers.height), size);
+            let cells = (layers.width * layers.height) as usize;
+            assert_eq!(layers.biome.len(), cells);
+            assert_eq!(layers.elevation.len(), cells);
+            assert_eq!(layers.overlay.len(), cells);
+            assert!(layers.biome.iter().all(|&g| class(g) == "biome"));
+            assert!(layers.elevation.iter().all(|&g| class(g) == "elevation"));
+            assert!(
+                layers
+                    .overlay
+                    .iter()
+                    .all(|&g| g == 0 || class(g) == "overlay")
+            );
+        }
+        // At full size every map tile is one world tile.
+        let layers = layers(&world, &TiledOptions { columns: 128 });
+        for (k, &gid) in layers.biome.iter().enumerate() {
+            let (x, y) = ((k % 128) as i32, (k / 128) as i32);
+            let biome = world.tiles[raster_tile(&world, x, y)].biome;
+            assert_eq!(gid, biome_order(biome) as u32 + 1);
+        }
+    }
+
+    #[test]
+    fn rivers_gather_downhill_to_the_sea() {
+        // A ridge along 180° falling to a sea strip along 0°, under steady rain.
+        let mut world = test_world();
+        world.sea_level = 0.0;
+        for tile in &mut world.tiles {
+            let from_sea = tile.q.min(world.width - tile.q);
+            tile.elevation = if from_sea == 0 {
+                -0.1
+            } else {
+                0.01 * from_sea as f32
+            };
+            tile.elevation_m = tile.elevation * world.metres_per_unit;
+            tile.ice_thickness = 0.0;
+            tile.precipitation_mm = 1000.0;
+        }
+        let river = rivers(&world);
+        let h = world.height;
+        let at = |q: i32, r: i32| river[(q * h + r) as usize];
+        for r in 0..h {
+            assert!(!at(0, r), "no rivers at sea");
+            assert!(!at(world.width / 2, r), "no rivers on the ridge");
+        }
+        // Away from the poles, the tiles before the sea have all the rain of
+        // the slope behind them.
+        for r in h / 4..3 * h / 4 {
+            assert!(at(1, r) && at(world.width - 1, r), "row {r}");
+        }
+
+        // Ice keeps the rain flowing but hides the river.
+        for tile in &mut world.tiles {
+            tile.ice_thickness = 0.1;
+        }
+        assert!(rivers(&world).iter().all(|&r| !r));
+    }
+}
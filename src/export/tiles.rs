@@ -0,0 +1,539 @@
+use super::geo::{sample, tile_index};
+use super::render::{
+    CONTOUR_DARKNESS, CONTOUR_LEVELS, RenderMode, RenderOptions, RenderStyle, hillshade, shade,
+    tile_colors, vary,
+};
+use super::theme::Palette;
+use crate::bathymetry::SeaFloor;
+use crate::biome::{choose_biome, planet_offsets};
+use crate::ecotone;
+use crate::glaciation::GlacialFeature;
+use crate::grid::{Grid, unit_vector};
+use crate::noise::{EARTH_CIRCUMFERENCE_KM, fbm, fbm_detail};
+use crate::volcanism::VolcanicFeature;
+use crate::world::*;
+use image::{Rgb, RgbImage};
+use noise::Perlin;
+use std::f64::consts::PI;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// Width and height of one tile in pixels.
+pub const TILE_SIZE: u32 = 256;
+/// Deepest zoom level rendered: pixels of about a centimetre on an
+/// Earth-sized world.
+pub const MAX_ZOOM: u32 = 24;
+/// Cap on the octaves added to a noise field, whatever the zoom.
+const MAX_EXTRA_OCTAVES: i32 = 24;
+/// Wavelength, in world tiles, of the coarsest octave of the warp that hides
+/// the tile grid in borders copied from the world.
+const BORDER_WARP_TILES: f64 = 4.0;
+/// Peak displacement of the border warp, in world tiles.
+const BORDER_WARP_AMPLITUDE: f64 = 1.5;
+
+/// Slippy-map tiling scheme: how `z/x/y` tiles cover the globe.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum TileScheme {
+    /// Web Mercator (EPSG:3857): `2^z × 2^z` square tiles, latitudes ±85.05°.
+    /// The default of Leaflet, OpenLayers and every web basemap.
+    WebMercator,
+    /// Plate carrée (EPSG:4326): `2^(z+1) × 2^z` tiles of equal degrees,
+    /// poles included.  Leaflet's `L.CRS.EPSG4326` and OpenLayers' EPSG:4326 grid.
+    PlateCarree,
+}
+
+impl TileScheme {
+    pub const ALL: [TileScheme; 2] = [TileScheme::WebMercator, TileScheme::PlateCarree];
+
+    /// Name used in tile paths and URLs.
+    pub fn name(self) -> &'static str {
+        match self {
+            TileScheme::WebMercator => "mercator",
+            TileScheme::PlateCarree => "plate-carree",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        TileScheme::ALL.into_iter().find(|s| s.name() == name)
+    }
+
+    /// Number of tiles across and down at zoom `z`.
+    pub fn tiles(self, z: u32) -> (u32, u32) {
+        match self {
+            TileScheme::WebMercator => (1 << z, 1 << z),
+            TileScheme::PlateCarree => (2 << z, 1 << z),
+        }
+    }
+
+    /// Longitude (`-180..180`) and latitude in degrees of global pixel
+    /// position `(px, py)` at zoom `z`, counted from the north-west corner.
+    fn lon_lat(self, z: u32, px: f64, py: f64) -> (f64, f64) {
+        let (cols, rows) = self.tiles(z);
+        // At the deepest zooms the pixel count overflows `u32`.
+        let x = px / (cols as f64 * TILE_SIZE as f64);
+        let y = py / (rows as f64 * TILE_SIZE as f64);
+        let lat = match self {
+            TileScheme::WebMercator => (PI * (1.0 - 2.0 * y)).sinh().atan().to_degrees(),
+            TileScheme::PlateCarree => 90.0 - y * 180.0,
+        };
+        (x * 360.0 - 180.0, lat)
+    }
+}
+
+/// Options for [`export_tiles`].
+#[derive(Clone, Copy, Debug)]
+pub struct TileOptions {
+    /// Deepest zoom level written to disk.
+    pub max_zoom: u32,
+}
+
+impl Default for TileOptions {
+    fn default() -> Self {
+        TileOptions { max_zoom: 4 }
+    }
+}
+
+/// Extra octaves of detail per noise field for one zoom level.
+#[derive(Clone, Copy)]
+struct Octaves {
+    continent: u32,
+    moisture: u32,
+    dither: u32,
+    /// Octaves of the border warp, starting at [`BORDER_WARP_TILES`].
+    warp: u32,
+}
+
+/// Terrain at one point, resolved below the world's tile size.
+struct Sample {
+    elevation: f32,
+    biome: Biome,
+    secondary_biome: Biome,
+    blend: f32,
+}
+
+/// Renders `z/x/y` map tiles of a world on demand.
+///
+/// While a tile pixel is at least as large as a world tile, pixels are copied
+/// from the world's own colours, so the tiles match `world.png`.  Zoomed in
+/// further, each pixel is resampled: elevation, moisture and temperature are
+/// interpolated between tiles and the noise pipeline adds the octaves that
+/// were too fine for the world grid, down to about two pixels.  Climate
+/// biomes are then chosen again from the detailed values; biomes left by the
+/// grid simulations (ice, volcanoes, seafloor provinces) keep their tile's
+/// biome, except where the detailed coastline turns ocean into land.
+pub struct TileRenderer<'a> {
+    world: &'a World,
+    options: RenderOptions,
+    /// `tile_colors` of the world, for the coarse zoom levels.
+    colors: Vec<[u8; 3]>,
+    /// Whether each tile's biome follows from its climate alone (and may be
+    /// re-chosen per pixel).
+    climate_driven: Vec<bool>,
+    // Same seeds as in `generate_world`.
+    continent_noise: Perlin,
+    moisture_noise: Perlin,
+    dither_noise: Perlin,
+    warp_noise: Perlin,
+    /// Same seed as the theme variation in `tile_colors`.
+    variation_noise: Perlin,
+    noise_scale: f64,
+}
+
+impl<'a> TileRenderer<'a> {
+    pub fn new(world: &'a World, options: &RenderOptions) -> Self {
+        let dither_noise = Perlin::new(world.seed + 500);
+        let noise_scale = (EARTH_CIRCUMFERENCE_KM / world.circumference_km.max(1.0)) as f64;
+        let (dt, dm, _) = planet_offsets(world.planet_type);
+
+        // Repeat pass 6 of `generate_world` without seafloor, ice or volcanic
+        // features: tiles that still get their biome are climate-driven.
+        let grid = Grid::new(world.width, world.height);
+        let climate_driven = world
+            .tiles
+            .iter()
+            .map(|tile| {
+                let (lon, lat) = grid.lon_lat(tile.q, tile.r);
+                let [nx, ny, nz] = unit_vector(lon, lat).map(|c| c * noise_scale);
+                let (ddt, ddm) = ecotone::dither(&dither_noise, nx, ny, nz);
+                let t = ((tile.temperature + dt).clamp(0.0, 1.0) + ddt).clamp(0.0, 1.0);
+                let m = ((tile.moisture + dm).clamp(-1.0, 1.0) + ddm).clamp(-1.0, 1.0);
+                let e = (tile.elevation - world.sea_level).clamp(-1.0, 1.0);
+                neutral_biome(world, e, t, m) == tile.biome
+            })
+            .collect();
+
+        TileRenderer {
+            world,
+            options: options.clone(),
+            colors: tile_colors(world, options),
+            climate_driven,
+            continent_noise: Perlin::new(world.seed + 100),
+            moisture_noise: Perlin::new(world.seed + 1),
+            dither_noise,
+            warp_noise: Perlin::new(world.seed + 600),
+            variation_noise: Perlin::new(world.seed + 700),
+            noise_scale,
+        }
+    }
+
+    /// Renders tile `(z, x, y)`, or `None` if it lies outside the scheme.
+    pub fn render(&self, scheme: TileScheme, z: u32, x: u32, y: u32) -> Option<RgbImage> {
+        let (cols, rows) = scheme.tiles(z.min(MAX_ZOOM));
+        if z > MAX_ZOOM || x >= cols || y >= rows {
+            return None;
+        }
+        let size = TILE_SIZE as usize;
+        let size_px = TILE_SIZE as f64;
+        let span = cols as f64 * size_px;
+        let (x0, y0) = (x as f64 * size_px, y as f64 * size_px);
+        let world = self.world;
+        let mut img = RgbImage::new(TILE_SIZE, TILE_SIZE);
+
+        if span <= world.width as f64 {
+            for (px, py, pixel) in img.enumerate_pixels_mut() {
+                let (lon, lat) = scheme.lon_lat(z, x0 + px as f64 + 0.5, y0 + py as f64 + 0.5);
+                *pixel = Rgb(self.colors[tile_index(world, lon.rem_euclid(360.0), lat)]);
+            }
+            return Some(img);
+        }
+
+        // Noise fields are refined until their finest octave spans ~2 pixels.
+        let pixel = 2.0 * PI / span;
+        let octaves = Octaves {
+            continent: extra_octaves(0.8 * self.noise_scale, 5, pixel),
+            moisture: extra_octaves(1.5 * self.noise_scale, 4, pixel),
+            dither: extra_octaves(12.0 * self.noise_scale, 3, pixel),
+            warp: extra_octaves(self.warp_frequency(), 0, pixel).max(1),
+        };
+
+        // One pixel of margin so contours can look across the tile's edges.
+        let n = size + 2;
+        let samples: Vec<Sample> = (0..n * n)
+            .map(|i| {
+                let (px, py) = ((i % n) as f64 - 0.5, (i / n) as f64 - 0.5);
+                let (lon, lat) = scheme.lon_lat(z, x0 + px, y0 + py);
+                self.sample(lon.rem_euclid(360.0), lat, octaves)
+            })
+            .collect();
+
+        let crosses_contour =
+            |a: f32, b: f32| -> bool { CONTOUR_LEVELS.iter().any(|&lvl| (a < lvl) != (b < lvl)) };
+        let radius = world.circumference_km as f64 * 1000.0 / (2.0 * PI);
+        let theme = &*self.options.theme;
+        for (px, py, pixel) in img.enumerate_pixels_mut() {
+            let i = (py as usize + 1) * n + px as usize + 1;
+            let s = &samples[i];
+            let height = (s.elevation - world.sea_level) * world.metres_per_unit;
+            let mut color = theme.base_color(
+                self.options.mode,
+                s.biome,
+                s.secondary_biome,
+                s.blend,
+                height,
+            );
+            if self.options.ocean_tint && theme.palette != Palette::Elevation {
+                color = theme.ocean_tint(s.biome, -height).unwrap_or(color);
+            }
+            if theme.variation > 0.0 {
+                let (lon, lat) = scheme.lon_lat(z, x0 + px as f64 + 0.5, y0 + py as f64 + 0.5);
+                let p = unit_vector(lon.to_radians(), lat.to_radians());
+                color = vary(color, theme.variation, &self.variation_noise, p);
+            }
+            color = theme.on_paper(color);
+            if self.options.style == RenderStyle::Hillshade {
+                // Slope across the neighbouring pixels, in metres per metre.
+                let (cx, cy) = (x0 + px as f64 + 0.5, y0 + py as f64 + 0.5);
+                let (west, _) = scheme.lon_lat(z, cx - 1.0, cy);
+                let (east, lat) = scheme.lon_lat(z, cx + 1.0, cy);
+                let (_, north) = scheme.lon_lat(z, cx, cy - 1.0);
+                let (_, south) = scheme.lon_lat(z, cx, cy + 1.0);
+                let dx = radius * (east - west).to_radians() * lat.to_radians().cos().max(1e-3);
+                let dy = radius * (north - south).to_radians();
+                let metres = |j: usize| (samples[j].elevation * world.metres_per_unit) as f64;
+                let dzdx = (metres(i + 1) - metres(i - 1)) / dx;
+                let dzdy = (metres(i - n) - metres(i + n)) / dy;
+                *pixel = Rgb(shade(color, hillshade(&self.options, dzdx, dzdy)));
+                continue;
+            }
+            let is_contour = [i - 1, i + 1, i - n, i + n]
+                .iter()
+                .any(|&j| crosses_contour(s.elevation, samples[j].elevation));
+            if is_contour {
+                color = theme.inked(color, CONTOUR_DARKNESS);
+            }
+            *pixel = Rgb(color);
+        }
+        Some(img)
+    }
+
+    /// Frequency (per radian of arc) of the coarsest border-warp octave.
+    fn warp_frequency(&self) -> f64 {
+        let tile = 2.0 * PI / self.world.width as f64;
+        1.0 / (BORDER_WARP_TILES * tile)
+    }
+
+    /// Detailed terrain at `(lon_deg, lat_deg)`, longitude in `0..360`.
+    fn sample(&self, lon_deg: f64, lat_deg: f64, octaves: Octaves) -> Sample {
+        let world = self.world;
+        let [nx, ny, nz] =
+            unit_vector(lon_deg.to_radians(), lat_deg.to_radians()).map(|c| c * self.noise_scale);
+
+        // Biomes copied from a tile would show the grid as a staircase: look
+        // the tile up through a fractal warp of about a tile instead.
+        let [ux, uy, uz] = unit_vector(lon_deg.to_radians(), lat_deg.to_radians())
+            .map(|c| c * self.warp_frequency());
+        let tile_deg = 360.0 / world.width as f64;
+        let warp_lon = fbm(&self.warp_noise, ux, uy, uz, octaves.warp) as f64;
+        let warp_lat = fbm(&self.warp_noise, ux + 5.2, uy + 1.3, uz + 3.7, octaves.warp) as f64;
+        let index = tile_index(
+            world,
+            lon_deg + warp_lon * BORDER_WARP_AMPLITUDE * tile_deg,
+            (lat_deg + warp_lat * BORDER_WARP_AMPLITUDE * tile_deg).clamp(-90.0, 90.0),
+        );
+        let base = &world.tiles[index];
+
+        // Octaves the world grid could not resolve, on top of its smooth fields.
+        let de = fbm_detail(
+            &self.continent_noise,
+            nx * 0.8,
+            ny * 0.8,
+            nz * 0.8,
+            5,
+            octaves.continent,
+        );
+        let dm = fbm_detail(
+            &self.moisture_noise,
+            nx * 1.5,
+            ny * 1.5,
+            nz * 1.5,
+            4,
+            octaves.moisture,
+        );
+        let elevation = sample(world, lon_deg, lat_deg, |t| t.elevation) + de;
+        let moisture = sample(world, lon_deg, lat_deg, |t| t.moisture) + dm;
+        // Temperature falls with height, as in `generate_world`.
+        let temperature = sample(world, lon_deg, lat_deg, |t| t.temperature) - de * 0.3;
+        let e = (elevation - world.sea_level).clamp(-1.0, 1.0);
+
+        let (offset_t, offset_m, _) = planet_offsets(world.planet_type);
+        let (dither_t, dither_m) = ecotone::dither(&self.dither_noise, nx, ny, nz);
+        let (detail_t, detail_m) =
+            ecotone::dither_detail(&self.dither_noise, nx, ny, nz, octaves.dither);
+        let t = ((temperature + offset_t).clamp(0.0, 1.0) + dither_t + detail_t).clamp(0.0, 1.0);
+        let m = ((moisture + offset_m).clamp(-1.0, 1.0) + dither_m + detail_m).clamp(-1.0, 1.0);
+
+        let new_land = base.elevation < world.sea_level && e >= 0.0 && base.ice_thickness == 0.0;
+        if !self.climate_driven[index] && !new_land {
+            return Sample {
+                elevation,
+                biome: base.biome,
+                secondary_biome: base.secondary_biome,
+                blend: base.blend,
+            };
+        }
+        let classify = |t: f32, m: f32| neutral_biome(world, e, t, m);
+        let biome = classify(t, m);
+        let (secondary_biome, blend) = match self.options.mode {
+            RenderMode::Flat => (biome, 0.0),
+            RenderMode::Blend => ecotone::transition(biome, t, m, classify),
+        };
+        Sample {
+            elevation,
+            biome,
+            secondary_biome,
+            blend,
+        }
+    }
+}
+
+/// Biome from climate alone: no ice, no volcanism, and shore biomes wherever
+/// the ground is below sea level.
+fn neutral_biome(world: &World, e: f32, t: f32, m: f32) -> Biome {
+    choose_biome(
+        e,
+        m,
+        t,
+        SeaFloor::Coast,
+        GlacialFeature::None,
+        VolcanicFeature::None,
+        world.planet_type,
+    )
+}
+
+/// Octaves to add to an fbm series of `octaves` at base `frequency` (per
+/// radian of arc) so its finest octave's features are about two `pixel`s wide.
+/// Perlin features are about one lattice cell, `1 / frequency` radians.
+fn extra_octaves(frequency: f64, octaves: u32, pixel: f64) -> u32 {
+    let total = (1.0 / (2.0 * frequency * pixel)).log2().floor() as i32 + 1;
+    (total - octaves as i32).clamp(0, MAX_EXTRA_OCTAVES) as u32
+}
+
+/// Writes a `z/x/y.png` tile pyramid of every scheme into `dir`, from zoom 0
+/// to `tiles.max_zoom`:
+///
+/// - `mercator/{z}/{x}/{y}.png`     — Web Mercator tiles
+/// - `plate-carree/{z}/{x}/{y}.png` — plate carrée tiles
+/// - `index.html`                   — a Leaflet viewer for both
+pub fn export_tiles(world: &World, options: &RenderOptions, tiles: &TileOptions, dir: &str) {
+    let max_zoom = tiles.max_zoom.min(MAX_ZOOM);
+    let renderer = TileRenderer::new(world, options);
+
+    let mut jobs = Vec::new();
+    for scheme in TileScheme::ALL {
+        for z in 0..=max_zoom {
+            let (cols, rows) = scheme.tiles(z);
+            for x in 0..cols {
+                std::fs::create_dir_all(format!("{dir}/{}/{z}/{x}", scheme.name()))
+                    .expect("failed to create tile directory");
+                jobs.extend((0..rows).map(|y| (scheme, z, x, y)));
+            }
+        }
+    }
+
+    // Tiles are independent: hand them out to one worker per core.
+    let next = AtomicUsize::new(0);
+    let workers = std::thread::available_parallelism().map_or(1, |n| n.get());
+    std::thread::scope(|s| {
+        for _ in 0..workers {
+            s.spawn(|| {
+                while let Some(&(scheme, z, x, y)) = jobs.get(next.fetch_add(1, Ordering::Relaxed))
+                {
+                    let img = renderer.render(scheme, z, x, y).unwrap();
+                    img.save(format!("{dir}/{}/{z}/{x}/{y}.png", scheme.name()))
+                        .expect("failed to write tile");
+                }
+            });
+        }
+    });
+
+    std::fs::write(format!("{dir}/index.html"), viewer_html(max_zoom))
+        .expect("failed to write tile viewer");
+}
+
+/// Standalone Leaflet page showing the tiles next to it (`{scheme}/{z}/{x}/{y}.png`).
+///
+/// Web Mercator by default; `?scheme=plate-carree` switches to plate carrée.
+/// Zooming past `max_native_zoom` enlarges the deepest tiles.
+pub fn viewer_html(max_native_zoom: u32) -> String {
+    format!(
+        r#"<!DOCTYPE html>
+<html>
+<head>
+<meta charset="utf-8">
+<title>map-generator</title>
+<link rel="stylesheet" href="https://unpkg.com/leaflet@1.9.4/dist/leaflet.css">
+<script src="https://unpkg.com/leaflet@1.9.4/dist/leaflet.js"></script>
+<style>html, body, #map {{ height: 100%; margin: 0; }}</style>
+</head>
+<body>
+<div id="map"></div>
+<script>
+const scheme = new URLSearchParams(location.search).get("scheme") || "mercator";
+const map = L.map("map", {{
+  crs: scheme === "plate-carree" ? L.CRS.EPSG4326 : L.CRS.EPSG3857,
+  worldCopyJump: true,
+}}).setView([0, 0], scheme === "plate-carree" ? 1 : 2);
+L.tileLayer(scheme + "/{{z}}/{{x}}/{{y}}.png", {{
+  tileSize: {TILE_SIZE},
+  maxNativeZoom: {max_native_zoom},
+  maxZoom: {MAX_ZOOM},
+}}).addTo(map);
+</script>
+</body>
+</html>
+"#
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generation::test_world;
+
+    fn assert_close(a: (f64, f64), b: (f64, f64)) {
+        assert!(
+            (a.0 - b.0).abs() < 1e-9 && (a.1 - b.1).abs() < 1e-9,
+            "{a:?} != {b:?}"
+        );
+    }
+
+    /// West, north, east and south edges of tile `(z, x, y)`.
+    fn bounds(scheme: TileScheme, z: u32, x: u32, y: u32) -> (f64, f64, f64, f64) {
+        let s = TILE_SIZE as f64;
+        let (west, north) = scheme.lon_lat(z, x as f64 * s, y as f64 * s);
+        let (east, south) = scheme.lon_lat(z, (x + 1) as f64 * s, (y + 1) as f64 * s);
+        (west, north, east, south)
+    }
+
+    #[test]
+    fn tile_grids_cover_the_globe() {
+        // Web Mercator stops where the map is square: atan(sinh(π)).
+        let mercator_limit = PI.sinh().atan().to_degrees();
+        for (scheme, limit) in [
+            (TileScheme::WebMercator, mercator_limit),
+            (TileScheme::PlateCarree, 90.0),
+        ] {
+            for z in 0..4 {
+                let (cols, rows) = scheme.tiles(z);
+                let across = if scheme == TileScheme::PlateCarree {
+                    2
+                } else {
+                    1
+                };
+                assert_eq!((cols, rows), (across << z, 1 << z));
+                let (w, n, _, _) = bounds(scheme, z, 0, 0);
+                let (_, _, e, s) = bounds(scheme, z, cols - 1, rows - 1);
+                assert_close((w, n), (-180.0, limit));
+                assert_close((e, s), (180.0, -limit));
+            }
+        }
+    }
+
+    #[test]
+    fn tile_bounds_match_the_slippy_map_convention() {
+        // Web Mercator z = 2, tile (2, 1): 0°..90° E, from 66.51° N down to the equator.
+        let (w, n, e, s) = bounds(TileScheme::WebMercator, 2, 2, 1);
+        assert_close((w, e), (0.0, 90.0));
+        assert_close((n, s), ((PI / 2.0).sinh().atan().to_degrees(), 0.0));
+        assert!((n - 66.513_260_443).abs() < 1e-6);
+
+        // Plate carrée tiles are squares of 180° / 2^z.
+        for z in 0..4 {
+            let (cols, rows) = TileScheme::PlateCarree.tiles(z);
+            let side = 180.0 / (1 << z) as f64;
+            for (x, y) in [(0, 0), (cols - 1, rows - 1), (cols / 2, rows / 2)] {
+                let (w, n, e, s) = bounds(TileScheme::PlateCarree, z, x, y);
+                assert_close((w, n), (-180.0 + x as f64 * side, 90.0 - y as f64 * side));
+                assert_close((e - w, n - s), (side, side));
+            }
+        }
+    }
+
+    #[test]
+    fn tiles_outside_the_scheme_are_not_rendered() {
+        let world = test_world();
+        let renderer = TileRenderer::new(&world, &RenderOptions::default());
+        assert!(renderer.render(TileScheme::WebMercator, 0, 1, 0).is_none());
+        assert!(renderer.render(TileScheme::WebMercator, 0, 0, 1).is_none());
+        assert!(renderer.render(TileScheme::PlateCarree, 1, 4, 0).is_none());
+        assert!(
+            renderer
+                .render(TileScheme::PlateCarree, MAX_ZOOM + 1, 0, 0)
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn deepest_tiles_render_in_both_schemes() {
+        let world = test_world();
+        let renderer = TileRenderer::new(&world, &RenderOptions::default());
+        for scheme in TileScheme::ALL {
+            let (cols, rows) = scheme.tiles(MAX_ZOOM);
+            // The last tile, where pixel positions are furthest from zero.
+            let (x, y) = (cols - 1, rows - 1);
+            let img = renderer.render(scheme, MAX_ZOOM, x, y).unwrap();
+            assert_eq!(img.dimensions(), (TILE_SIZE, TILE_SIZE));
+            let (west, north, east, south) = bounds(scheme, MAX_ZOOM, x, y);
+            assert!(west < east && east <= 180.0 && south < north);
+        }
+    }
+}
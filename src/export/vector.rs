@@ -0,0 +1,487 @@
+/// Raster-to-vector tracing shared by the SVG and GeoJSON exporters.
+///
+/// Works on a `w × h` grid of cells addressed `(x, y)`.  Output coordinates
+/// are lattice coordinates: cell `(x, y)` spans `[x, x+1] × [y, y+1]`.
+///
+///  - [`trace_regions`] — outlines every 4-connected region of equal labels
+///    as polygon rings along the cell edges.  Rings run counter-clockwise
+///    around their region (with `y` pointing up); holes run clockwise.
+///  - [`isolines`]      — marching-squares isolines of a scalar field sampled at
+///    cell centres.
+///
+/// Simplification is topology-preserving: borders between regions are
+/// simplified once, between fixed junction points, so neighbouring polygons
+/// keep sharing exactly the same vertices.
+use std::collections::{HashMap, HashSet};
+
+pub type Point = (f64, f64);
+/// Integer lattice corner, before simplification.
+type Lattice = (i64, i64);
+
+/// Label of the area outside the grid.
+const OUTSIDE: u32 = u32::MAX;
+
+/// One 4-connected region of equal labels.
+pub struct Region {
+    pub label: u32,
+    /// Outer boundary and holes, each implicitly closed (first point not repeated).
+    pub rings: Vec<Vec<Point>>,
+}
+
+/// Traces every region of `label(x, y)` and simplifies its rings with
+/// Douglas–Peucker at `tolerance` cells (`0.0` only drops collinear points).
+pub fn trace_regions(
+    w: usize,
+    h: usize,
+    label: impl Fn(usize, usize) -> u32,
+    tolerance: f64,
+) -> Vec<Region> {
+    let labels: Vec<u32> = (0..h)
+        .flat_map(|y| (0..w).map(move |x| (x, y)))
+        .map(|(x, y)| label(x, y))
+        .collect();
+    let at = |x: i64, y: i64| -> u32 {
+        if x < 0 || y < 0 || x >= w as i64 || y >= h as i64 {
+            OUTSIDE
+        } else {
+            labels[y as usize * w + x as usize]
+        }
+    };
+
+    // ── Components (4-connected flood fill) ───────────────────────────────────
+    let mut component = vec![usize::MAX; w * h];
+    let mut regions: Vec<Region> = Vec::new();
+    let mut stack = Vec::new();
+    for start in 0..w * h {
+        if component[start] != usize::MAX {
+            continue;
+        }
+        let id = regions.len();
+        regions.push(Region {
+            label: labels[start],
+            rings: Vec::new(),
+        });
+        component[start] = id;
+        stack.push(start);
+        while let Some(i) = stack.pop() {
+            let (x, y) = ((i % w) as i64, (i / w) as i64);
+            for (nx, ny) in [(x + 1, y), (x - 1, y), (x, y + 1), (x, y - 1)] {
+                if at(nx, ny) == labels[start] {
+                    let j = ny as usize * w + nx as usize;
+                    if component[j] == usize::MAX {
+                        component[j] = id;
+                        stack.push(j);
+                    }
+                }
+            }
+        }
+    }
+
+    // ── Junctions: lattice points where three or more borders meet ───────────
+    let degree = |x: i64, y: i64| {
+        let (nw, ne, sw, se) = (at(x - 1, y - 1), at(x, y - 1), at(x - 1, y), at(x, y));
+        [(nw, ne), (sw, se), (nw, sw), (ne, se)]
+            .iter()
+            .filter(|(a, b)| a != b)
+            .count()
+    };
+    let is_fixed = |(x, y): Lattice| {
+        let corner = (x == 0 || x == w as i64) && (y == 0 || y == h as i64);
+        corner || degree(x, y) >= 3
+    };
+
+    // ── Boundary walk ─────────────────────────────────────────────────────────
+    // A boundary edge is (cell, side) with side 0 = y-, 1 = x+, 2 = y+, 3 = x-,
+    // walked so the cell stays on the left: y- side from (x, y) to (x+1, y), …
+    const STEP: [Lattice; 4] = [(0, -1), (1, 0), (0, 1), (-1, 0)];
+    let corner = |x: i64, y: i64, side: usize| -> Lattice {
+        [(x, y), (x + 1, y), (x + 1, y + 1), (x, y + 1)][side]
+    };
+    let is_border = |x: i64, y: i64, side: usize| {
+        let (dx, dy) = STEP[side];
+        at(x + dx, y + dy) != at(x, y)
+    };
+
+    let mut visited = vec![0u8; w * h];
+    let mut chains = ChainCache::default();
+    for start in 0..w * h {
+        for side in 0..4 {
+            let (sx, sy) = ((start % w) as i64, (start / w) as i64);
+            if visited[start] & (1 << side) != 0 || !is_border(sx, sy, side) {
+                continue;
+            }
+            let mut ring = Vec::new();
+            let (mut x, mut y, mut s) = (sx, sy, side);
+            loop {
+                visited[y as usize * w + x as usize] |= 1 << s;
+                ring.push(corner(x, y, s));
+                // Next edge at the end corner: turn around this cell, go
+                // straight into the next cell, or turn into the diagonal one.
+                let turn = (s + 1) % 4;
+                if is_border(x, y, turn) {
+                    s = turn;
+                } else {
+                    let (nx, ny) = (x + STEP[turn].0, y + STEP[turn].1);
+                    if is_border(nx, ny, s) {
+                        (x, y) = (nx, ny);
+                    } else {
+                        (x, y) = (nx + STEP[s].0, ny + STEP[s].1);
+                        s = (s + 3) % 4;
+                    }
+                }
+                if (x, y, s) == (sx, sy, side) {
+                    break;
+                }
+            }
+            let ring = chains.simplify_ring(&ring, &is_fixed, tolerance);
+            regions[component[start]].rings.push(ring);
+        }
+    }
+    regions
+}
+
+/// Simplified border chains, keyed by their canonical point sequence, so both
+/// regions sharing a border get the same vertices.
+#[derive(Default)]
+struct ChainCache {
+    done: HashMap<Vec<Lattice>, Vec<Lattice>>,
+}
+
+impl ChainCache {
+    fn simplify_ring(
+        &mut self,
+        ring: &[Lattice],
+        is_fixed: &impl Fn(Lattice) -> bool,
+        tolerance: f64,
+    ) -> Vec<Point> {
+        let fixed: Vec<usize> = (0..ring.len()).filter(|&i| is_fixed(ring[i])).collect();
+        let mut out: Vec<Lattice> = Vec::new();
+        if fixed.is_empty() {
+            // Closed loop with no junction: start at its smallest point.
+            let start = (0..ring.len()).min_by_key(|&i| ring[i]).unwrap();
+            let mut chain: Vec<_> = ring[start..]
+                .iter()
+                .chain(&ring[..=start])
+                .copied()
+                .collect();
+            out = self.chain(&mut chain, tolerance);
+            out.pop();
+        } else {
+            for (k, &from) in fixed.iter().enumerate() {
+                let to = fixed[(k + 1) % fixed.len()];
+                let mut chain: Vec<_> = if to > from {
+                    ring[from..=to].to_vec()
+                } else {
+                    ring[from..].iter().chain(&ring[..=to]).copied().collect()
+                };
+                let piece = self.chain(&mut chain, tolerance);
+                out.extend(&piece[..piece.len() - 1]);
+            }
+        }
+        out.into_iter().map(|(x, y)| (x as f64, y as f64)).collect()
+    }
+
+    /// Simplifies an open chain (endpoints kept) in a canonical direction.
+    fn chain(&mut self, chain: &mut [Lattice], tolerance: f64) -> Vec<Lattice> {
+        // Walk each border in one agreed direction, whichever side asks.
+        let n = chain.len();
+        let reversed = (chain[n - 1], chain[n.saturating_sub(2)]) < (chain[0], chain[1]);
+        if reversed {
+            chain.reverse();
+        }
+        let mut result = self
+            .done
+            .entry(chain.to_vec())
+            .or_insert_with(|| {
+                let pts: Vec<Point> = chain.iter().map(|&(x, y)| (x as f64, y as f64)).collect();
+                simplify_indices(&pts, tolerance)
+                    .into_iter()
+                    .map(|i| chain[i])
+                    .collect()
+            })
+            .clone();
+        if reversed {
+            result.reverse();
+        }
+        result
+    }
+}
+
+/// Douglas–Peucker simplification of an open polyline (endpoints kept).
+pub fn simplify(points: &[Point], tolerance: f64) -> Vec<Point> {
+    simplify_indices(points, tolerance)
+        .into_iter()
+        .map(|i| points[i])
+        .collect()
+}
+
+/// Indices of the points kept by Douglas–Peucker, in order.
+fn simplify_indices(points: &[Point], tolerance: f64) -> Vec<usize> {
+    if points.len() < 3 {
+        return (0..points.len()).collect();
+    }
+    let mut keep = vec![false; points.len()];
+    keep[0] = true;
+    keep[points.len() - 1] = true;
+    let mut stack = vec![(0, points.len() - 1)];
+    while let Some((a, b)) = stack.pop() {
+        let (mut worst, mut worst_d) = (0, 0.0);
+        for i in a + 1..b {
+            let d = segment_distance(points[i], points[a], points[b]);
+            if d > worst_d {
+                (worst, worst_d) = (i, d);
+            }
+        }
+        // Strictly greater, so collinear points always go.
+        if worst_d > tolerance.max(1e-9) {
+            keep[worst] = true;
+            stack.push((a, worst));
+            stack.push((worst, b));
+        }
+    }
+    (0..points.len()).filter(|&i| keep[i]).collect()
+}
+
+/// Distance from `p` to segment `ab` (to `a` when the segment is a point).
+fn segment_distance(p: Point, a: Point, b: Point) -> f64 {
+    let (dx, dy) = (b.0 - a.0, b.1 - a.1);
+    let len2 = dx * dx + dy * dy;
+    let t = if len2 == 0.0 {
+        0.0
+    } else {
+        (((p.0 - a.0) * dx + (p.1 - a.1) * dy) / len2).clamp(0.0, 1.0)
+    };
+    let (cx, cy) = (a.0 + t * dx, a.1 + t * dy);
+    ((p.0 - cx).powi(2) + (p.1 - cy).powi(2)).sqrt()
+}
+
+/// Marching-squares isolines of `value(x, y)` at `level`.
+///
+/// Samples sit at cell centres `(x + 0.5, y + 0.5)`.  Returns polylines;
+/// closed loops repeat their first point at the end.
+pub fn isolines(
+    w: usize,
+    h: usize,
+    value: impl Fn(usize, usize) -> f32,
+    level: f32,
+) -> Vec<Vec<Point>> {
+    let above = |x: usize, y: usize| value(x, y) >= level;
+    // A crossing is identified by the sample edge it lies on:
+    // (x, y, false) = between (x, y) and (x+1, y); (x, y, true) = (x, y)–(x, y+1).
+    type Edge = (usize, usize, bool);
+    let crossing = |(x, y, vertical): Edge| -> Point {
+        let (x1, y1) = if vertical { (x, y + 1) } else { (x + 1, y) };
+        let (a, b) = (value(x, y), value(x1, y1));
+        let t = ((level - a) / (b - a)).clamp(0.0, 1.0) as f64;
+        (
+            x as f64 + 0.5 + t * (x1 as f64 - x as f64),
+            y as f64 + 0.5 + t * (y1 as f64 - y as f64),
+        )
+    };
+
+    let mut links: HashMap<Edge, Vec<Edge>> = HashMap::new();
+    let mut link = |a: Edge, b: Edge| {
+        links.entry(a).or_default().push(b);
+        links.entry(b).or_default().push(a);
+    };
+    for y in 0..h.saturating_sub(1) {
+        for x in 0..w.saturating_sub(1) {
+            let case = (above(x, y) as u8)
+                | (above(x + 1, y) as u8) << 1
+                | (above(x + 1, y + 1) as u8) << 2
+                | (above(x, y + 1) as u8) << 3;
+            let (top, right, bottom, left) = (
+                (x, y, false),
+                (x + 1, y, true),
+                (x, y + 1, false),
+                (x, y, true),
+            );
+            match case {
+                0 | 15 => {}
+                1 | 14 => link(left, top),
+                2 | 13 => link(top, right),
+                3 | 12 => link(left, right),
+                4 | 11 => link(right, bottom),
+                6 | 9 => link(top, bottom),
+                7 | 8 => link(left, bottom),
+                5 | 10 => {
+                    // Saddle: the centre average decides which corners connect.
+                    let centre =
+                        (value(x, y) + value(x + 1, y) + value(x + 1, y + 1) + value(x, y + 1))
+                            / 4.0;
+                    if (centre >= level) == (case == 5) {
+                        link(left, bottom);
+                        link(top, right);
+                    } else {
+                        link(left, top);
+                        link(right, bottom);
+                    }
+                }
+                _ => unreachable!(),
+            }
+        }
+    }
+
+    // Walk chains from open ends first, then the remaining closed loops.
+    let mut starts: Vec<Edge> = links
+        .iter()
+        .filter(|(_, n)| n.len() == 1)
+        .map(|(&e, _)| e)
+        .collect();
+    starts.sort_unstable();
+    let mut rest: Vec<Edge> = links.keys().copied().collect();
+    rest.sort_unstable();
+    starts.extend(rest);
+
+    let mut used: HashSet<Edge> = HashSet::new();
+    let mut lines = Vec::new();
+    for start in starts {
+        if !used.insert(start) {
+            continue;
+        }
+        let mut line = vec![crossing(start)];
+        let mut cur = start;
+        while let Some(&next) = links[&cur].iter().find(|e| !used.contains(e)) {
+            used.insert(next);
+            line.push(crossing(next));
+            cur = next;
+        }
+        if line.len() > 2 && links[&cur].contains(&start) {
+            line.push(line[0]);
+        }
+        if line.len() >= 2 {
+            lines.push(line);
+        }
+    }
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Shoelace area, positive for counter-clockwise rings (y up).
+    fn signed_area(ring: &[Point]) -> f64 {
+        let n = ring.len();
+        (0..n)
+            .map(|i| {
+                let (a, b) = (ring[i], ring[(i + 1) % n]);
+                a.0 * b.1 - b.0 * a.1
+            })
+            .sum::<f64>()
+            / 2.0
+    }
+
+    /// A patchy labelling with several regions, holes and junctions.
+    fn patchy(x: usize, y: usize) -> u32 {
+        let h = (x as u32).wrapping_mul(73_856_093) ^ (y as u32).wrapping_mul(19_349_663);
+        if (x / 3 + y / 2).is_multiple_of(4) {
+            1
+        } else {
+            h % 3 / 2 * 2
+        }
+    }
+
+    #[test]
+    fn square_island_has_one_ring_and_a_hole() {
+        let regions = trace_regions(5, 5, |x, y| (x == 2 && y == 2) as u32, 0.0);
+        assert_eq!(regions.len(), 2);
+        let sea = regions.iter().find(|r| r.label == 0).unwrap();
+        let island = regions.iter().find(|r| r.label == 1).unwrap();
+
+        assert_eq!(island.rings.len(), 1);
+        let mut corners = island.rings[0].clone();
+        corners.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        assert_eq!(
+            corners,
+            vec![(2.0, 2.0), (2.0, 3.0), (3.0, 2.0), (3.0, 3.0)]
+        );
+        assert_eq!(signed_area(&island.rings[0]), 1.0);
+
+        // Outer boundary counter-clockwise, the island's hole clockwise.
+        let mut areas: Vec<f64> = sea.rings.iter().map(|r| signed_area(r)).collect();
+        areas.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        assert_eq!(areas, vec![-1.0, 25.0]);
+    }
+
+    #[test]
+    fn rings_are_closed_and_enclose_their_cells() {
+        let (w, h) = (24, 17);
+        let regions = trace_regions(w, h, patchy, 0.0);
+        let mut total = 0.0;
+        for region in &regions {
+            let area: f64 = region.rings.iter().map(|r| signed_area(r)).sum();
+            assert!(area >= 1.0, "region {} has area {area}", region.label);
+            for ring in &region.rings {
+                assert!(ring.len() >= 4, "ring of {} points", ring.len());
+                assert_ne!(ring.first(), ring.last(), "rings are implicitly closed");
+            }
+            total += area;
+        }
+        assert_eq!(total, (w * h) as f64);
+        // Each label's regions together cover exactly its cells.
+        for label in 0..3 {
+            let cells = (0..h)
+                .flat_map(|y| (0..w).map(move |x| (x, y)))
+                .filter(|&(x, y)| patchy(x, y) == label)
+                .count();
+            let area: f64 = regions
+                .iter()
+                .filter(|r| r.label == label)
+                .flat_map(|r| &r.rings)
+                .map(|r| signed_area(r))
+                .sum();
+            assert_eq!(area, cells as f64, "label {label}");
+        }
+    }
+
+    #[test]
+    fn simplified_neighbours_share_their_borders() {
+        let (w, h) = (24, 17);
+        let regions = trace_regions(w, h, patchy, 2.0);
+        let mut edges = HashSet::new();
+        for ring in regions.iter().flat_map(|r| &r.rings) {
+            for i in 0..ring.len() {
+                let (a, b) = (ring[i], ring[(i + 1) % ring.len()]);
+                edges.insert(((a.0 as i64, a.1 as i64), (b.0 as i64, b.1 as i64)));
+            }
+        }
+        let (w, h) = (w as i64, h as i64);
+        let on_frame = |(a, b): ((i64, i64), (i64, i64))| {
+            (a.0 == b.0 && (a.0 == 0 || a.0 == w)) || (a.1 == b.1 && (a.1 == 0 || a.1 == h))
+        };
+        for &(a, b) in &edges {
+            assert!(
+                on_frame((a, b)) || edges.contains(&(b, a)),
+                "edge {a:?} → {b:?} has no twin in the neighbouring region"
+            );
+        }
+    }
+
+    #[test]
+    fn douglas_peucker_keeps_ends_and_far_points() {
+        let line = [(0.0, 0.0), (1.0, 0.0), (2.0, 0.0), (3.0, 0.0)];
+        assert_eq!(simplify(&line, 0.0), vec![(0.0, 0.0), (3.0, 0.0)]);
+
+        let zigzag = [(0.0, 0.0), (1.0, 0.2), (2.0, 3.0), (3.0, 0.1), (4.0, 0.0)];
+        assert_eq!(
+            simplify(&zigzag, 0.8),
+            vec![(0.0, 0.0), (2.0, 3.0), (4.0, 0.0)]
+        );
+        assert_eq!(simplify(&zigzag, 0.05), zigzag.to_vec());
+        assert_eq!(simplify(&zigzag[..2], 10.0), zigzag[..2].to_vec());
+    }
+
+    #[test]
+    fn isoline_around_a_peak_is_a_closed_loop() {
+        let peak = |x: usize, y: usize| 4.0 - (x as f32 - 4.0).abs() - (y as f32 - 4.0).abs();
+        let lines = isolines(9, 9, peak, 2.5);
+        assert_eq!(lines.len(), 1);
+        let line = &lines[0];
+        assert_eq!(line.first(), line.last());
+        for &(x, y) in line {
+            let d = (x - 4.5).abs() + (y - 4.5).abs();
+            assert!((d - 1.5).abs() < 1e-6, "({x}, {y}) is off the 2.5 contour");
+        }
+    }
+}
@@ -1,16 +1,23 @@
+use crate::bathymetry;
 use crate::biome::{choose_biome, planet_offsets};
+use crate::ecotone;
+use crate::glaciation;
+use crate::grid::{Grid, unit_vector};
 use crate::noise::{EARTH_CIRCUMFERENCE_KM, fbm, ridged};
+use crate::units;
+use crate::volcanism;
 use crate::world::*;
 use noise::{NoiseFn, Perlin};
 
+#[allow(clippy::too_many_arguments)]
 pub fn generate_world(
-    width: i32,
-    height: i32,
+    grid: Grid,
     seed: u32,
     sea_level: f32,
     volcanic_intensity: f32,
     planet_type: PlanetType,
     circumference_km: f32,
+    stellar_flux: f32,
 ) -> World {
     let elevation_noise = Perlin::new(seed);
     let moisture_noise = Perlin::new(seed + 1);
@@ -19,6 +26,8 @@ pub fn generate_world(
     let warp_noise_b = Perlin::new(seed + 201);
     // Low-frequency noise that selects which mountain chains turn volcanic.
     let volcano_noise = Perlin::new(seed + 300);
+    // High-frequency, low-amplitude noise that roughens biome borders.
+    let dither_noise = Perlin::new(seed + 500);
 
     // Scale noise frequencies by planet size: a larger circumference stretches
     // the unit-sphere coordinates, producing broader continents and ocean basins.
@@ -33,125 +42,235 @@ pub fn generate_world(
     // Mountain blend coefficient: baseline 0.35 at Earth gravity, compressed or
     // stretched proportionally.  sqrt dampens the effect for extreme values.
     let mountain_blend = 0.35 / gravity_modifier.sqrt();
+    let metres_per_unit = units::metres_per_unit(gravity_modifier);
 
-    let mut tiles = Vec::new();
-
-    for q in 0..width {
-        for r in 0..height {
-            use std::f64::consts::PI;
-
-            // Proper spherical mapping: longitude 0..2π, latitude -π/2..π/2
-            // Projects the flat map onto a unit sphere → no seams, no mirror symmetry
-            let lon = (q as f64 / width as f64) * 2.0 * PI;
-            let lat = (r as f64 / height as f64) * PI - PI / 2.0;
-
-            let nx = lat.cos() * lon.cos();
-            let ny = lat.cos() * lon.sin();
-            let nz = lat.sin();
-
-            // Domain warping: twist coordinates before sampling for organic coastlines
-            let warp_x = warp_noise_a.get([
-                nx * 2.0 * noise_scale,
-                ny * 2.0 * noise_scale,
-                nz * 2.0 * noise_scale,
-            ]);
-            let warp_y = warp_noise_b.get([
-                nx * 2.0 * noise_scale + 5.2,
-                ny * 2.0 * noise_scale + 1.3,
-                nz * 2.0 * noise_scale + 3.7,
-            ]);
-            let wnx = nx + warp_x * 0.25;
-            let wny = ny + warp_y * 0.25;
-
-            // Continent shape: low-frequency 3D FBM — all three axes used, no symmetry
-            let continent = fbm(
-                &continent_noise,
-                nx * 0.8 * noise_scale,
-                ny * 0.8 * noise_scale,
-                nz * 0.8 * noise_scale,
-                5,
-            );
-
-            // Ridged mountains blended only onto elevated terrain
-            let mountain = ridged(
-                &elevation_noise,
-                wnx * 5.0 * noise_scale,
-                wny * 5.0 * noise_scale,
-                nz * 5.0 * noise_scale,
-            );
-            let mountain_weight = ((continent - 0.2) * 2.5).clamp(0.0, 1.0);
-            let elevation =
-                (continent + mountain * mountain_weight * mountain_blend).clamp(-1.0, 1.0);
-
-            // Shift elevation by sea_level before biome selection.
-            // Positive sea_level raises the waterline (more ocean);
-            // negative sea_level lowers it (more land).
-            let biome_elevation = (elevation - sea_level).clamp(-1.0, 1.0);
-
-            // Moisture uses 3D sphere coords so it also wraps seamlessly
-            let moisture = fbm(
-                &moisture_noise,
-                nx * 1.5 * noise_scale,
-                ny * 1.5 * noise_scale,
-                nz * 1.5 * noise_scale,
-                4,
-            );
-
-            // Volcanic zone: low-frequency noise determines which mountain chains are volcanic.
-            // volcanic_intensity 0.0 → no volcanoes; 1.0 → most high mountain chains volcanic.
-            // The threshold slides so that higher intensity makes more terrain volcanic.
-            let volcanic_raw = fbm(
-                &volcano_noise,
-                nx * 1.0 * noise_scale,
-                ny * 1.0 * noise_scale,
-                nz * 1.0 * noise_scale,
-                3,
-            );
-            let volcanic_threshold = 1.0 - volcanic_intensity.clamp(0.0, 1.0);
-            // volcanic_zone: 0 = cold/neutral, >0 = inside a volcanic chain
-            let volcanic_zone = ((volcanic_raw - volcanic_threshold) * 4.0).clamp(0.0, 1.0);
-
-            // Temperature: equator warm, poles cold, high elevation colder
-            let latitude_norm = r as f32 / height as f32; // 0 = south pole, 1 = north pole
-            let temp_gradient = 1.0 - (latitude_norm - 0.5).abs() * 2.0;
-
-            let temperature = temp_gradient - biome_elevation * 0.3;
-
-            // Planet-type global offsets applied to temperature, moisture and volcanic zone.
-            // These shift the entire planet climate before biome selection.
-            let (dt, dm, dvz) = planet_offsets(planet_type);
-            let eff_temperature = (temperature + dt).clamp(0.0, 1.0);
-            let eff_moisture = (moisture + dm).clamp(-1.0, 1.0);
-            let eff_volcanic_zone = (volcanic_zone + dvz).clamp(0.0, 1.0);
-
-            let biome = choose_biome(
+    // ── Pass 1: sample the noise pipeline ─────────────────────────────────────
+    // Fields are kept in flat column-major buffers (index = q * height + r) so
+    // the grid simulations below can look at neighbouring tiles.
+    let n = grid.tile_count();
+    let mut elevations = Vec::with_capacity(n);
+    let mut moistures = Vec::with_capacity(n);
+    let mut temperatures = Vec::with_capacity(n);
+    let mut eff_temperatures = Vec::with_capacity(n);
+    let mut eff_moistures = Vec::with_capacity(n);
+    let mut eff_volcanic_zones = Vec::with_capacity(n);
+    let mut temp_gradients = Vec::with_capacity(n);
+
+    // Planet-type global offsets applied to temperature, moisture and volcanic zone.
+    // These shift the entire planet climate before biome selection.
+    let (dt, dm, dvz) = planet_offsets(planet_type);
+
+    for i in 0..n {
+        use std::f64::consts::PI;
+
+        // Proper spherical mapping: longitude 0..2π, latitude -π/2..π/2
+        // Projects the flat map onto a unit sphere → no seams, no mirror symmetry
+        let (q, r) = grid.coords(i);
+        let (lon, lat) = grid.lon_lat(q, r);
+
+        let nx = lat.cos() * lon.cos();
+        let ny = lat.cos() * lon.sin();
+        let nz = lat.sin();
+
+        // Domain warping: twist coordinates before sampling for organic coastlines
+        let warp_x = warp_noise_a.get([
+            nx * 2.0 * noise_scale,
+            ny * 2.0 * noise_scale,
+            nz * 2.0 * noise_scale,
+        ]);
+        let warp_y = warp_noise_b.get([
+            nx * 2.0 * noise_scale + 5.2,
+            ny * 2.0 * noise_scale + 1.3,
+            nz * 2.0 * noise_scale + 3.7,
+        ]);
+        let wnx = nx + warp_x * 0.25;
+        let wny = ny + warp_y * 0.25;
+
+        // Continent shape: low-frequency 3D FBM — all three axes used, no symmetry
+        let continent = fbm(
+            &continent_noise,
+            nx * 0.8 * noise_scale,
+            ny * 0.8 * noise_scale,
+            nz * 0.8 * noise_scale,
+            5,
+        );
+
+        // Ridged mountains blended only onto elevated terrain
+        let mountain = ridged(
+            &elevation_noise,
+            wnx * 5.0 * noise_scale,
+            wny * 5.0 * noise_scale,
+            nz * 5.0 * noise_scale,
+        );
+        let mountain_weight = ((continent - 0.2) * 2.5).clamp(0.0, 1.0);
+        let elevation = (continent + mountain * mountain_weight * mountain_blend).clamp(-1.0, 1.0);
+
+        // Shift elevation by sea_level before biome selection.
+        // Positive sea_level raises the waterline (more ocean);
+        // negative sea_level lowers it (more land).
+        let biome_elevation = (elevation - sea_level).clamp(-1.0, 1.0);
+
+        // Moisture uses 3D sphere coords so it also wraps seamlessly
+        let moisture = fbm(
+            &moisture_noise,
+            nx * 1.5 * noise_scale,
+            ny * 1.5 * noise_scale,
+            nz * 1.5 * noise_scale,
+            4,
+        );
+
+        // Volcanic zone: low-frequency noise determines which mountain chains are volcanic.
+        // volcanic_intensity 0.0 → no volcanoes; 1.0 → most high mountain chains volcanic.
+        // The threshold slides so that higher intensity makes more terrain volcanic.
+        let volcanic_raw = fbm(
+            &volcano_noise,
+            nx * 1.0 * noise_scale,
+            ny * 1.0 * noise_scale,
+            nz * 1.0 * noise_scale,
+            3,
+        );
+        let volcanic_threshold = 1.0 - volcanic_intensity.clamp(0.0, 1.0);
+        // volcanic_zone: 0 = cold/neutral, >0 = inside a volcanic chain
+        let volcanic_zone = ((volcanic_raw - volcanic_threshold) * 4.0).clamp(0.0, 1.0);
+
+        // Temperature: equator warm, poles cold, high elevation colder
+        let latitude_norm = (lat / PI + 0.5) as f32; // 0 = south pole, 1 = north pole
+        let temp_gradient = 1.0 - (latitude_norm - 0.5).abs() * 2.0;
+
+        let temperature = temp_gradient - biome_elevation * 0.3;
+
+        let eff_temperature = (temperature + dt).clamp(0.0, 1.0);
+        let eff_moisture = (moisture + dm).clamp(-1.0, 1.0);
+        let eff_volcanic_zone = (volcanic_zone + dvz).clamp(0.0, 1.0);
+
+        elevations.push(elevation);
+        moistures.push(moisture);
+        temperatures.push(temperature);
+        eff_temperatures.push(eff_temperature);
+        eff_moistures.push(eff_moisture);
+        eff_volcanic_zones.push(eff_volcanic_zone);
+        temp_gradients.push(temp_gradient);
+    }
+
+    // ── Pass 2: ocean floor ───────────────────────────────────────────────────
+    // Shelves, slopes, abyssal plains, ridges and trenches from distance-to-coast.
+    let seafloor = bathymetry::shape_seafloor(
+        &grid,
+        seed,
+        &mut elevations,
+        &eff_volcanic_zones,
+        sea_level,
+        circumference_km,
+        noise_scale,
+    );
+
+    // ── Pass 3: volcanic edifices ─────────────────────────────────────────────
+    // Hotspot chains and stratovolcanoes are built into the terrain before the
+    // ice sees it, so tall cones can carry their own glaciers.
+    let volcanoes = volcanism::place_volcanoes(
+        &grid,
+        seed,
+        &mut elevations,
+        &eff_volcanic_zones,
+        sea_level,
+        volcanic_intensity + dvz,
+    );
+
+    // The seafloor and the cones moved the terrain: redo the elevation lapse so
+    // the ice and the biomes see the air over the new relief.
+    for i in 0..n {
+        let biome_elevation = (elevations[i] - sea_level).clamp(-1.0, 1.0);
+        temperatures[i] = temp_gradients[i] - biome_elevation * 0.3;
+        eff_temperatures[i] = (temperatures[i] + dt).clamp(0.0, 1.0);
+    }
+
+    // ── Pass 4: ice sheets ────────────────────────────────────────────────────
+    // Grows and flows ice, depresses the bedrock beneath it and leaves moraines
+    // and fjords at its margins.
+    let ice = glaciation::simulate(
+        &grid,
+        &mut elevations,
+        &eff_temperatures,
+        &eff_moistures,
+        sea_level,
+    );
+
+    // ── Pass 5: lava flows over the final terrain ─────────────────────────────
+    let eruptions = volcanism::erupt(&grid, seed, &elevations, &volcanoes, sea_level);
+
+    // ── Pass 6: biome selection ───────────────────────────────────────────────
+    // Climate thresholds are dithered so borders wander, and every tile records
+    // the neighbouring biome it is transitioning into (see `ecotone.rs`).
+    let mut tiles = Vec::with_capacity(n);
+    for i in 0..n {
+        let (q, r) = grid.coords(i);
+        let elevation = elevations[i];
+        let biome_elevation = (elevation - sea_level).clamp(-1.0, 1.0);
+
+        let (lon, lat) = grid.lon_lat(q, r);
+        let [nx, ny, nz] = unit_vector(lon, lat).map(|c| c * noise_scale);
+        let (ddt, ddm) = ecotone::dither(&dither_noise, nx, ny, nz);
+        let t = (eff_temperatures[i] + ddt).clamp(0.0, 1.0);
+        let m = (eff_moistures[i] + ddm).clamp(-1.0, 1.0);
+
+        let classify = |t: f32, m: f32| {
+            choose_biome(
                 biome_elevation,
-                eff_moisture,
-                eff_temperature,
-                eff_volcanic_zone,
+                m,
+                t,
+                seafloor[i],
+                ice.feature[i],
+                eruptions[i],
                 planet_type,
-            );
-
-            tiles.push(Tile {
-                q,
-                r,
-                elevation,
-                moisture,
-                temperature,
-                biome,
-            });
-        }
+            )
+        };
+        let biome = classify(t, m);
+        let (secondary_biome, blend) = ecotone::transition(biome, t, m, classify);
+
+        tiles.push(Tile {
+            q,
+            r,
+            elevation,
+            moisture: moistures[i],
+            temperature: temperatures[i],
+            elevation_m: units::elevation_m(elevation, sea_level, metres_per_unit),
+            temperature_c: units::temperature_c(temperatures[i], planet_type, stellar_flux),
+            precipitation_mm: units::precipitation_mm(moistures[i], planet_type),
+            ice_thickness: ice.thickness[i],
+            biome,
+            secondary_biome,
+            blend,
+        });
     }
 
     World {
-        width,
-        height,
+        width: grid.width,
+        height: grid.height,
+        layout: grid.layout,
+        geodesic: grid.geodesic,
         seed,
         planet_type,
         sea_level,
         volcanic_intensity,
         circumference_km,
         gravity_modifier,
+        stellar_flux,
+        metres_per_unit,
+        volcanoes,
         tiles,
     }
 }
+
+/// Small Earth-like world for unit tests.
+#[cfg(test)]
+pub fn test_world() -> World {
+    generate_world(
+        Grid::new(128, 72),
+        7,
+        0.0,
+        0.5,
+        PlanetType::Terran,
+        40_000.0,
+        1.0,
+    )
+}
@@ -0,0 +1,250 @@
+/// Icosahedral geodesic cells — the Goldberg polyhedron behind
+/// [`Layout::Geodesic`](crate::grid::Layout::Geodesic).
+///
+/// Every edge of an icosahedron is split into `frequency` segments, each face
+/// into the matching triangles, and the points are pushed out onto the unit
+/// sphere.  Each point is the centre of one cell: the 12 icosahedron corners
+/// become pentagons and every other point a hexagon, `10 f² + 2` cells in
+/// all.  Cell corners are the centroids of the triangles around the centre.
+///
+/// The icosahedron stands on a corner, so there is a pentagon at each pole.
+/// Axes follow [`unit_vector`](crate::grid::unit_vector).
+use std::collections::HashMap;
+
+pub struct Geodesic {
+    /// Segments per icosahedron edge.
+    pub frequency: u32,
+    /// Cell centres on the unit sphere.  The first 12 are the pentagons.
+    pub centres: Vec<[f64; 3]>,
+    /// Neighbours of each cell, counter-clockwise seen from outside the
+    /// sphere: five around a pentagon, six around a hexagon.
+    pub neighbors: Vec<Vec<usize>>,
+}
+
+impl Geodesic {
+    pub fn new(frequency: u32) -> Self {
+        let f = frequency.max(1);
+        let corners = icosahedron();
+        let mut centres: Vec<[f64; 3]> = corners.to_vec();
+        // A point is named by its nonzero corner weights, so the faces on
+        // either side of an edge find the same cell.
+        let mut ids: HashMap<[(usize, u32); 3], usize> = (0..12)
+            .map(|v| ([(v, f), (usize::MAX, 0), (usize::MAX, 0)], v))
+            .collect();
+        let mut neighbors: Vec<Vec<usize>> = vec![Vec::new(); 12];
+
+        for [a, b, c] in ICOSAHEDRON_FACES {
+            let mut id = |i: u32, j: u32| {
+                let mut key = [(a, f - i - j), (b, i), (c, j)];
+                key.sort_by_key(|&(v, w)| if w == 0 { usize::MAX } else { v });
+                let key = key.map(|(v, w)| if w == 0 { (usize::MAX, 0) } else { (v, w) });
+                *ids.entry(key).or_insert_with(|| {
+                    let p: [f64; 3] = std::array::from_fn(|k| {
+                        (corners[a][k] * (f - i - j) as f64
+                            + corners[b][k] * i as f64
+                            + corners[c][k] * j as f64)
+                            / f as f64
+                    });
+                    centres.push(normalize(p));
+                    neighbors.push(Vec::new());
+                    centres.len() - 1
+                })
+            };
+            // Walk the face's small triangles, pointing up and down.
+            let mut triangles = Vec::new();
+            for i in 0..f {
+                for j in 0..f - i {
+                    triangles.push([id(i, j), id(i + 1, j), id(i, j + 1)]);
+                    if i + j + 1 < f {
+                        triangles.push([id(i + 1, j), id(i + 1, j + 1), id(i, j + 1)]);
+                    }
+                }
+            }
+            for [p, q, r] in triangles {
+                for (u, v) in [(p, q), (q, r), (r, p)] {
+                    if !neighbors[u].contains(&v) {
+                        neighbors[u].push(v);
+                        neighbors[v].push(u);
+                    }
+                }
+            }
+        }
+
+        // Order each ring counter-clockwise in the tangent plane.
+        for (i, ring) in neighbors.iter_mut().enumerate() {
+            let p = centres[i];
+            let d = sub(centres[ring[0]], p);
+            let e1 = normalize(sub(d, scale(p, dot(d, p))));
+            let e2 = cross(p, e1);
+            let angle = |j: &usize| {
+                let d = sub(centres[*j], p);
+                dot(d, e2).atan2(dot(d, e1))
+            };
+            ring.sort_by(|a, b| angle(a).total_cmp(&angle(b)));
+        }
+
+        Geodesic {
+            frequency: f,
+            centres,
+            neighbors,
+        }
+    }
+
+    /// Corners of cell `i` on the unit sphere, in the order of its neighbours:
+    /// corner `k` lies between neighbours `k` and `k + 1`.
+    pub fn corners(&self, i: usize) -> Vec<[f64; 3]> {
+        let ring = &self.neighbors[i];
+        (0..ring.len())
+            .map(|k| {
+                let (a, b) = (
+                    self.centres[ring[k]],
+                    self.centres[ring[(k + 1) % ring.len()]],
+                );
+                normalize(std::array::from_fn(|c| self.centres[i][c] + a[c] + b[c]))
+            })
+            .collect()
+    }
+
+    /// Cell containing the direction `p`, found by walking downhill from cell
+    /// `start`.  Starting near the answer (the previous pixel, say) makes the
+    /// walk short.
+    pub fn nearest(&self, p: [f64; 3], start: usize) -> usize {
+        let mut i = start;
+        loop {
+            let next = self.neighbors[i]
+                .iter()
+                .copied()
+                .max_by(|&a, &b| dot(p, self.centres[a]).total_cmp(&dot(p, self.centres[b])))
+                .unwrap();
+            if dot(p, self.centres[next]) <= dot(p, self.centres[i]) {
+                return i;
+            }
+            i = next;
+        }
+    }
+
+    /// Cell containing the direction `p`, from anywhere on the sphere.
+    pub fn locate(&self, p: [f64; 3]) -> usize {
+        let start = (0..12)
+            .max_by(|&a, &b| dot(p, self.centres[a]).total_cmp(&dot(p, self.centres[b])))
+            .unwrap();
+        self.nearest(p, start)
+    }
+}
+
+/// Icosahedron corners: the north pole, a ring of five at latitude
+/// `atan(1/2)` from 0°, a ring of five at `-atan(1/2)` offset by 36°, and the
+/// south pole.
+fn icosahedron() -> [[f64; 3]; 12] {
+    let ring_lat = 0.5f64.atan();
+    std::array::from_fn(|v| match v {
+        0 => [0.0, 0.0, 1.0],
+        11 => [0.0, 0.0, -1.0],
+        _ => {
+            let (lon, lat) = if v <= 5 {
+                ((v - 1) as f64 * 72.0, ring_lat)
+            } else {
+                ((v - 6) as f64 * 72.0 + 36.0, -ring_lat)
+            };
+            let lon = lon.to_radians();
+            [lat.cos() * lon.cos(), lat.cos() * lon.sin(), lat.sin()]
+        }
+    })
+}
+
+/// The 20 faces as corner indices into [`icosahedron`].
+const ICOSAHEDRON_FACES: [[usize; 3]; 20] = [
+    [0, 1, 2],
+    [0, 2, 3],
+    [0, 3, 4],
+    [0, 4, 5],
+    [0, 5, 1],
+    [1, 6, 2],
+    [2, 7, 3],
+    [3, 8, 4],
+    [4, 9, 5],
+    [5, 10, 1],
+    [2, 6, 7],
+    [3, 7, 8],
+    [4, 8, 9],
+    [5, 9, 10],
+    [1, 10, 6],
+    [11, 7, 6],
+    [11, 8, 7],
+    [11, 9, 8],
+    [11, 10, 9],
+    [11, 6, 10],
+];
+
+fn dot(a: [f64; 3], b: [f64; 3]) -> f64 {
+    a[0] * b[0] + a[1] * b[1] + a[2] * b[2]
+}
+
+fn sub(a: [f64; 3], b: [f64; 3]) -> [f64; 3] {
+    [a[0] - b[0], a[1] - b[1], a[2] - b[2]]
+}
+
+fn scale(a: [f64; 3], s: f64) -> [f64; 3] {
+    a.map(|c| c * s)
+}
+
+fn cross(a: [f64; 3], b: [f64; 3]) -> [f64; 3] {
+    [
+        a[1] * b[2] - a[2] * b[1],
+        a[2] * b[0] - a[0] * b[2],
+        a[0] * b[1] - a[1] * b[0],
+    ]
+}
+
+fn normalize(a: [f64; 3]) -> [f64; 3] {
+    scale(a, 1.0 / dot(a, a).sqrt())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cell_count_follows_the_frequency() {
+        for f in 1..=8 {
+            let cells = Geodesic::new(f);
+            assert_eq!(
+                cells.centres.len(),
+                (10 * f * f + 2) as usize,
+                "frequency {f}"
+            );
+            assert_eq!(cells.neighbors.len(), cells.centres.len());
+            for p in &cells.centres {
+                assert!((dot(*p, *p) - 1.0).abs() < 1e-12);
+            }
+        }
+    }
+
+    #[test]
+    fn twelve_pentagons_and_the_rest_hexagons() {
+        let cells = Geodesic::new(6);
+        for (i, ring) in cells.neighbors.iter().enumerate() {
+            assert_eq!(ring.len(), if i < 12 { 5 } else { 6 }, "cell {i}");
+            for &j in ring {
+                assert!(cells.neighbors[j].contains(&i), "{j} does not see {i}");
+            }
+        }
+        // One pentagon at each pole.
+        assert!(cells.centres[..12].iter().any(|p| p[2] > 1.0 - 1e-12));
+        assert!(cells.centres[..12].iter().any(|p| p[2] < -1.0 + 1e-12));
+    }
+
+    #[test]
+    fn rings_run_counter_clockwise_and_cells_contain_their_centres() {
+        let cells = Geodesic::new(5);
+        for (i, &p) in cells.centres.iter().enumerate() {
+            let ring = &cells.neighbors[i];
+            for k in 0..ring.len() {
+                let a = sub(cells.centres[ring[k]], p);
+                let b = sub(cells.centres[ring[(k + 1) % ring.len()]], p);
+                assert!(dot(cross(a, b), p) > 0.0, "cell {i} turns clockwise at {k}");
+            }
+            assert_eq!(cells.locate(p), i);
+        }
+    }
+}
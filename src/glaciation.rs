@@ -0,0 +1,269 @@
+/// Ice-sheet simulation.
+///
+/// Replaces the old "cold tile ⇒ `IceCap`" recolour with a small mass-balance
+/// model run over the whole grid:
+///
+///  1. **Mass balance** — ice accumulates where the climate is below freezing,
+///     in proportion to available moisture, and ablates (melts) elsewhere.
+///     Ice over open water calves away unless the sea itself is frozen.
+///  2. **Flow** — each step, ice slides from high ice surfaces
+///     (bedrock + thickness) towards lower neighbours, spreading sheets outward.
+///  3. **Isostasy** — the bedrock under the ice is pushed down by a fraction of
+///     the ice load.
+///  4. **Margin features** — where a sheet ends on land it drops a moraine
+///     ridge; where it met the coast in a valley it carves a flooded fjord.
+use crate::grid::Grid;
+
+/// Effective temperature below which snowfall survives the summer.
+/// Matches the old `IceCap` threshold used by biome selection.
+pub const FREEZING_T: f32 = 0.15;
+/// Minimum thickness (elevation units) for a tile to count as ice-covered.
+pub const ICE_SHEET_MIN: f32 = 0.02;
+
+/// Number of mass-balance + flow iterations.
+const STEPS: u32 = 40;
+/// Ice gained per step on a saturated, freezing tile.
+const ACCUMULATION: f32 = 0.012;
+/// Ice lost per step per unit of temperature above freezing.
+const ABLATION: f32 = 0.08;
+/// Extra loss per step for ice floating over water that is not frozen solid.
+const CALVING: f32 = 0.02;
+/// Temperatures this far below freezing keep floating ice shelves intact.
+const SHELF_MARGIN: f32 = 0.10;
+/// Fraction of the surface-slope difference moved to a neighbour each step.
+const FLOW_RATE: f32 = 0.12;
+/// Bedrock depression per unit of ice thickness (≈ ρ_ice / ρ_mantle).
+const ISOSTATIC_RATIO: f32 = 0.28;
+/// Height of the debris ridge left at a land-terminating ice margin.
+const MORAINE_HEIGHT: f32 = 0.03;
+/// Depth below sea level of a carved fjord floor.
+const FJORD_DEPTH: f32 = 0.08;
+
+/// Landform left behind by the ice sheet on a tile.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum GlacialFeature {
+    /// No glacial influence.
+    None,
+    /// Covered by an ice sheet or floating ice shelf.
+    Sheet,
+    /// Terminal/lateral moraine ridge at a land-terminating margin.
+    Moraine,
+    /// Glacially carved coastal valley, drowned below sea level.
+    Fjord,
+}
+
+/// Result of [`simulate`]: per-tile ice thickness and margin landforms.
+pub struct Glaciation {
+    /// Ice thickness in elevation units (same scale as `Tile::elevation`).
+    pub thickness: Vec<f32>,
+    pub feature: Vec<GlacialFeature>,
+}
+
+/// Runs the ice-sheet model and modifies `elevation` in place
+/// (isostatic depression, moraine ridges, fjord carving).
+///
+/// - `elevation`   raw elevation in `[-1, 1]`, column-major
+/// - `temperature` effective temperature in `[0, 1]` (planet offsets applied)
+/// - `moisture`    effective moisture in `[-1, 1]` (planet offsets applied)
+/// - `sea_level`   waterline in elevation units
+pub fn simulate(
+    grid: &Grid,
+    elevation: &mut [f32],
+    temperature: &[f32],
+    moisture: &[f32],
+    sea_level: f32,
+) -> Glaciation {
+    let n = grid.tile_count();
+    let mut thickness = vec![0.0f32; n];
+    let mut flux = vec![0.0f32; n];
+
+    // Per-step mass balance never changes, so compute it once.
+    let balance: Vec<f32> = (0..n)
+        .map(|i| {
+            let t = temperature[i];
+            let wet = (moisture[i] + 1.0) * 0.5;
+            let mut b = if t < FREEZING_T {
+                ACCUMULATION * wet
+            } else {
+                -ABLATION * (t - FREEZING_T)
+            };
+            if elevation[i] < sea_level && t > FREEZING_T - SHELF_MARGIN {
+                b -= CALVING;
+            }
+            b
+        })
+        .collect();
+
+    for _ in 0..STEPS {
+        for i in 0..n {
+            thickness[i] = (thickness[i] + balance[i]).max(0.0);
+        }
+
+        // Outflow towards each lower neighbour, proportional to the surface
+        // drop, scaled down so a tile never ships more ice than it holds.
+        flux.fill(0.0);
+        for i in 0..n {
+            let h = thickness[i];
+            if h <= 0.0 {
+                continue;
+            }
+            let surface = elevation[i] + h;
+            // Up to four neighbours, or six on a hex grid.
+            let mut drops = [(0usize, 0.0f32); 6];
+            let mut count = 0;
+            for j in grid.neighbors4(i) {
+                let d = surface - (elevation[j] + thickness[j]);
+                if d > 0.0 {
+                    drops[count] = (j, d * FLOW_RATE);
+                    count += 1;
+                }
+            }
+            let total: f32 = drops[..count].iter().map(|&(_, d)| d).sum();
+            if total <= 0.0 {
+                continue;
+            }
+            let scale = (h / total).min(1.0);
+            for &(j, d) in &drops[..count] {
+                let moved = d * scale;
+                flux[i] -= moved;
+                flux[j] += moved;
+            }
+        }
+        for i in 0..n {
+            thickness[i] = (thickness[i] + flux[i]).max(0.0);
+        }
+    }
+
+    // Isostatic depression of the bedrock under the final ice load.
+    for i in 0..n {
+        elevation[i] = (elevation[i] - thickness[i] * ISOSTATIC_RATIO).clamp(-1.0, 1.0);
+    }
+
+    let mut feature: Vec<GlacialFeature> = thickness
+        .iter()
+        .map(|&h| {
+            if h >= ICE_SHEET_MIN {
+                GlacialFeature::Sheet
+            } else {
+                GlacialFeature::None
+            }
+        })
+        .collect();
+
+    // Margin landforms: ice-free tiles bordering a sheet.  Checks read the
+    // pre-carving bedrock so one fjord does not make its neighbour "coastal".
+    let bedrock = elevation.to_vec();
+    for i in 0..n {
+        if feature[i] != GlacialFeature::None || bedrock[i] < sea_level {
+            continue;
+        }
+        let touches_ice = grid.neighbors4(i).any(|j| thickness[j] >= ICE_SHEET_MIN);
+        if !touches_ice {
+            continue;
+        }
+        let coastal = grid.neighbors4(i).any(|j| bedrock[j] < sea_level);
+        let (sum, count) = grid
+            .neighbors8(i)
+            .fold((0.0, 0), |(s, c), j| (s + bedrock[j], c + 1));
+        let in_valley = count > 0 && bedrock[i] < sum / count as f32;
+
+        if coastal && in_valley {
+            elevation[i] = (sea_level - FJORD_DEPTH).max(-1.0);
+            feature[i] = GlacialFeature::Fjord;
+        } else {
+            elevation[i] = (elevation[i] + MORAINE_HEIGHT).min(1.0);
+            feature[i] = GlacialFeature::Moraine;
+        }
+    }
+
+    Glaciation { thickness, feature }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Land at `0.3` everywhere, freezing within `cold_rows` of either pole
+    /// and warm elsewhere.
+    fn polar_world(grid: &Grid, cold_rows: i32) -> (Vec<f32>, Vec<f32>, Vec<f32>) {
+        let n = grid.tile_count();
+        let temperature = (0..n)
+            .map(|i| {
+                let r = grid.coords(i).1;
+                if r < cold_rows || r >= grid.height - cold_rows {
+                    0.0
+                } else {
+                    0.8
+                }
+            })
+            .collect();
+        (vec![0.3; n], temperature, vec![0.5; n])
+    }
+
+    #[test]
+    fn ice_grows_at_cold_poles_and_depresses_the_bedrock() {
+        let grid = Grid::new(32, 16);
+        let (mut elevation, temperature, moisture) = polar_world(&grid, 3);
+        let ice = simulate(&grid, &mut elevation, &temperature, &moisture, 0.0);
+        for (i, &e) in elevation.iter().enumerate() {
+            let r = grid.coords(i).1;
+            let (h, feature) = (ice.thickness[i], ice.feature[i]);
+            if r < 2 || r >= grid.height - 2 {
+                assert!(
+                    h >= ICE_SHEET_MIN && feature == GlacialFeature::Sheet,
+                    "row {r}: {h}"
+                );
+            }
+            if (6..grid.height - 6).contains(&r) {
+                assert_eq!(h, 0.0, "row {r}");
+                assert_eq!(e, 0.3);
+            }
+            if feature == GlacialFeature::Sheet {
+                assert!((e - (0.3 - h * ISOSTATIC_RATIO)).abs() < 1e-6);
+            }
+        }
+    }
+
+    #[test]
+    fn sheets_ending_on_land_leave_moraines() {
+        let grid = Grid::new(32, 16);
+        let (mut elevation, temperature, moisture) = polar_world(&grid, 3);
+        let ice = simulate(&grid, &mut elevation, &temperature, &moisture, 0.0);
+        let moraines: Vec<usize> = (0..grid.tile_count())
+            .filter(|&i| ice.feature[i] == GlacialFeature::Moraine)
+            .collect();
+        // A ridge along each margin, all the way round.
+        assert_eq!(moraines.len(), 2 * grid.width as usize, "{moraines:?}");
+        for &i in &moraines {
+            assert!(ice.thickness[i] < ICE_SHEET_MIN);
+            assert!(
+                grid.neighbors4(i)
+                    .any(|j| ice.feature[j] == GlacialFeature::Sheet)
+            );
+            let bedrock = 0.3 - ice.thickness[i] * ISOSTATIC_RATIO;
+            assert!((elevation[i] - (bedrock + MORAINE_HEIGHT)).abs() < 1e-6);
+        }
+    }
+
+    #[test]
+    fn coastal_valleys_at_the_margin_become_fjords() {
+        let grid = Grid::new(32, 16);
+        let (mut elevation, temperature, moisture) = polar_world(&grid, 3);
+        // Sea east of column 16, with a valley cut into the coast at the
+        // first ice-free row of the south sheet.
+        for (i, e) in elevation.iter_mut().enumerate() {
+            let (q, r) = grid.coords(i);
+            if q >= 16 {
+                *e = -0.3;
+            } else if q == 15 && r == 3 {
+                *e = 0.02;
+            }
+        }
+        let ice = simulate(&grid, &mut elevation, &temperature, &moisture, 0.0);
+        let valley = grid.index(15, 3);
+        assert!(ice.feature[valley] == GlacialFeature::Fjord);
+        assert!((elevation[valley] - (0.0 - FJORD_DEPTH)).abs() < 1e-6);
+        // Its inland neighbour is not coastal and stays a moraine.
+        assert!(ice.feature[grid.index(14, 3)] == GlacialFeature::Moraine);
+    }
+}
@@ -0,0 +1,602 @@
+/// Neighbourhood and coordinate helpers for the tile grid.
+///
+/// Tiles are stored column-major (`index = q * height + r`), matching the
+/// order in which `generate_world` emits them.  The equirectangular grid
+/// wraps east–west (longitude is periodic) but not north–south (the poles are
+/// hard edges).  The cube-sphere grid stores its six faces side by side and
+/// has no edges at all: neighbours step across face seams.  Hex grids are
+/// stored by offset column and row, but their tiles are addressed in axial
+/// coordinates.  The geodesic grid is a single row of cells whose neighbours
+/// come from [`Geodesic`] rather than from coordinates.
+use crate::geodesic::Geodesic;
+use serde::Serialize;
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashSet};
+use std::f64::consts::{FRAC_PI_4, PI};
+use std::sync::Arc;
+
+/// How tiles cover the sphere.
+#[derive(Clone, Copy, Serialize, PartialEq, Eq, Debug)]
+pub enum Layout {
+    /// Longitude/latitude raster: `width` columns from longitude 0°, `height`
+    /// rows from the south pole.
+    Equirectangular,
+    /// Equiangular cube-sphere: six `N × N` faces side by side, so `width = 6N`
+    /// and `height = N`.  Tile `(q, r)` is cell `(q mod N, r)` of face `q / N`.
+    CubeSphere,
+    /// Hexes laid out over longitude/latitude like the equirectangular grid,
+    /// in axial coordinates `(q, r)`.  `width` is the number of hex columns
+    /// around the planet and `height` the number of rows from pole to pole.
+    Hex(HexOrientation),
+    /// Goldberg polyhedron of 12 pentagons and hexagons, from an icosahedron
+    /// with every edge split into this many segments (see [`Geodesic`]).
+    /// `width` is the number of cells, `height` is 1, and tile `(q, 0)` is
+    /// cell `q`.
+    Geodesic(u32),
+}
+
+/// Which way hexes point.
+#[derive(Clone, Copy, Serialize, PartialEq, Eq, Debug)]
+pub enum HexOrientation {
+    /// A vertex at the top: rows run east–west, odd rows shifted half a hex east.
+    Pointy,
+    /// An edge at the top: columns run north–south, odd columns shifted half a
+    /// hex north.
+    Flat,
+}
+
+/// Axial offsets of the six neighbours of a hex, counter-clockwise from east
+/// (pointy) or from north-east (flat).
+pub const HEX_DIRECTIONS: [(i32, i32); 6] = [(1, 0), (1, -1), (0, -1), (-1, 0), (-1, 1), (0, 1)];
+
+/// One face of the cube-sphere grid.  Cell `(x, y)` runs along `right` and
+/// `up`; `cross` is the face's `(column, row)` in the unfolded 4 × 3 cross.
+pub struct CubeFace {
+    pub name: &'static str,
+    pub normal: [f64; 3],
+    pub right: [f64; 3],
+    pub up: [f64; 3],
+    pub cross: (i32, i32),
+}
+
+/// Faces in storage order.  Axes follow [`unit_vector`]: `x` points at 0°,
+/// `y` at 90° E and `z` at the north pole.  The four equatorial faces have
+/// north up and run west to east; the polar faces touch the 0° face.
+pub const CUBE_FACES: [CubeFace; 6] = [
+    CubeFace {
+        name: "px",
+        normal: [1.0, 0.0, 0.0],
+        right: [0.0, 1.0, 0.0],
+        up: [0.0, 0.0, 1.0],
+        cross: (1, 1),
+    },
+    CubeFace {
+        name: "py",
+        normal: [0.0, 1.0, 0.0],
+        right: [-1.0, 0.0, 0.0],
+        up: [0.0, 0.0, 1.0],
+        cross: (2, 1),
+    },
+    CubeFace {
+        name: "nx",
+        normal: [-1.0, 0.0, 0.0],
+        right: [0.0, -1.0, 0.0],
+        up: [0.0, 0.0, 1.0],
+        cross: (3, 1),
+    },
+    CubeFace {
+        name: "ny",
+        normal: [0.0, -1.0, 0.0],
+        right: [1.0, 0.0, 0.0],
+        up: [0.0, 0.0, 1.0],
+        cross: (0, 1),
+    },
+    CubeFace {
+        name: "pz",
+        normal: [0.0, 0.0, 1.0],
+        right: [0.0, 1.0, 0.0],
+        up: [-1.0, 0.0, 0.0],
+        cross: (1, 0),
+    },
+    CubeFace {
+        name: "nz",
+        normal: [0.0, 0.0, -1.0],
+        right: [0.0, 1.0, 0.0],
+        up: [1.0, 0.0, 0.0],
+        cross: (1, 2),
+    },
+];
+
+#[derive(Clone)]
+pub struct Grid {
+    pub width: i32,
+    pub height: i32,
+    pub layout: Layout,
+    /// Cells of a [`Layout::Geodesic`] grid; `None` on the other layouts.
+    pub geodesic: Option<Arc<Geodesic>>,
+}
+
+impl Grid {
+    /// Equirectangular grid of `width × height` tiles.
+    pub fn new(width: i32, height: i32) -> Self {
+        Grid {
+            width,
+            height,
+            layout: Layout::Equirectangular,
+            geodesic: None,
+        }
+    }
+
+    /// Cube-sphere grid of six `face_size × face_size` faces.
+    pub fn cube_sphere(face_size: i32) -> Self {
+        Grid {
+            width: 6 * face_size,
+            height: face_size,
+            layout: Layout::CubeSphere,
+            geodesic: None,
+        }
+    }
+
+    /// Hex grid of `columns` hexes around the planet, with as many rows as
+    /// keep the hexes regular on the map.  Flat-top grids need an even number
+    /// of columns to wrap, so odd counts are rounded up.
+    pub fn hex(orientation: HexOrientation, columns: i32) -> Self {
+        let (width, height) = match orientation {
+            HexOrientation::Pointy => (columns, columns as f64 / 3f64.sqrt()),
+            HexOrientation::Flat => {
+                let columns = columns + columns % 2;
+                (columns, columns as f64 * 3f64.sqrt() / 4.0)
+            }
+        };
+        Grid {
+            width,
+            height: (height.round() as i32).max(1),
+            layout: Layout::Hex(orientation),
+            geodesic: None,
+        }
+    }
+
+    /// Geodesic grid from an icosahedron with each edge split into
+    /// `frequency` segments: `10 × frequency² + 2` cells.
+    pub fn geodesic(frequency: u32) -> Self {
+        let cells = Geodesic::new(frequency);
+        Grid {
+            width: cells.centres.len() as i32,
+            height: 1,
+            layout: Layout::Geodesic(cells.frequency),
+            geodesic: Some(Arc::new(cells)),
+        }
+    }
+
+    /// Cells of a geodesic grid.
+    fn cells(&self) -> &Geodesic {
+        self.geodesic
+            .as_deref()
+            .expect("geodesic grid without cells")
+    }
+
+    /// Total number of tiles.
+    pub fn tile_count(&self) -> usize {
+        (self.width * self.height) as usize
+    }
+
+    /// Flat index of tile `(q, r)`.  `q` is wrapped, `r` must be in range.
+    pub fn index(&self, q: i32, r: i32) -> usize {
+        let (col, row) = self.offset(q, r);
+        (col.rem_euclid(self.width) * self.height + row) as usize
+    }
+
+    /// `(q, r)` coordinates of the tile at flat index `i`.
+    pub fn coords(&self, i: usize) -> (i32, i32) {
+        let i = i as i32;
+        let (col, row) = (i / self.height, i % self.height);
+        match self.layout {
+            Layout::Hex(HexOrientation::Pointy) => (col - row.div_euclid(2), row),
+            Layout::Hex(HexOrientation::Flat) => (col, row - col.div_euclid(2)),
+            _ => (col, row),
+        }
+    }
+
+    /// Storage column and row of tile `(q, r)`: the offset coordinates of an
+    /// axial hex, unchanged on the other layouts.  The column is not wrapped.
+    pub fn offset(&self, q: i32, r: i32) -> (i32, i32) {
+        match self.layout {
+            Layout::Hex(HexOrientation::Pointy) => (q + r.div_euclid(2), r),
+            Layout::Hex(HexOrientation::Flat) => (q, r + q.div_euclid(2)),
+            _ => (q, r),
+        }
+    }
+
+    /// Axial coordinates of the hexes around `(q, r)`, wrapped east–west.
+    /// Hexes in the first and last rows have no neighbours beyond the poles.
+    pub fn hex_neighbors(&self, q: i32, r: i32) -> impl Iterator<Item = (i32, i32)> + '_ {
+        self.neighbors4(self.index(q, r)).map(|j| self.coords(j))
+    }
+
+    /// Longitude and latitude (radians) of the centre of tile `(q, r)`.  On
+    /// the equirectangular grid longitude runs `0..2π` across `q` and latitude
+    /// `-π/2..π/2` across `r` (row 0 borders the south pole), so tile `(q, r)`
+    /// is pixel `(q, r)` of a raster covering the whole sphere.  Longitudes
+    /// are in `[0, 2π)` on every layout.
+    pub fn lon_lat(&self, q: i32, r: i32) -> (f64, f64) {
+        match self.layout {
+            Layout::Equirectangular => {
+                let lon = ((q as f64 + 0.5) / self.width as f64) * 2.0 * PI;
+                let lat = ((r as f64 + 0.5) / self.height as f64) * PI - PI / 2.0;
+                (lon, lat)
+            }
+            Layout::CubeSphere => {
+                let n = self.height;
+                let cell = |c: i32| (c as f64 + 0.5) / n as f64 * 2.0 - 1.0;
+                let [x, y, z] = cube_point((q / n) as usize, cell(q % n), cell(r));
+                (y.atan2(x).rem_euclid(2.0 * PI), z.asin())
+            }
+            Layout::Hex(orientation) => {
+                let (col, row) = self.offset(q, r);
+                let col = col.rem_euclid(self.width);
+                // Centres in columns and rows; shifted lines sit half a step over.
+                let (x, y) = match orientation {
+                    HexOrientation::Pointy => {
+                        (col as f64 + 0.5 * (row % 2) as f64, row as f64 + 0.5)
+                    }
+                    HexOrientation::Flat => {
+                        (col as f64, row as f64 + 0.25 + 0.5 * (col % 2) as f64)
+                    }
+                };
+                let lon = x / self.width as f64 * 2.0 * PI;
+                let lat = y / self.height as f64 * PI - PI / 2.0;
+                (lon, lat)
+            }
+            Layout::Geodesic(_) => {
+                let [x, y, z] = self.cells().centres[q as usize];
+                (y.atan2(x).rem_euclid(2.0 * PI), z.asin())
+            }
+        }
+    }
+
+    /// Tile containing the point at `(lon, lat)` radians (inverse of [`Grid::lon_lat`]).
+    pub fn tile_at(&self, lon: f64, lat: f64) -> (i32, i32) {
+        match self.layout {
+            Layout::Equirectangular => {
+                let q = (lon / (2.0 * PI) * self.width as f64).floor() as i32;
+                let r = ((lat + PI / 2.0) / PI * self.height as f64).floor() as i32;
+                (q.rem_euclid(self.width), r.clamp(0, self.height - 1))
+            }
+            Layout::CubeSphere => self.cube_tile(unit_vector(lon, lat)),
+            Layout::Hex(_) => {
+                // The nearest centre among the hexes around the estimated cell.
+                let p = unit_vector(lon, lat);
+                let col = (lon / (2.0 * PI) * self.width as f64).round() as i32;
+                let row = ((lat + PI / 2.0) / PI * self.height as f64).floor() as i32;
+                let nearest = (col - 1..=col + 1)
+                    .flat_map(|c| (row - 1..=row + 1).map(move |r| (c, r)))
+                    .filter(|&(_, r)| r >= 0 && r < self.height)
+                    .map(|(c, r)| (c.rem_euclid(self.width) * self.height + r) as usize)
+                    .min_by(|&a, &b| {
+                        angle_between(p, self.centre(a))
+                            .total_cmp(&angle_between(p, self.centre(b)))
+                    })
+                    .unwrap();
+                self.coords(nearest)
+            }
+            Layout::Geodesic(_) => (self.cells().locate(unit_vector(lon, lat)) as i32, 0),
+        }
+    }
+
+    /// Point on the unit sphere at the centre of tile `i`.
+    pub fn centre(&self, i: usize) -> [f64; 3] {
+        if let Some(cells) = &self.geodesic {
+            return cells.centres[i];
+        }
+        let (q, r) = self.coords(i);
+        let (lon, lat) = self.lon_lat(q, r);
+        unit_vector(lon, lat)
+    }
+
+    /// Tiles per radian of great-circle distance, near enough everywhere
+    /// (measured along a meridian on the equirectangular grid).
+    pub fn tiles_per_radian(&self) -> f64 {
+        match self.layout {
+            Layout::Equirectangular => self.height as f64 / PI,
+            Layout::CubeSphere => self.height as f64 / (PI / 2.0),
+            Layout::Hex(_) => self.height as f64 / PI,
+            // Neighbouring cells are about an icosahedron edge / frequency apart.
+            Layout::Geodesic(frequency) => frequency as f64 / 2f64.atan(),
+        }
+    }
+
+    /// Every tile whose centre lies within `radius` radians (great-circle
+    /// distance) of `(lon, lat)`, paired with that distance.
+    pub fn tiles_within(&self, lon: f64, lat: f64, radius: f64) -> Vec<(usize, f64)> {
+        let (q0, r0) = self.tile_at(lon, lat);
+        if self.layout != Layout::Equirectangular {
+            return self.flood_within(self.index(q0, r0), unit_vector(lon, lat), radius);
+        }
+        let dr = (radius / PI * self.height as f64).ceil() as i32 + 1;
+        let dq = (radius / (2.0 * PI) * self.width as f64 / lat.cos().max(0.05)).ceil() as i32 + 1;
+        let dq = dq.min(self.width / 2);
+        let centre = unit_vector(lon, lat);
+
+        let mut out = Vec::new();
+        for r in (r0 - dr).max(0)..=(r0 + dr).min(self.height - 1) {
+            for q in (q0 - dq)..=(q0 + dq) {
+                let (tl, tp) = self.lon_lat(q, r);
+                let d = angle_between(centre, unit_vector(tl, tp));
+                if d <= radius {
+                    out.push((self.index(q, r), d));
+                }
+            }
+        }
+        out
+    }
+
+    /// The 4-connected neighbours of tile `i`, wrapping east–west.  On hex
+    /// grids both this and [`Grid::neighbors8`] give the six hexes around `i`,
+    /// and on geodesic grids the five or six cells around it.
+    pub fn neighbors4(&self, i: usize) -> impl Iterator<Item = usize> + '_ {
+        self.neighbors(i, &[(-1, 0), (1, 0), (0, -1), (0, 1)])
+    }
+
+    /// The 8-connected neighbours of tile `i`, wrapping east–west.
+    pub fn neighbors8(&self, i: usize) -> impl Iterator<Item = usize> + '_ {
+        self.neighbors(
+            i,
+            &[
+                (-1, -1),
+                (-1, 0),
+                (-1, 1),
+                (0, -1),
+                (0, 1),
+                (1, -1),
+                (1, 0),
+                (1, 1),
+            ],
+        )
+    }
+
+    /// Distinct tiles at `offsets` from tile `i` (the hex directions on hex
+    /// grids).  Cube-sphere corners have only seven 8-neighbours, so two
+    /// offsets can land on the same tile.
+    fn neighbors(&self, i: usize, offsets: &[(i32, i32)]) -> impl Iterator<Item = usize> + '_ {
+        let (q, r) = self.coords(i);
+        let offsets = match self.layout {
+            Layout::Hex(_) => &HEX_DIRECTIONS[..],
+            _ => offsets,
+        };
+        let mut out = [0usize; 8];
+        let mut count = 0;
+        if let Some(cells) = &self.geodesic {
+            for &j in &cells.neighbors[i] {
+                out[count] = j;
+                count += 1;
+            }
+            return out.into_iter().take(count);
+        }
+        for &(dq, dr) in offsets {
+            let j = match self.layout {
+                Layout::Equirectangular if r + dr < 0 || r + dr >= self.height => continue,
+                Layout::Equirectangular => self.index(q + dq, r + dr),
+                Layout::CubeSphere => self.cube_step(q, r, dq, dr),
+                Layout::Hex(_) => {
+                    let row = self.offset(q + dq, r + dr).1;
+                    if row < 0 || row >= self.height {
+                        continue;
+                    }
+                    self.index(q + dq, r + dr)
+                }
+                Layout::Geodesic(_) => unreachable!("geodesic neighbours come from its cells"),
+            };
+            if j != i && !out[..count].contains(&j) {
+                out[count] = j;
+                count += 1;
+            }
+        }
+        out.into_iter().take(count)
+    }
+
+    /// Cube-sphere tile one `(dx, dy)` step from `(q, r)`.  Off the face, the
+    /// step lands just across the seam, where both faces measure position
+    /// along the shared edge alike.
+    fn cube_step(&self, q: i32, r: i32, dx: i32, dy: i32) -> usize {
+        let n = self.height;
+        let (face, x, y) = (q / n, q % n + dx, r + dy);
+        if (0..n).contains(&x) && (0..n).contains(&y) {
+            return self.index(face * n + x, y);
+        }
+        let cell = |c: i32| match c {
+            c if c < 0 => -1.0 - 1e-9,
+            c if c >= n => 1.0 + 1e-9,
+            c => (c as f64 + 0.5) / n as f64 * 2.0 - 1.0,
+        };
+        let (q, r) = self.cube_tile(cube_point(face as usize, cell(x), cell(y)));
+        self.index(q, r)
+    }
+
+    /// Cube-sphere tile containing the direction `p`.
+    fn cube_tile(&self, p: [f64; 3]) -> (i32, i32) {
+        let n = self.height;
+        let face = (0..6)
+            .max_by(|&a, &b| dot(p, CUBE_FACES[a].normal).total_cmp(&dot(p, CUBE_FACES[b].normal)))
+            .unwrap();
+        let CubeFace {
+            normal, right, up, ..
+        } = CUBE_FACES[face];
+        let d = dot(p, normal);
+        // Plane coordinates → equiangular cell coordinates in [-1, 1].
+        let cell = |t: f64| {
+            let c = (t / d).atan() / FRAC_PI_4;
+            (((c + 1.0) / 2.0 * n as f64).floor() as i32).clamp(0, n - 1)
+        };
+        (face as i32 * n + cell(dot(p, right)), cell(dot(p, up)))
+    }
+
+    /// Breadth-first search from tile `start` for every tile whose centre lies
+    /// within `radius` of `centre`.
+    fn flood_within(&self, start: usize, centre: [f64; 3], radius: f64) -> Vec<(usize, f64)> {
+        let mut out = Vec::new();
+        let mut seen = HashSet::from([start]);
+        let mut queue = vec![start];
+        while let Some(i) = queue.pop() {
+            let d = angle_between(centre, self.centre(i));
+            if d <= radius {
+                out.push((i, d));
+            } else if i != start {
+                continue;
+            }
+            for j in self.neighbors8(i) {
+                if seen.insert(j) {
+                    queue.push(j);
+                }
+            }
+        }
+        out
+    }
+
+    /// Great-circle distance (radians) from every tile to the nearest source
+    /// tile, measured along 8-connected grid steps, plus the index of that
+    /// nearest source.  Tiles unreachable from any source get `f64::INFINITY`
+    /// and `usize::MAX`.
+    pub fn distance_from(&self, is_source: impl Fn(usize) -> bool) -> (Vec<f64>, Vec<usize>) {
+        let n = self.tile_count();
+        let mut dist = vec![f64::INFINITY; n];
+        let mut nearest = vec![usize::MAX; n];
+        // Non-negative f64 bit patterns sort like the values they encode.
+        let mut heap = BinaryHeap::new();
+        for i in (0..n).filter(|&i| is_source(i)) {
+            dist[i] = 0.0;
+            nearest[i] = i;
+            heap.push(Reverse((0u64, i)));
+        }
+
+        let dy = PI / self.height as f64;
+        while let Some(Reverse((bits, i))) = heap.pop() {
+            let d = f64::from_bits(bits);
+            if d > dist[i] {
+                continue;
+            }
+            let (q, r) = self.coords(i);
+            let (_, lat) = self.lon_lat(q, r);
+            let dx = 2.0 * PI / self.width as f64 * lat.cos().max(1e-3);
+            for j in self.neighbors8(i) {
+                let (nq, nr) = self.coords(j);
+                let step = match ((nq - q).rem_euclid(self.width) != 0, nr != r) {
+                    _ if self.layout != Layout::Equirectangular => {
+                        angle_between(self.centre(i), self.centre(j))
+                    }
+                    (true, true) => dx.hypot(dy),
+                    (true, false) => dx,
+                    _ => dy,
+                };
+                let nd = d + step;
+                if nd < dist[j] {
+                    dist[j] = nd;
+                    nearest[j] = nearest[i];
+                    heap.push(Reverse((nd.to_bits(), j)));
+                }
+            }
+        }
+        (dist, nearest)
+    }
+}
+
+/// Point on the unit sphere for `(lon, lat)` radians — the same mapping
+/// `generate_world` feeds to the noise samplers.
+pub fn unit_vector(lon: f64, lat: f64) -> [f64; 3] {
+    [lat.cos() * lon.cos(), lat.cos() * lon.sin(), lat.sin()]
+}
+
+/// Unit vector through equiangular coordinates `(a, b)` of cube face `face`;
+/// `±1` are the face edges, and values beyond them extend the face.
+fn cube_point(face: usize, a: f64, b: f64) -> [f64; 3] {
+    let CubeFace {
+        normal, right, up, ..
+    } = CUBE_FACES[face];
+    let (u, v) = ((a * FRAC_PI_4).tan(), (b * FRAC_PI_4).tan());
+    let p: [f64; 3] = std::array::from_fn(|k| normal[k] + u * right[k] + v * up[k]);
+    let len = dot(p, p).sqrt();
+    p.map(|c| c / len)
+}
+
+fn dot(a: [f64; 3], b: [f64; 3]) -> f64 {
+    a[0] * b[0] + a[1] * b[1] + a[2] * b[2]
+}
+
+/// Great-circle angle (radians) between two unit vectors.
+pub fn angle_between(a: [f64; 3], b: [f64; 3]) -> f64 {
+    let dot = a[0] * b[0] + a[1] * b[1] + a[2] * b[2];
+    dot.clamp(-1.0, 1.0).acos()
+}
+
+/// Point reached by travelling `distance` radians from `(lon, lat)` along the
+/// initial `bearing` (radians clockwise from north).
+pub fn destination(lon: f64, lat: f64, bearing: f64, distance: f64) -> (f64, f64) {
+    let lat2 = (lat.sin() * distance.cos() + lat.cos() * distance.sin() * bearing.cos()).asin();
+    let lon2 = lon
+        + (bearing.sin() * distance.sin() * lat.cos())
+            .atan2(distance.cos() - lat.sin() * lat2.sin());
+    (lon2.rem_euclid(2.0 * PI), lat2)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hex_neighbours_wrap_across_the_seam() {
+        for orientation in [HexOrientation::Pointy, HexOrientation::Flat] {
+            let grid = Grid::hex(orientation, 48);
+            let (w, h) = (grid.width, grid.height);
+            let mut across_seam = 0;
+            for i in 0..grid.tile_count() {
+                let (q, r) = grid.coords(i);
+                let (col, row) = grid.offset(q, r);
+                let neighbours: Vec<usize> = grid
+                    .hex_neighbors(q, r)
+                    .map(|(q, r)| grid.index(q, r))
+                    .collect();
+                let mut distinct = neighbours.clone();
+                distinct.sort();
+                distinct.dedup();
+                assert_eq!(
+                    distinct.len(),
+                    neighbours.len(),
+                    "{orientation:?} {col},{row}"
+                );
+                if (1..h - 1).contains(&row) {
+                    assert_eq!(neighbours.len(), 6, "{orientation:?} {col},{row}");
+                }
+
+                let (lon, lat) = grid.lon_lat(q, r);
+                for &j in &neighbours {
+                    let (nq, nr) = grid.coords(j);
+                    assert!(
+                        grid.hex_neighbors(nq, nr)
+                            .any(|(a, b)| grid.index(a, b) == i),
+                        "{orientation:?}: {j} does not see {i}"
+                    );
+                    // Hexes are regular on the map, so every neighbour is one
+                    // hex across away, measured the short way round.
+                    let (nlon, nlat) = grid.lon_lat(nq, nr);
+                    let dlon = (nlon - lon + PI).rem_euclid(2.0 * PI) - PI;
+                    let step = match orientation {
+                        HexOrientation::Pointy => 2.0 * PI / w as f64,
+                        HexOrientation::Flat => 4.0 * PI / (3f64.sqrt() * w as f64),
+                    };
+                    let d = dlon.hypot(nlat - lat);
+                    assert!(
+                        (d / step - 1.0).abs() < 0.03,
+                        "{orientation:?} {col},{row}: {d} vs {step}"
+                    );
+                    let ncol = grid.offset(nq, nr).0.rem_euclid(w);
+                    if (col, ncol) == (0, w - 1) {
+                        across_seam += 1;
+                    }
+                }
+            }
+            assert!(
+                across_seam >= h,
+                "{orientation:?}: only {across_seam} links across the seam"
+            );
+        }
+    }
+}
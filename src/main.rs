@@ -1,32 +1,141 @@
+mod bathymetry;
 mod biome;
+mod ecotone;
 mod export;
 mod generation;
+mod geodesic;
+mod glaciation;
+mod grid;
 mod noise;
+mod server;
+mod units;
+mod volcanism;
 mod world;
 
-use export::{export_json, export_legend_png, export_noise_maps, export_png, export_svg};
+use export::{
+    DEFAULT_THRESHOLD, HeightRange, HeightmapOptions, MeshOptions, NormalSpace, PAGE_SIZES,
+    PALETTES, PageSize, PosterOptions, Projection, RenderMode, RenderOptions, RenderStyle,
+    StlRegion, SvgMode, SvgOptions, THEMES, Theme, TileOptions, TiledOptions,
+    export_ambient_occlusion, export_bathymetry, export_cube, export_engine_bundle,
+    export_geodesic_json, export_geodesic_png, export_geojson, export_geotiff, export_heightmap,
+    export_hex_json, export_hex_png, export_hex_svg, export_json, export_legend_png, export_mesh,
+    export_noise_maps, export_normal_map, export_png, export_poster, export_stl, export_svg,
+    export_tiled, export_tiles, palette,
+};
 use generation::generate_world;
+use grid::{Grid, HexOrientation};
 use rand::RngExt;
-use world::PlanetType;
+use std::sync::Arc;
+use world::{Layout, PlanetType, biome_name};
 
 fn main() {
     let mut rng = rand::rng();
 
-    // Parse optional named arguments:
+    // Usage: map-generator [serve | check-palette] [options]
+    //
+    // `serve` browses the world through a local tile server instead of writing
+    // files.  `check-palette` reports biome colours that colour-blind viewers
+    // may not tell apart under the chosen theme and palette, and exits.
+    // Optional named arguments:
     //   --planet    terran | volcanic | frozen | caustic | barren
     //   --sea-level <f32>          (default: random -0.3 .. 0.5)
     //   --volcanic  <f32>          (default: random 0.0 .. 1.0)
+    //   --circumference <f32>      (default: random 20 000 .. 80 000 km)
+    //   --stellar-flux  <f32>      (default: 1.0, Earth's insolation)
+    //   --grid      equirectangular | cube | hex | geodesic  (default: equirectangular)
+    //   --face-size <u32>          (cube face edge in tiles, default: 512)
+    //   --hex       pointy | flat  (hex orientation, default: pointy)
+    //   --hex-columns <u32>        (hexes around the planet, default: 256)
+    //   --subdivisions <u32>       (geodesic segments per icosahedron edge, default: 64)
+    //   --render    flat | blend   (default: flat)
+    //   --projection equirectangular | mollweide | robinson | orthographic
+    //               | polar-north | polar-south | cube  (world.png/svg, default: equirectangular)
+    //   --centre    <lon,lat>      (orthographic view centre in degrees, default: 0,0)
+    //   --graticule <degrees>      (labelled graticule on world.png/svg at this spacing)
+    //   --scale-bar                (scale bar on world.png/svg)
+    //   --north-arrow              (north arrow on world.png/svg)
+    //   --theme     classic | atlas | satellite | monochrome | hypsometric | <file.json>
+    //                              (palette and ink work, default: classic)
+    //   --palette   standard | red-green | blue-yellow  (biome colours, default: the theme's)
+    //   --threshold <f32>          (check-palette: smallest ΔE2000 allowed, default: 8)
+    //   --style     contours | hillshade  (relief on world.png, default: the theme's)
+    //   --sun       <azimuth,altitude>    (hillshade sun in degrees, default: 315,45)
+    //   --ocean-tint               (colour oceans by depth)
+    //   --bathymetry               (also write a 16-bit water-depth raster)
+    //   --svg       rects | vector (default: rects)
+    //   --simplify  <f64>          (vector SVG/GeoJSON tolerance in tiles, default: 1.0)
+    //   --geojson                  (also write GeoJSON layers for web maps)
+    //   --heightmap                (also write a 16-bit heightmap)
+    //   --height-range auto | fixed (default: auto)
+    //   --flatten-oceans           (heightmap: clamp water to sea level)
+    //   --geotiff                  (also write georeferenced GeoTIFF rasters)
+    //   --engine                   (also write a Godot / Unity terrain bundle)
+    //   --mesh                     (also write the 3-D globe as glTF and OBJ, with normal and AO maps)
+    //   --exaggeration <f32>       (3-D relief exaggeration, default: 40)
+    //   --normal-space tangent | object  (globe normal map frame, default: tangent)
+    //   --stl                      (also write a printable globe STL)
+    //   --stl-region <lon_min,lat_min,lon_max,lat_max>  (--stl: degrees, also print a slab)
+    //   --seed      <u32>          (default: random)
+    //   --tiles     <zoom>         (also write a tile pyramid down to this zoom)
+    //   --tiled     <columns>      (also write a Tiled map this many tiles wide)
+    //   --poster    a4 | a3 | a2 | a1 | letter | tabloid  (also write a print poster)
+    //   --dpi       <u32>          (poster resolution, default: 150)
+    //   --insets                   (poster: add temperature and precipitation maps)
+    //   --port      <u16>          (tile server port, default: 8080)
     //
     // Any omitted parameter is chosen randomly.
     let mut planet_arg: Option<String> = None;
     let mut sea_level_arg: Option<f32> = None;
     let mut volcanic_arg: Option<f32> = None;
     let mut circumference_arg: Option<f32> = None;
+    let mut stellar_flux_arg: Option<f32> = None;
+    let mut grid_arg: Option<String> = None;
+    let mut face_size_arg: Option<i32> = None;
+    let mut hex_arg: Option<String> = None;
+    let mut hex_columns_arg: Option<i32> = None;
+    let mut subdivisions_arg: Option<u32> = None;
+    let mut render_arg: Option<String> = None;
+    let mut projection_arg: Option<String> = None;
+    let mut centre_arg: Option<(f64, f64)> = None;
+    let mut graticule_arg: Option<f64> = None;
+    let mut scale_bar = false;
+    let mut north_arrow = false;
+    let mut theme_arg: Option<String> = None;
+    let mut palette_arg: Option<String> = None;
+    let mut threshold_arg: Option<f32> = None;
+    let mut style_arg: Option<String> = None;
+    let mut sun_arg: Option<(f64, f64)> = None;
+    let mut ocean_tint = false;
+    let mut bathymetry = false;
+    let mut svg_arg: Option<String> = None;
+    let mut simplify_arg: Option<f64> = None;
+    let mut geojson = false;
+    let mut heightmap_export = false;
+    let mut height_range_arg: Option<String> = None;
+    let mut flatten_oceans = false;
+    let mut geotiff = false;
+    let mut engine = false;
+    let mut mesh_export = false;
+    let mut exaggeration_arg: Option<f32> = None;
+    let mut normal_space_arg: Option<String> = None;
+    let mut stl = false;
+    let mut stl_region_arg: Option<String> = None;
+    let mut seed_arg: Option<u32> = None;
+    let mut tiles_arg: Option<u32> = None;
+    let mut tiled_arg: Option<u32> = None;
+    let mut poster_arg: Option<String> = None;
+    let mut dpi_arg: Option<u32> = None;
+    let mut insets = false;
+    let mut port_arg: Option<u16> = None;
+    let mut serve = false;
+    let mut check_palette = false;
 
     let args: Vec<String> = std::env::args().skip(1).collect();
     let mut idx = 0;
     while idx < args.len() {
         match args[idx].as_str() {
+            "serve" if idx == 0 => serve = true,
+            "check-palette" if idx == 0 => check_palette = true,
             "--planet" => {
                 idx += 1;
                 planet_arg = args.get(idx).cloned();
@@ -43,6 +152,138 @@ fn main() {
                 idx += 1;
                 circumference_arg = args.get(idx).and_then(|v| v.parse().ok());
             }
+            "--stellar-flux" => {
+                idx += 1;
+                stellar_flux_arg = args.get(idx).and_then(|v| v.parse().ok());
+            }
+            "--grid" => {
+                idx += 1;
+                grid_arg = args.get(idx).cloned();
+            }
+            "--face-size" => {
+                idx += 1;
+                face_size_arg = args.get(idx).and_then(|v| v.parse().ok());
+            }
+            "--hex" => {
+                idx += 1;
+                hex_arg = args.get(idx).cloned();
+            }
+            "--hex-columns" => {
+                idx += 1;
+                hex_columns_arg = args.get(idx).and_then(|v| v.parse().ok());
+            }
+            "--subdivisions" => {
+                idx += 1;
+                subdivisions_arg = args.get(idx).and_then(|v| v.parse().ok());
+            }
+            "--render" => {
+                idx += 1;
+                render_arg = args.get(idx).cloned();
+            }
+            "--projection" => {
+                idx += 1;
+                projection_arg = args.get(idx).cloned();
+            }
+            "--centre" => {
+                idx += 1;
+                centre_arg = args.get(idx).and_then(|v| parse_centre(v));
+                if centre_arg.is_none() {
+                    eprintln!("warning: --centre expects lon,lat");
+                }
+            }
+            "--graticule" => {
+                idx += 1;
+                graticule_arg = args
+                    .get(idx)
+                    .and_then(|v| v.parse().ok())
+                    .filter(|&d: &f64| d > 0.0 && d <= 90.0);
+                if graticule_arg.is_none() {
+                    eprintln!("warning: --graticule expects a spacing in degrees, 0 < d <= 90");
+                }
+            }
+            "--scale-bar" => scale_bar = true,
+            "--north-arrow" => north_arrow = true,
+            "--theme" => {
+                idx += 1;
+                theme_arg = args.get(idx).cloned();
+            }
+            "--palette" => {
+                idx += 1;
+                palette_arg = args.get(idx).cloned();
+            }
+            "--threshold" => {
+                idx += 1;
+                threshold_arg = args.get(idx).and_then(|v| v.parse().ok());
+            }
+            "--style" => {
+                idx += 1;
+                style_arg = args.get(idx).cloned();
+            }
+            "--sun" => {
+                idx += 1;
+                sun_arg = args.get(idx).and_then(|v| parse_sun(v));
+                if sun_arg.is_none() {
+                    eprintln!("warning: --sun expects azimuth,altitude");
+                }
+            }
+            "--ocean-tint" => ocean_tint = true,
+            "--svg" => {
+                idx += 1;
+                svg_arg = args.get(idx).cloned();
+            }
+            "--simplify" => {
+                idx += 1;
+                simplify_arg = args.get(idx).and_then(|v| v.parse().ok());
+            }
+            "--bathymetry" => bathymetry = true,
+            "--geojson" => geojson = true,
+            "--heightmap" => heightmap_export = true,
+            "--height-range" => {
+                idx += 1;
+                height_range_arg = args.get(idx).cloned();
+            }
+            "--flatten-oceans" => flatten_oceans = true,
+            "--geotiff" => geotiff = true,
+            "--engine" => engine = true,
+            "--mesh" => mesh_export = true,
+            "--exaggeration" => {
+                idx += 1;
+                exaggeration_arg = args.get(idx).and_then(|v| v.parse().ok());
+            }
+            "--normal-space" => {
+                idx += 1;
+                normal_space_arg = args.get(idx).cloned();
+            }
+            "--stl" => stl = true,
+            "--stl-region" => {
+                idx += 1;
+                stl_region_arg = args.get(idx).cloned();
+            }
+            "--seed" => {
+                idx += 1;
+                seed_arg = args.get(idx).and_then(|v| v.parse().ok());
+            }
+            "--tiles" => {
+                idx += 1;
+                tiles_arg = args.get(idx).and_then(|v| v.parse().ok());
+            }
+            "--poster" => {
+                idx += 1;
+                poster_arg = args.get(idx).cloned();
+            }
+            "--dpi" => {
+                idx += 1;
+                dpi_arg = args.get(idx).and_then(|v| v.parse().ok());
+            }
+            "--insets" => insets = true,
+            "--tiled" => {
+                idx += 1;
+                tiled_arg = args.get(idx).and_then(|v| v.parse().ok());
+            }
+            "--port" => {
+                idx += 1;
+                port_arg = args.get(idx).and_then(|v| v.parse().ok());
+            }
             other => eprintln!("warning: unknown argument '{other}' — ignored"),
         }
         idx += 1;
@@ -61,40 +302,324 @@ fn main() {
         None => random_planet(&mut rng),
     };
 
+    let hex_orientation = match hex_arg.as_deref() {
+        Some("pointy") | None => HexOrientation::Pointy,
+        Some("flat") => HexOrientation::Flat,
+        Some(other) => {
+            eprintln!("warning: unknown hex orientation '{other}', using pointy");
+            HexOrientation::Pointy
+        }
+    };
+    let grid = match grid_arg.as_deref() {
+        Some("equirectangular") | None => Grid::new(1920, 1080),
+        Some("cube") => Grid::cube_sphere(face_size_arg.unwrap_or(512).max(1)),
+        Some("hex") => Grid::hex(hex_orientation, hex_columns_arg.unwrap_or(256).max(2)),
+        Some("geodesic") => Grid::geodesic(subdivisions_arg.unwrap_or(64).clamp(1, 4096)),
+        Some(other) => {
+            eprintln!("warning: unknown grid '{other}', using equirectangular");
+            Grid::new(1920, 1080)
+        }
+    };
+
+    // Rendering is cosmetic, so it keeps a fixed default instead of a random pick.
+    let render_mode = match render_arg.as_deref() {
+        Some("flat") | None => RenderMode::Flat,
+        Some("blend") => RenderMode::Blend,
+        Some(other) => {
+            eprintln!("warning: unknown render mode '{other}', using flat");
+            RenderMode::Flat
+        }
+    };
+    let (lon, lat) = centre_arg.unwrap_or((0.0, 0.0));
+    let projection = match projection_arg.as_deref() {
+        Some("equirectangular") | None => Projection::Equirectangular,
+        Some("mollweide") => Projection::Mollweide,
+        Some("robinson") => Projection::Robinson,
+        Some("orthographic") => Projection::Orthographic { lon, lat },
+        Some("polar-north") => Projection::PolarNorth,
+        Some("polar-south") => Projection::PolarSouth,
+        Some("cube") => Projection::Cube,
+        Some(other) => {
+            eprintln!("warning: unknown projection '{other}', using equirectangular");
+            Projection::Equirectangular
+        }
+    };
+    let mut theme = match theme_arg.as_deref() {
+        None => Theme::default(),
+        Some(name) => Theme::named(name).unwrap_or_else(|| load_theme(name)),
+    };
+    if let Some(name) = palette_arg.as_deref() {
+        match palette::palette(name) {
+            Some(colors) => theme.biome_colors = colors,
+            None => eprintln!(
+                "warning: unknown palette '{name}' (expected one of {}), using the theme's",
+                PALETTES.join(", ")
+            ),
+        }
+    }
+    if check_palette {
+        let threshold = threshold_arg.unwrap_or(DEFAULT_THRESHOLD);
+        let palette = palette_arg
+            .as_deref()
+            .filter(|name| PALETTES.contains(name));
+        let clean = report_palette(&theme, palette, threshold);
+        std::process::exit(if clean { 0 } else { 1 });
+    }
+    let style = match style_arg.as_deref() {
+        Some("contours") => RenderStyle::Contours,
+        Some("hillshade") => RenderStyle::Hillshade,
+        None => theme.style,
+        Some(other) => {
+            eprintln!("warning: unknown render style '{other}', using the theme's");
+            theme.style
+        }
+    };
+    let (sun_azimuth, sun_altitude) = sun_arg.unwrap_or((315.0, 45.0));
+    let render = RenderOptions {
+        mode: render_mode,
+        style,
+        sun_azimuth,
+        sun_altitude,
+        ocean_tint: ocean_tint || theme.ocean_tint,
+        theme: Arc::new(theme),
+        projection,
+        graticule: graticule_arg,
+        scale_bar,
+        north_arrow,
+    };
+    let mut svg = SvgOptions::default();
+    match svg_arg.as_deref() {
+        Some("rects") | None => {}
+        Some("vector") if projection != Projection::Equirectangular => {
+            eprintln!("warning: vector SVG is equirectangular only, using rects");
+        }
+        Some("vector") => svg.mode = SvgMode::Vector,
+        Some(other) => eprintln!("warning: unknown SVG mode '{other}', using rects"),
+    }
+    if let Some(tolerance) = simplify_arg {
+        svg.simplify = tolerance.max(0.0);
+    }
+    let height_range = match height_range_arg.as_deref() {
+        Some("auto") | None => HeightRange::Auto,
+        Some("fixed") => HeightRange::Fixed,
+        Some(other) => {
+            eprintln!("warning: unknown height range '{other}', using auto");
+            HeightRange::Auto
+        }
+    };
+    let heightmap = HeightmapOptions {
+        range: height_range,
+        flatten_oceans,
+    };
+    let mut mesh = MeshOptions::default();
+    if let Some(exaggeration) = exaggeration_arg {
+        mesh.exaggeration = exaggeration.max(0.0);
+    }
+    match normal_space_arg.as_deref() {
+        Some("tangent") | None => {}
+        Some("object") => mesh.normal_space = NormalSpace::Object,
+        Some(other) => eprintln!("warning: unknown normal space '{other}', using tangent"),
+    }
+
+    let poster = poster_arg.as_deref().map(|name| {
+        let page = PageSize::named(name).unwrap_or_else(|| {
+            eprintln!(
+                "warning: unknown page size '{name}' (expected one of {}), using a3",
+                PAGE_SIZES.join(", ")
+            );
+            PageSize::A3
+        });
+        PosterOptions {
+            page,
+            dpi: dpi_arg
+                .unwrap_or(PosterOptions::default().dpi)
+                .clamp(72, 600),
+            insets,
+        }
+    });
+
+    let stl_region: Option<StlRegion> = match stl_region_arg.as_deref() {
+        Some(_) if !stl => {
+            eprintln!("warning: --stl-region needs --stl, ignored");
+            None
+        }
+        Some(v) => {
+            let region = parse_region(v);
+            if region.is_none() {
+                eprintln!("warning: --stl-region expects lon_min,lat_min,lon_max,lat_max");
+            }
+            region
+        }
+        None => None,
+    };
+
+    let seed = seed_arg.unwrap_or_else(rand::random);
     let sea_level = sea_level_arg.unwrap_or_else(|| rng.random_range(-0.30_f32..0.50));
     let volcanic_intensity = volcanic_arg.unwrap_or_else(|| rng.random_range(0.00_f32..1.00));
     // Default: random planet in the range of small rocky worlds to super-Earths.
     // Earth ≈ 40 075 km.  Range 20 000–80 000 km covers sub-Earth to ~2× Earth.
     let circumference_km =
         circumference_arg.unwrap_or_else(|| rng.random_range(20_000.0_f32..80_000.0));
+    // Not randomised: biome selection works on the normalised climate, so the
+    // flux only calibrates absolute temperatures.  Earth ≡ 1.0.
+    let stellar_flux = stellar_flux_arg.unwrap_or(1.0).max(0.0);
 
     // Gravity mirrors the formula in generate_world — printed before generation
     // so the user sees it even without inspecting the JSON output.
     let gravity_preview = circumference_km / 40_075.0_f32;
     println!(
-        "Parameters → planet={planet_type:?}  sea_level={sea_level:.2}  volcanic_intensity={volcanic_intensity:.2}  circumference={circumference_km:.0} km  gravity≈{gravity_preview:.2}g"
+        "Parameters → planet={planet_type:?}  seed={seed}  sea_level={sea_level:.2}  volcanic_intensity={volcanic_intensity:.2}  circumference={circumference_km:.0} km  gravity≈{gravity_preview:.2}g  stellar_flux={stellar_flux:.2}"
     );
 
     let world = generate_world(
-        1920,
-        1080,
-        rand::random(),
+        grid,
+        seed,
         sea_level,
         volcanic_intensity,
         planet_type,
         circumference_km,
+        stellar_flux,
     );
 
+    if world.layout != Layout::Equirectangular && projection != Projection::Equirectangular {
+        eprintln!("warning: --projection applies to the equirectangular grid only, ignored");
+    }
+    if world.layout != Layout::Equirectangular
+        && (render.graticule.is_some() || render.scale_bar || render.north_arrow)
+    {
+        eprintln!(
+            "warning: --graticule, --scale-bar and --north-arrow apply to the equirectangular grid only, ignored"
+        );
+    }
+    if world.layout != Layout::Equirectangular && poster.is_some() {
+        eprintln!("warning: --poster applies to the equirectangular grid only, ignored");
+    }
+    if world.layout != Layout::Equirectangular {
+        let ignored: Vec<&str> = [
+            ("--svg", svg_arg.is_some()),
+            ("--simplify", simplify_arg.is_some()),
+            ("--bathymetry", bathymetry),
+            ("--geojson", geojson),
+            ("--heightmap", heightmap_export),
+            ("--height-range", height_range_arg.is_some()),
+            ("--flatten-oceans", flatten_oceans),
+            ("--geotiff", geotiff),
+            ("--engine", engine),
+            ("--mesh", mesh_export),
+            ("--exaggeration", exaggeration_arg.is_some()),
+            ("--normal-space", normal_space_arg.is_some()),
+            ("--stl", stl),
+            ("--stl-region", stl_region_arg.is_some()),
+            ("--tiles", tiles_arg.is_some()),
+            ("--tiled", tiled_arg.is_some()),
+        ]
+        .into_iter()
+        .filter_map(|(flag, given)| given.then_some(flag))
+        .collect();
+        match ignored.split_last() {
+            Some((flag, [])) => {
+                eprintln!("warning: {flag} applies to the equirectangular grid only, ignored")
+            }
+            Some((last, rest)) => eprintln!(
+                "warning: {} and {last} apply to the equirectangular grid only, ignored",
+                rest.join(", ")
+            ),
+            None => {}
+        }
+    }
+    if serve && world.layout != Layout::Equirectangular {
+        eprintln!("error: serve needs the equirectangular grid");
+        return;
+    }
+    if serve {
+        server::serve(&world, &render, port_arg.unwrap_or(8080));
+        return;
+    }
+
     let dir = format!("worlds/{}-{}", planet_type, world.seed);
-    let noise_dir = format!("{}/noise_maps", dir);
     let raw_dir = format!("{}/raw_data", dir);
+    // The other exporters read the equirectangular raster.
+    if world.layout != Layout::Equirectangular {
+        std::fs::create_dir_all(&raw_dir).expect("failed to create raw_data directory");
+        let (maps, note) = match world.layout {
+            Layout::Hex(_) => {
+                export_hex_png(&world, &render, &format!("{}/world.png", dir));
+                export_hex_svg(&world, &render, &format!("{}/world.svg", dir));
+                export_hex_json(&world, &format!("{}/hex.json", raw_dir));
+                ("world.png, world.svg", "")
+            }
+            Layout::Geodesic(_) => {
+                export_geodesic_png(&world, &render, &format!("{}/world.png", dir));
+                export_geodesic_json(&world, &format!("{}/geodesic.json", raw_dir));
+                ("world.png", ", which has no SVG")
+            }
+            _ => {
+                export_cube(&world, &render, &dir);
+                ("world.png, faces", ", which has no SVG")
+            }
+        };
+        export_legend_png(&world, &render, &format!("{}/legend.png", dir));
+        export_json(&world, &format!("{}/world.json", raw_dir));
+        println!(
+            "World generated → {}/ ({maps}, legend and JSON only on this grid{note})",
+            dir
+        );
+        return;
+    }
+    let noise_dir = format!("{}/noise_maps", dir);
+    let geotiff_dir = format!("{}/geotiff", raw_dir);
+    let mesh_dir = format!("{}/mesh", dir);
+    let geojson_dir = format!("{}/geojson", dir);
     std::fs::create_dir_all(&noise_dir).expect("failed to create noise_maps directory");
     std::fs::create_dir_all(&raw_dir).expect("failed to create raw_data directory");
+    if geotiff {
+        std::fs::create_dir_all(&geotiff_dir).expect("failed to create geotiff directory");
+    }
+    if mesh_export || stl {
+        std::fs::create_dir_all(&mesh_dir).expect("failed to create mesh directory");
+    }
+    if geojson {
+        std::fs::create_dir_all(&geojson_dir).expect("failed to create geojson directory");
+    }
 
-    export_png(&world, &format!("{}/world.png", dir));
-    export_legend_png(&world, &format!("{}/legend.png", dir));
+    export_png(&world, &render, &format!("{}/world.png", dir));
+    export_legend_png(&world, &render, &format!("{}/legend.png", dir));
     export_json(&world, &format!("{}/world.json", raw_dir));
-    export_svg(&world, &format!("{}/world.svg", dir));
+    if bathymetry {
+        export_bathymetry(&world, &format!("{}/bathymetry.png", raw_dir));
+    }
+    if heightmap_export {
+        export_heightmap(&world, &heightmap, &format!("{}/heightmap", raw_dir));
+    }
+    if geotiff {
+        export_geotiff(&world, &geotiff_dir);
+    }
+    if engine {
+        export_engine_bundle(&world, &heightmap, &format!("{}/engine", dir));
+    }
+    export_svg(&world, &render, &svg, &format!("{}/world.svg", dir));
+    if geojson {
+        export_geojson(&world, svg.simplify, &geojson_dir);
+    }
+    if mesh_export {
+        export_mesh(&world, &mesh, &render, &mesh_dir);
+        export_normal_map(&world, &mesh, &format!("{}/globe_normal.png", mesh_dir));
+        export_ambient_occlusion(&world, &mesh, &format!("{}/globe_ao.png", mesh_dir));
+    }
+    if stl {
+        export_stl(&world, &mesh, stl_region, &mesh_dir);
+    }
+    if let Some(max_zoom) = tiles_arg {
+        let tiles = TileOptions { max_zoom };
+        export_tiles(&world, &render, &tiles, &format!("{}/tiles", dir));
+    }
+    if let Some(columns) = tiled_arg {
+        let tiled = TiledOptions { columns };
+        export_tiled(&world, &tiled, &format!("{}/tiled", dir));
+    }
+    if let Some(poster) = &poster {
+        export_poster(&world, &render, poster, &dir);
+    }
     export_noise_maps(
         world.width,
         world.height,
@@ -118,3 +643,74 @@ fn random_planet(rng: &mut impl rand::RngExt) -> PlanetType {
         _ => PlanetType::Barren,
     }
 }
+
+/// Parses `lon,lat` (degrees), clamping the latitude to the poles.
+fn parse_centre(s: &str) -> Option<(f64, f64)> {
+    let (lon, lat) = s.split_once(',')?;
+    let lon: f64 = lon.trim().parse().ok()?;
+    let lat: f64 = lat.trim().parse().ok()?;
+    Some((lon, lat.clamp(-90.0, 90.0)))
+}
+
+/// Prints the pairs of biome colours closer than `threshold` for each kind of
+/// colour vision; `true` if there are none.
+fn report_palette(theme: &Theme, palette: Option<&str>, threshold: f32) -> bool {
+    let clashes = palette::check(theme, threshold);
+    println!(
+        "Theme {}, palette {}: biome pairs closer than ΔE2000 {threshold}",
+        theme.name,
+        palette.unwrap_or("of the theme")
+    );
+    for vision in palette::VISIONS {
+        let found: Vec<_> = clashes.iter().filter(|c| c.vision == vision).collect();
+        println!("  {vision}: {}", found.len());
+        for c in found {
+            println!(
+                "    {:<14} {:<14} {:5.1}",
+                biome_name(c.a),
+                biome_name(c.b),
+                c.delta_e
+            );
+        }
+    }
+    clashes.is_empty()
+}
+
+/// Reads a theme file, falling back to the classic theme if it cannot be used.
+fn load_theme(path: &str) -> Theme {
+    let theme = std::fs::read_to_string(path)
+        .map_err(|e| e.to_string())
+        .and_then(|text| Theme::from_json(&text));
+    theme.unwrap_or_else(|e| {
+        eprintln!(
+            "warning: theme '{path}' is neither one of {} nor a usable theme file ({e}), using classic",
+            THEMES.join(", ")
+        );
+        Theme::default()
+    })
+}
+
+/// Parses `azimuth,altitude` (degrees), clamping the altitude to the sky.
+fn parse_sun(s: &str) -> Option<(f64, f64)> {
+    let (azimuth, altitude) = s.split_once(',')?;
+    let azimuth: f64 = azimuth.trim().parse().ok()?;
+    let altitude: f64 = altitude.trim().parse().ok()?;
+    Some((azimuth.rem_euclid(360.0), altitude.clamp(0.0, 90.0)))
+}
+
+/// Parses `lon_min,lat_min,lon_max,lat_max` (degrees).
+fn parse_region(s: &str) -> Option<StlRegion> {
+    let v: Vec<f64> = s
+        .split(',')
+        .map(|x| x.trim().parse().ok())
+        .collect::<Option<_>>()?;
+    let [lon_min, lat_min, lon_max, lat_max] = v[..] else {
+        return None;
+    };
+    Some(StlRegion {
+        lon_min,
+        lat_min,
+        lon_max,
+        lat_max,
+    })
+}
@@ -28,6 +28,25 @@ pub fn fbm(noise: &Perlin, x: f64, y: f64, z: f64, octaves: u32) -> f32 {
     (value / max_value) as f32
 }
 
+/// The octaves an [`fbm`] series of `octaves` would gain by running
+/// `extra` octaves longer, normalised like `fbm(.., octaves)`.
+///
+/// `fbm(.., octaves) + fbm_detail(.., octaves, extra)` continues the same
+/// field with finer detail, leaving its large-scale shape untouched.
+pub fn fbm_detail(noise: &Perlin, x: f64, y: f64, z: f64, octaves: u32, extra: u32) -> f32 {
+    let max_value = 2.0 - 0.5f64.powi(octaves as i32 - 1);
+    let mut value = 0.0f64;
+    let mut amplitude = 0.5f64.powi(octaves as i32);
+    let mut frequency = 2.0f64.powi(octaves as i32);
+
+    for _ in 0..extra {
+        value += noise.get([x * frequency, y * frequency, z * frequency]) * amplitude;
+        amplitude *= 0.5;
+        frequency *= 2.0;
+    }
+    (value / max_value) as f32
+}
+
 /// Ridged noise — inverts the absolute value to produce sharp mountain peaks
 /// instead of smooth hills.  Returns a value in `[0.0, 1.0]`.
 pub fn ridged(noise: &Perlin, x: f64, y: f64, z: f64) -> f32 {
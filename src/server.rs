@@ -0,0 +1,99 @@
+/// Minimal localhost HTTP server for browsing a world as slippy-map tiles.
+///
+/// Routes:
+///
+/// - `/`                          — Leaflet viewer ([`viewer_html`])
+/// - `/{scheme}/{z}/{x}/{y}.png`  — one tile, rendered on request
+///
+/// Only `GET` is supported and every connection is closed after one
+/// response; that is all a map library needs.
+use crate::export::tiles::MAX_ZOOM;
+use crate::export::{RenderOptions, TileRenderer, TileScheme, viewer_html};
+use crate::world::World;
+use image::ImageFormat;
+use std::io::{Cursor, Read, Write};
+use std::net::{TcpListener, TcpStream};
+
+/// Serves `world` on `127.0.0.1:port` until the process is stopped.
+pub fn serve(world: &World, options: &RenderOptions, port: u16) {
+    let renderer = TileRenderer::new(world, options);
+    let listener = TcpListener::bind(("127.0.0.1", port)).expect("failed to bind tile server port");
+    println!("Serving tiles → http://127.0.0.1:{port}/  (Ctrl-C to stop)");
+
+    // Map libraries request many tiles at once: accept on one thread per core.
+    let workers = std::thread::available_parallelism().map_or(1, |n| n.get());
+    std::thread::scope(|s| {
+        for _ in 0..workers {
+            let listener = listener
+                .try_clone()
+                .expect("failed to share tile server socket");
+            let renderer = &renderer;
+            s.spawn(move || {
+                for stream in listener.incoming().flatten() {
+                    handle(renderer, stream);
+                }
+            });
+        }
+    });
+}
+
+fn handle(renderer: &TileRenderer, mut stream: TcpStream) {
+    // Read the request head; the body (if any) is ignored.
+    let mut head = Vec::new();
+    let mut buf = [0u8; 1024];
+    while !head.windows(4).any(|w| w == b"\r\n\r\n") && head.len() < 8192 {
+        match stream.read(&mut buf) {
+            Ok(0) | Err(_) => break,
+            Ok(n) => head.extend_from_slice(&buf[..n]),
+        }
+    }
+    let head = String::from_utf8_lossy(&head);
+    let mut request = head.lines().next().unwrap_or("").split_whitespace();
+    let (method, path) = (request.next().unwrap_or(""), request.next().unwrap_or(""));
+    // Drop any query string; the viewer reads it client-side.
+    let path = path.split('?').next().unwrap_or("");
+
+    let response = match (method, path) {
+        ("GET", "/" | "/index.html") => Ok((
+            "text/html; charset=utf-8",
+            viewer_html(MAX_ZOOM).into_bytes(),
+        )),
+        ("GET", _) => tile(renderer, path)
+            .map(|png| ("image/png", png))
+            .ok_or("404 Not Found"),
+        _ => Err("405 Method Not Allowed"),
+    };
+    let (status, content_type, body) = match response {
+        Ok((content_type, body)) => ("200 OK", content_type, body),
+        Err(status) => (
+            status,
+            "text/plain; charset=utf-8",
+            status.as_bytes().to_vec(),
+        ),
+    };
+    let header = format!(
+        "HTTP/1.1 {status}\r\nContent-Type: {content_type}\r\nContent-Length: {}\r\nAccess-Control-Allow-Origin: *\r\nConnection: close\r\n\r\n",
+        body.len()
+    );
+    // The client may have gone away (e.g. the map was panned); nothing to do then.
+    let _ = stream
+        .write_all(header.as_bytes())
+        .and_then(|_| stream.write_all(&body));
+}
+
+/// PNG bytes of the tile at `/{scheme}/{z}/{x}/{y}.png`, if the path names one.
+fn tile(renderer: &TileRenderer, path: &str) -> Option<Vec<u8>> {
+    let parts: Vec<&str> = path.trim_start_matches('/').split('/').collect();
+    let [scheme, z, x, y] = parts[..] else {
+        return None;
+    };
+    let scheme = TileScheme::from_name(scheme)?;
+    let z = z.parse().ok()?;
+    let x = x.parse().ok()?;
+    let y = y.strip_suffix(".png")?.parse().ok()?;
+    let img = renderer.render(scheme, z, x, y)?;
+    let mut png = Vec::new();
+    img.write_to(&mut Cursor::new(&mut png), ImageFormat::Png)
+        .expect("failed to encode tile");
+    Some(png)
+}
@@ -0,0 +1,56 @@
+/// Physical unit model.
+///
+/// Generation works on normalised values (elevation `[-1, 1]`, temperature
+/// `[0, 1]`, moisture `[-1, 1]`); this module calibrates them to real units:
+///
+///  - **Elevation** — metres above sea level.  One elevation unit spans
+///    11 000 m on an Earth-gravity planet (deepest trench ≈ Challenger Deep),
+///    divided by `gravity_modifier`: weaker gravity lets relief stand taller.
+///  - **Temperature** — °C.  The normalised climate (planet offset included,
+///    unclamped) maps linearly onto Earth's pole-to-equator range, then the
+///    absolute temperature is scaled by `stellar_flux^¼` (radiative equilibrium).
+///  - **Precipitation** — mm/year, exponential in moisture so deserts and
+///    rainforests land in their real-world ranges.
+use crate::biome::planet_offsets;
+use crate::world::PlanetType;
+
+/// Metres per elevation unit on an Earth-gravity planet.
+pub const EARTH_METRES_PER_UNIT: f32 = 11_000.0;
+/// Mean annual temperature of a polar tile (normalised `0.0`) on Earth, °C.
+const POLE_C: f32 = -45.0;
+/// Mean annual temperature of an equatorial sea-level tile (normalised `1.0`), °C.
+const EQUATOR_C: f32 = 30.0;
+/// Absolute zero offset, °C → K.
+const KELVIN: f32 = 273.15;
+/// Precipitation of the driest possible tile, mm/year.
+const DRIEST_MM: f32 = 25.0;
+/// Exponential growth of precipitation with wetness.
+const PRECIP_GROWTH: f32 = 5.2;
+
+/// Vertical scale of the world: metres per elevation unit.
+pub fn metres_per_unit(gravity_modifier: f32) -> f32 {
+    EARTH_METRES_PER_UNIT / gravity_modifier
+}
+
+/// Height above (positive) or depth below (negative) sea level in metres.
+pub fn elevation_m(elevation: f32, sea_level: f32, metres_per_unit: f32) -> f32 {
+    (elevation - sea_level) * metres_per_unit
+}
+
+/// Mean annual temperature in °C for a tile's raw normalised `temperature`
+/// (latitude gradient + elevation cooling, before planet offsets).
+pub fn temperature_c(temperature: f32, planet_type: PlanetType, stellar_flux: f32) -> f32 {
+    let (dt, _, _) = planet_offsets(planet_type);
+    // Below sea level the raw value keeps warming with depth; the water
+    // surface (at most the sea-level equator, 1.0) is what we report.
+    let earth_c = POLE_C + (EQUATOR_C - POLE_C) * (temperature.min(1.0) + dt);
+    let kelvin = (earth_c + KELVIN).max(0.0) * stellar_flux.max(0.0).powf(0.25);
+    kelvin - KELVIN
+}
+
+/// Annual precipitation in mm for a tile's raw `moisture` (before planet offsets).
+pub fn precipitation_mm(moisture: f32, planet_type: PlanetType) -> f32 {
+    let (_, dm, _) = planet_offsets(planet_type);
+    let wetness = ((moisture + dm).clamp(-1.0, 1.0) + 1.0) * 0.5;
+    DRIEST_MM * (PRECIP_GROWTH * wetness).exp()
+}
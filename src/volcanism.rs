@@ -0,0 +1,362 @@
+/// Discrete volcanoes and lava-flow simulation.
+///
+/// Volcanism is modelled as individual edifices rather than a biome threshold:
+///
+///  - **Hotspots** — fixed mantle plumes under a moving plate.  Each plume
+///    leaves a chain of volcanoes along the plate's track: the newest one sits
+///    over the plume and is active, older ones trail behind, extinct and
+///    subsiding.  In ocean basins the chain surfaces as an island arc.
+///  - **Stratovolcanoes** — placed on the crests of the volcanic mountain
+///    chains selected by the volcanic-zone noise (the world's "arcs").
+///
+/// [`place_volcanoes`] builds each edifice into the elevation grid; after the
+/// terrain is final, [`erupt`] runs lava downhill from every active vent and
+/// returns the `Volcano` / `LavaField` / `AshLand` footprints.
+use crate::grid::{Grid, angle_between, destination, unit_vector};
+use rand::rngs::StdRng;
+use rand::{RngExt, SeedableRng};
+use serde::Serialize;
+use std::f64::consts::PI;
+
+/// Most hotspot plumes a world can have (at full effective intensity).
+const HOTSPOT_MAX: f32 = 6.0;
+/// Number of volcanoes left along a hotspot track.
+const CHAIN_LEN: std::ops::Range<u32> = 3..8;
+/// Angular spacing between successive volcanoes in a chain (radians).
+const CHAIN_SPACING: f64 = 0.06;
+/// Peak height added by the youngest hotspot volcano (elevation units).
+const HOTSPOT_HEIGHT: f32 = 0.55;
+/// Fraction of height lost per step back along the chain (erosion + subsidence).
+const HOTSPOT_DECAY: f32 = 0.12;
+/// Activity multiplier per step back along the chain.
+const ACTIVITY_DECAY: f32 = 0.35;
+/// Gaussian radius of a hotspot shield volcano (radians).
+const HOTSPOT_RADIUS: f64 = 0.035;
+
+/// Most stratovolcanoes a world can have (at full effective intensity).
+const STRATO_MAX: f32 = 36.0;
+/// Minimum effective volcanic zone for a stratovolcano site.
+const STRATO_MIN_ZONE: f32 = 0.30;
+/// Minimum biome elevation for a stratovolcano site (mountain chains).
+const STRATO_MIN_ELEVATION: f32 = 0.30;
+/// Minimum angular distance between two stratovolcanoes (radians).
+const STRATO_SPACING: f64 = 0.08;
+/// Peak height added by a stratovolcano cone (elevation units).
+const STRATO_HEIGHT: f32 = 0.15;
+/// Gaussian radius of a stratovolcano cone (radians).
+const STRATO_RADIUS: f64 = 0.015;
+
+/// Volcanoes below this activity are extinct: no vent, lava or ash.
+const ACTIVE_MIN: f32 = 0.15;
+/// Radius of the summit vent footprint (radians).
+const VENT_RADIUS: f64 = 0.004;
+/// Ash-fall radius of a fully active volcano (radians).
+const ASH_RADIUS: f64 = 0.03;
+/// Most lava flows released by a fully active volcano.
+const MAX_FLOWS: f32 = 12.0;
+/// Maximum flow length of a fully active volcano (radians).
+const LAVA_REACH: f64 = 0.06;
+
+#[derive(Clone, Copy, Serialize, PartialEq, Eq, Debug)]
+pub enum VolcanoKind {
+    /// Shield volcano fed by a mantle plume; forms island chains.
+    Hotspot,
+    /// Steep composite cone on a volcanic mountain chain.
+    Stratovolcano,
+}
+
+/// A single volcanic edifice, exported in `world.json`.
+#[derive(Clone, Serialize)]
+pub struct Volcano {
+    pub kind: VolcanoKind,
+    /// Tile containing the summit vent.
+    pub q: i32,
+    pub r: i32,
+    /// Summit position in degrees: longitude `[0, 360)`, latitude `[-90, 90]`.
+    pub lon_deg: f32,
+    pub lat_deg: f32,
+    /// Summit elevation after the edifice is built (same scale as `Tile::elevation`).
+    pub height: f32,
+    /// `0.0` = extinct, `1.0` = erupting.
+    pub activity: f32,
+}
+
+/// Footprint left on a tile by an eruption.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum VolcanicFeature {
+    None,
+    /// Active summit crater.
+    Vent,
+    /// Solidified lava flow.
+    Lava,
+    /// Ash-fall blanket around an active vent.
+    Ash,
+}
+
+/// Seeds hotspot chains and stratovolcanoes and adds their edifices to
+/// `elevation`.  Returns the volcano list with summit heights filled in.
+///
+/// - `volcanic_zone` effective volcanic zone in `[0, 1]` (planet offsets applied)
+/// - `intensity`     `volcanic_intensity` shifted by the planet's volcanic offset
+pub fn place_volcanoes(
+    grid: &Grid,
+    seed: u32,
+    elevation: &mut [f32],
+    volcanic_zone: &[f32],
+    sea_level: f32,
+    intensity: f32,
+) -> Vec<Volcano> {
+    let mut rng = StdRng::seed_from_u64(seed as u64 + 300);
+    let intensity = intensity.clamp(0.0, 1.0);
+    let mut volcanoes = Vec::new();
+    let mut cones: Vec<(f64, f64, f32, f64)> = Vec::new(); // lon, lat, height, radius
+
+    // ── Hotspot chains ────────────────────────────────────────────────────────
+    let hotspots = (intensity * HOTSPOT_MAX).round() as u32;
+    for _ in 0..hotspots {
+        let lon = rng.random_range(0.0..2.0 * PI);
+        // Uniform on the sphere: sin(lat) uniform; keep plumes off the poles.
+        let lat = rng.random_range(-0.9f64..0.9).asin();
+        // Older volcanoes are carried away from the plume in the plate's direction.
+        let plate_bearing = rng.random_range(0.0..2.0 * PI);
+        let mut activity = rng.random_range(0.6f32..1.0);
+
+        for age in 0..rng.random_range(CHAIN_LEN) {
+            let (vlon, vlat) = destination(lon, lat, plate_bearing, age as f64 * CHAIN_SPACING);
+            let height = HOTSPOT_HEIGHT * (1.0 - age as f32 * HOTSPOT_DECAY);
+            cones.push((vlon, vlat, height, HOTSPOT_RADIUS));
+            volcanoes.push(new_volcano(
+                grid,
+                VolcanoKind::Hotspot,
+                vlon,
+                vlat,
+                activity,
+            ));
+            activity *= ACTIVITY_DECAY;
+        }
+    }
+
+    // ── Stratovolcanoes along volcanic mountain chains ────────────────────────
+    let mut sites: Vec<(usize, f32)> = (0..grid.tile_count())
+        .filter(|&i| {
+            volcanic_zone[i] > STRATO_MIN_ZONE && elevation[i] - sea_level > STRATO_MIN_ELEVATION
+        })
+        .map(|i| (i, volcanic_zone[i] * (elevation[i] - sea_level)))
+        .collect();
+    sites.sort_by(|a, b| b.1.total_cmp(&a.1));
+
+    let strato_max = (intensity * STRATO_MAX).round() as usize;
+    let mut accepted: Vec<[f64; 3]> = Vec::new();
+    for (i, _) in sites {
+        if accepted.len() >= strato_max {
+            break;
+        }
+        let (q, r) = grid.coords(i);
+        let (lon, lat) = grid.lon_lat(q, r);
+        let p = unit_vector(lon, lat);
+        if accepted
+            .iter()
+            .any(|&a| angle_between(a, p) < STRATO_SPACING)
+        {
+            continue;
+        }
+        accepted.push(p);
+        let activity = volcanic_zone[i] * rng.random_range(0.4f32..1.0);
+        cones.push((lon, lat, STRATO_HEIGHT, STRATO_RADIUS));
+        volcanoes.push(new_volcano(
+            grid,
+            VolcanoKind::Stratovolcano,
+            lon,
+            lat,
+            activity,
+        ));
+    }
+
+    // ── Build the edifices ────────────────────────────────────────────────────
+    for &(lon, lat, height, radius) in &cones {
+        for (i, d) in grid.tiles_within(lon, lat, radius * 2.5) {
+            let falloff = (-(d / radius).powi(2)).exp() as f32;
+            elevation[i] = (elevation[i] + height * falloff).min(1.0);
+        }
+    }
+    for v in &mut volcanoes {
+        v.height = elevation[grid.index(v.q, v.r)];
+    }
+
+    volcanoes
+}
+
+/// Runs lava flows from every active vent over the final terrain.
+///
+/// Each flow starts at the summit and steps to a random lower 8-neighbour,
+/// favouring the steepest drop, until it reaches water, a pit, or its maximum
+/// reach.  Returns one [`VolcanicFeature`] per tile; tiles below sea level
+/// are never marked.
+pub fn erupt(
+    grid: &Grid,
+    seed: u32,
+    elevation: &[f32],
+    volcanoes: &[Volcano],
+    sea_level: f32,
+) -> Vec<VolcanicFeature> {
+    let mut rng = StdRng::seed_from_u64(seed as u64 + 301);
+    let mut feature = vec![VolcanicFeature::None; grid.tile_count()];
+    // Flow reach is counted in tile steps.
+    let tiles_per_rad = grid.tiles_per_radian();
+
+    for v in volcanoes.iter().filter(|v| v.activity >= ACTIVE_MIN) {
+        let lon = (v.lon_deg as f64).to_radians();
+        let lat = (v.lat_deg as f64).to_radians();
+        let activity = v.activity as f64;
+
+        // Ash blanket first so lava and the vent overwrite it.
+        for (i, _) in grid.tiles_within(lon, lat, ASH_RADIUS * activity) {
+            if elevation[i] >= sea_level {
+                feature[i] = VolcanicFeature::Ash;
+            }
+        }
+
+        let vent = grid.index(v.q, v.r);
+        let max_steps = (LAVA_REACH * activity * tiles_per_rad).ceil() as u32;
+        let flows = (MAX_FLOWS * v.activity).ceil() as u32;
+        for _ in 0..flows {
+            let mut i = vent;
+            for _ in 0..max_steps {
+                if elevation[i] < sea_level {
+                    break;
+                }
+                feature[i] = VolcanicFeature::Lava;
+
+                let mut next = [(0usize, 0.0f32); 8];
+                let mut count = 0;
+                for j in grid.neighbors8(i) {
+                    let drop = elevation[i] - elevation[j];
+                    if drop > 0.0 {
+                        next[count] = (j, drop);
+                        count += 1;
+                    }
+                }
+                if count == 0 {
+                    break; // lava ponds in a pit
+                }
+                let total: f32 = next[..count].iter().map(|&(_, d)| d).sum();
+                let mut pick = rng.random_range(0.0..total);
+                i = next[count - 1].0;
+                for &(j, d) in &next[..count] {
+                    if pick < d {
+                        i = j;
+                        break;
+                    }
+                    pick -= d;
+                }
+            }
+        }
+
+        // Like the flows and the ash, the vent only marks land: a submarine
+        // vent leaves the seafloor as it is.
+        let summit = grid.tiles_within(lon, lat, VENT_RADIUS);
+        for i in summit.into_iter().map(|(i, _)| i).chain([vent]) {
+            if elevation[i] >= sea_level {
+                feature[i] = VolcanicFeature::Vent;
+            }
+        }
+    }
+
+    feature
+}
+
+fn new_volcano(grid: &Grid, kind: VolcanoKind, lon: f64, lat: f64, activity: f32) -> Volcano {
+    let (q, r) = grid.tile_at(lon, lat);
+    Volcano {
+        kind,
+        q,
+        r,
+        lon_deg: lon.to_degrees() as f32,
+        lat_deg: lat.to_degrees() as f32,
+        height: 0.0,
+        activity,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Sea at `-0.4` with a conical island of `peak` rising from it at
+    /// `(lon, lat)` radians.
+    fn island(grid: &Grid, lon: f64, lat: f64, peak: f32) -> Vec<f32> {
+        let centre = unit_vector(lon, lat);
+        (0..grid.tile_count())
+            .map(|i| {
+                let (q, r) = grid.coords(i);
+                let (tlon, tlat) = grid.lon_lat(q, r);
+                let d = angle_between(centre, unit_vector(tlon, tlat)) as f32;
+                (peak - d * 4.0).max(-0.4)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn eruptions_mark_only_land() {
+        let grid = Grid::new(256, 128);
+        let (lon, lat) = (1.0, 0.3);
+        let elevation = island(&grid, lon, lat, 0.6);
+        let volcano = new_volcano(&grid, VolcanoKind::Stratovolcano, lon, lat, 1.0);
+        let vent = grid.index(volcano.q, volcano.r);
+        let feature = erupt(&grid, 7, &elevation, &[volcano], 0.0);
+
+        assert!(feature[vent] == VolcanicFeature::Vent);
+        assert!(feature.contains(&VolcanicFeature::Lava));
+        assert!(feature.contains(&VolcanicFeature::Ash));
+        for (i, &f) in feature.iter().enumerate() {
+            if f != VolcanicFeature::None {
+                assert!(elevation[i] >= 0.0, "tile {i} under water marked");
+            }
+        }
+    }
+
+    #[test]
+    fn submarine_and_extinct_volcanoes_leave_no_footprint() {
+        let grid = Grid::new(256, 128);
+        let (lon, lat) = (2.0, -0.4);
+        // A seamount whose summit stays below the waterline.
+        let elevation = island(&grid, lon, lat, -0.1);
+        let active = new_volcano(&grid, VolcanoKind::Hotspot, lon, lat, 1.0);
+        let feature = erupt(&grid, 7, &elevation, &[active], 0.0);
+        assert!(feature.iter().all(|&f| f == VolcanicFeature::None));
+
+        let elevation = island(&grid, lon, lat, 0.6);
+        let extinct = new_volcano(&grid, VolcanoKind::Hotspot, lon, lat, ACTIVE_MIN / 2.0);
+        let feature = erupt(&grid, 7, &elevation, &[extinct], 0.0);
+        assert!(feature.iter().all(|&f| f == VolcanicFeature::None));
+    }
+
+    #[test]
+    fn hotspot_chains_fade_along_the_plate_track() {
+        let grid = Grid::new(256, 128);
+        let n = grid.tile_count();
+        let mut elevation = vec![-0.4; n];
+        let none = place_volcanoes(&grid, 3, &mut elevation, &vec![0.0; n], 0.0, 0.0);
+        assert!(none.is_empty());
+        assert!(elevation.iter().all(|&e| e == -0.4));
+
+        let volcanoes = place_volcanoes(&grid, 3, &mut elevation, &vec![0.0; n], 0.0, 1.0);
+        assert!(volcanoes.iter().all(|v| v.kind == VolcanoKind::Hotspot));
+        assert_eq!(
+            volcanoes.iter().filter(|v| v.activity >= 0.6).count(),
+            HOTSPOT_MAX as usize,
+            "one active volcano over each plume"
+        );
+        for pair in volcanoes.windows(2) {
+            // Within a chain activity falls by ACTIVITY_DECAY per step.
+            if pair[1].activity < pair[0].activity {
+                let ratio = pair[1].activity / pair[0].activity;
+                assert!((ratio - ACTIVITY_DECAY).abs() < 1e-5);
+            }
+        }
+        for v in &volcanoes {
+            assert_eq!(v.height, elevation[grid.index(v.q, v.r)]);
+            assert!(v.height > -0.4);
+        }
+    }
+}
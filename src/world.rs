@@ -1,8 +1,13 @@
+use crate::geodesic::Geodesic;
+use crate::grid::Grid;
+use crate::volcanism::Volcano;
 use serde::Serialize;
 use std::fmt;
+use std::sync::Arc;
 
 // Re-export so existing `use crate::world::*;` in other modules keeps working.
-pub use crate::biome::{Biome, biome_color, biome_name, biome_order};
+pub use crate::biome::{BIOMES, Biome, biome_color, biome_name, biome_order};
+pub use crate::grid::Layout;
 
 /// Master planet archetype.  Controls global temperature/moisture offsets and
 /// unlocks planet-specific biomes during biome selection.
@@ -40,13 +45,35 @@ pub struct Tile {
     pub elevation: f32,
     pub moisture: f32,
     pub temperature: f32,
+    /// Height above (positive) or depth below (negative) sea level in metres.
+    pub elevation_m: f32,
+    /// Mean annual temperature in °C (planet offset and stellar flux applied).
+    pub temperature_c: f32,
+    /// Annual precipitation in mm (planet offset applied).
+    pub precipitation_mm: f32,
+    /// Ice-sheet thickness in elevation units (same scale as `elevation`).
+    /// `0.0` = ice-free.  `elevation` is the bedrock surface beneath the ice.
+    pub ice_thickness: f32,
     pub biome: Biome,
+    /// Neighbouring biome this tile is transitioning into (equals `biome`
+    /// away from any border).
+    pub secondary_biome: Biome,
+    /// Weight of `secondary_biome` in `[0, 0.5]`: `0.5` on the border itself,
+    /// `0.0` deep inside `biome`.
+    pub blend: f32,
 }
 
 #[derive(Serialize)]
 pub struct World {
     pub width: i32,
     pub height: i32,
+    /// How the tiles cover the sphere; `width` and `height` are in tiles of
+    /// this layout.
+    pub layout: Layout,
+    /// Cells of a [`Layout::Geodesic`] world with their adjacency, indexed
+    /// like `tiles`; `None` on the other layouts.
+    #[serde(skip)]
+    pub geodesic: Option<Arc<Geodesic>>,
     pub seed: u32,
     /// Master planet archetype driving global temperature/moisture offsets and
     /// unlocking planet-specific biomes.
@@ -68,5 +95,25 @@ pub struct World {
     /// Higher values flatten the landscape (mountains can't stand as tall);
     /// lower values produce more rugged, dramatic terrain.
     pub gravity_modifier: f32,
+    /// Stellar energy received relative to Earth (1.0 = Earth's solar constant).
+    /// Scales absolute temperatures by `stellar_flux^¼`.
+    pub stellar_flux: f32,
+    /// Vertical scale: metres per elevation unit (`11 000 / gravity_modifier`).
+    /// `elevation_m = (elevation - sea_level) × metres_per_unit`.
+    pub metres_per_unit: f32,
+    /// Every hotspot and stratovolcano on the planet, active or extinct.
+    pub volcanoes: Vec<Volcano>,
     pub tiles: Vec<Tile>,
 }
+
+impl World {
+    /// The tile grid the world was generated on.
+    pub fn grid(&self) -> Grid {
+        Grid {
+            width: self.width,
+            height: self.height,
+            layout: self.layout,
+            geodesic: self.geodesic.clone(),
+        }
+    }
+}